@@ -1,5 +1,6 @@
 //! Abstract Syntax Tree (AST) module
-use crate::token::Token;
+use crate::parser::Precedence;
+use crate::token::{Token, TokenType};
 use std::any::Any;
 use std::fmt;
 use std::fmt::Debug;
@@ -11,6 +12,45 @@ use std::fmt::Debug;
 pub trait Node: Any {
     /// Returns the literal value of the token associated with this node
     fn token_literal(&self) -> String;
+    /// Returns the region of source code this node was parsed from
+    fn span(&self) -> Span;
+}
+
+/// A region of source code, from the first character of a node's first
+/// token to the last character of its last token
+///
+/// Positions are (line, column) pairs, both starting at 1. Nodes built by
+/// hand rather than parsed have the default span of (0, 0).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: (usize, usize),
+    pub end: (usize, usize),
+}
+
+impl Span {
+    pub fn new(start: (usize, usize), end: (usize, usize)) -> Self {
+        Span { start, end }
+    }
+
+    /// The span covered by a single token
+    pub fn from_token(token: &Token) -> Self {
+        let width = token.literal.chars().count().saturating_sub(1);
+        Span::new(
+            (token.line, token.column),
+            (token.line, token.column + width),
+        )
+    }
+
+    /// Joins two spans, from the start of this one to the end of `other`
+    pub fn to(self, other: Span) -> Self {
+        Span::new(self.start, other.end)
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.start.0, self.start.1)
+    }
 }
 
 /// Represents a statement in the language
@@ -22,6 +62,8 @@ pub trait Statement: Node + Debug {
     fn statement_node(&self);
     /// Enables downcasting to concrete statement types
     fn as_any(&self) -> &dyn Any;
+    /// For proper Clone
+    fn clone_box(&self) -> Box<dyn Statement>;
 }
 
 /// Represents an expression in the language
@@ -50,6 +92,7 @@ pub struct ReturnStatement {
 
 /// An identifier (e.g., variable names)
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Identifier {
     /// The identifier's token
     pub token: Token,
@@ -68,6 +111,41 @@ pub struct LetStatement {
     pub value: Option<Box<dyn Expression>>,
 }
 
+/// A let statement that unpacks its value into several names
+/// (e.g., "let [a, b] = pair;")
+#[derive(Debug)]
+pub struct DestructureStatement {
+    /// The 'let' token
+    pub token: Token,
+    /// The pattern the value is unpacked by, an ArrayDestructurePattern or a
+    /// HashDestructurePattern
+    pub pattern: Box<dyn Expression>,
+    /// The value being unpacked
+    pub value: Box<dyn Expression>,
+}
+
+/// A const statement (e.g., "const x = 5;")
+#[derive(Debug)]
+pub struct ConstStatement {
+    /// The 'const' token
+    pub token: Token,
+    /// The identifier being bound
+    pub name: Identifier,
+    /// The value being bound
+    pub value: Box<dyn Expression>,
+}
+
+/// An assignment to an existing binding (e.g., "x = 10;")
+#[derive(Debug)]
+pub struct AssignStatement {
+    /// The '=' token
+    pub token: Token,
+    /// The identifier being updated
+    pub target: Identifier,
+    /// The new value
+    pub value: Box<dyn Expression>,
+}
+
 /// The root node of our AST
 #[derive(Debug)]
 pub struct Program {
@@ -112,6 +190,15 @@ pub struct PrefixExpression {
     pub right: Box<dyn Expression>,
 }
 
+/// A typeof expression (e.g., "typeof 5")
+#[derive(Debug)]
+pub struct TypeofExpression {
+    /// The 'typeof' token
+    pub token: Token,
+    /// The expression whose type is taken
+    pub expr: Box<dyn Expression>,
+}
+
 /// An integer literal (e.g., "5", "10")
 #[derive(Debug)]
 pub struct IntegerLiteral {
@@ -121,6 +208,15 @@ pub struct IntegerLiteral {
     pub value: i64,
 }
 
+/// A float literal (e.g., "3.14")
+#[derive(Debug)]
+pub struct FloatLiteral {
+    /// The float token
+    pub token: Token,
+    /// The parsed float value
+    pub value: f64,
+}
+
 /// boolean literal (true or false)
 #[derive(Debug)]
 pub struct Boolean {
@@ -130,6 +226,13 @@ pub struct Boolean {
     pub value: bool,
 }
 
+/// null literal
+#[derive(Debug, Clone)]
+pub struct NullLiteral {
+    /// 'null' token
+    pub token: Token,
+}
+
 // block statement, a collection of statments
 #[derive(Debug)]
 pub struct BlockStatement {
@@ -152,13 +255,103 @@ pub struct IfExpression {
     pub alternative: Option<BlockStatement>,
 }
 
+/// while statement (eg. "while (x < 10) { let x = x + 1; }")
+#[derive(Debug)]
+pub struct WhileStatement {
+    /// 'while' token
+    pub token: Token,
+    /// loop condition, checked before every iteration
+    pub condition: Box<dyn Expression>,
+    /// loop body
+    pub body: BlockStatement,
+}
+
+/// do-while statement (eg. "do { x = x + 1; } while (x < 10);")
+#[derive(Debug)]
+pub struct DoWhileStatement {
+    /// 'do' token
+    pub token: Token,
+    /// loop body, run once before the condition is first checked
+    pub body: BlockStatement,
+    /// loop condition, checked after every iteration
+    pub condition: Box<dyn Expression>,
+}
+
+/// loop statement (eg. "loop { if (done()) { break; } }")
+#[derive(Debug, Clone)]
+pub struct LoopStatement {
+    /// 'loop' token
+    pub token: Token,
+    /// loop body, repeated until it breaks or returns
+    pub body: BlockStatement,
+}
+
+/// while let statement (eg. "while let x = next() { puts(x); }")
+#[derive(Debug)]
+pub struct WhileLetStatement {
+    /// 'while' token
+    pub token: Token,
+    /// name bound to the value of `expression` on each iteration
+    pub pattern: Identifier,
+    /// re-evaluated before every iteration; the loop ends once it is null
+    pub expression: Box<dyn Expression>,
+    /// loop body
+    pub body: BlockStatement,
+}
+
+/// for statement (eg. "for (x in [1, 2, 3]) { puts(x); }")
+#[derive(Debug)]
+pub struct ForStatement {
+    /// 'for' token
+    pub token: Token,
+    /// name bound to each element in turn
+    pub iterator_name: Identifier,
+    /// expression producing the values to iterate over
+    pub iterable: Box<dyn Expression>,
+    /// loop body
+    pub body: BlockStatement,
+}
+
+/// break statement (eg. "break;", "break 'outer;")
+#[derive(Debug, Clone)]
+pub struct BreakStatement {
+    /// 'break' token
+    pub token: Token,
+    /// label of the loop to leave, the innermost loop if None
+    pub label: Option<String>,
+}
+
+/// continue statement (eg. "continue;", "continue 'outer;")
+#[derive(Debug, Clone)]
+pub struct ContinueStatement {
+    /// 'continue' token
+    pub token: Token,
+    /// label of the loop to continue, the innermost loop if None
+    pub label: Option<String>,
+}
+
+/// labeled loop (eg. "'outer: while (true) { break 'outer; }")
+#[derive(Debug)]
+pub struct LabeledStatement {
+    /// label token
+    pub token: Token,
+    /// the label, without its leading `'`
+    pub label: String,
+    /// the loop being labeled
+    pub body: Box<dyn Statement>,
+}
+
 /// function literal (eg. "fn(x, y) { x + y; }")
 #[derive(Debug)]
 pub struct FunctionLiteral {
     /// 'fn' token
     pub token: Token,
+    /// name given by the `fn name(params) { body }` declaration form
+    pub name: Option<Identifier>,
     /// function parameters
     pub parameters: Vec<Identifier>,
+    /// trailing `...rest` parameter collecting any extra arguments
+    pub rest: Option<Identifier>,
     /// function body
     pub body: BlockStatement,
 }
@@ -181,64 +374,298 @@ pub struct CallExpression {
     pub arguments: Vec<Box<dyn Expression>>,
 }
 
+/// spread call argument (eg. "...arr" in "f(...arr)"), passing each element
+/// of an array as its own argument
+#[derive(Debug)]
+pub struct SpreadExpression {
+    /// '...' token
+    pub token: Token,
+    /// the expression that produces the array
+    pub value: Box<dyn Expression>,
+}
+
+/// array literal (eg. "[1, 2 * 2, 3 + 3]")
+#[derive(Debug)]
+pub struct ArrayLiteral {
+    /// '[' token
+    pub token: Token,
+    /// element expressions
+    pub elements: Vec<Box<dyn Expression>>,
+}
+
+/// hash literal (eg. "{"one": 1, "two": 2}")
+#[derive(Debug)]
+pub struct HashLiteral {
+    /// '{' token
+    pub token: Token,
+    /// key/value expression pairs, in source order
+    pub pairs: Vec<(Box<dyn Expression>, Box<dyn Expression>)>,
+}
+
+/// match expression (eg. "match (x) { 1 => "one", _ => "other" }")
+#[derive(Debug)]
+pub struct MatchExpression {
+    /// 'match' token
+    pub token: Token,
+    /// the value being matched
+    pub subject: Box<dyn Expression>,
+    /// arms, tried in source order
+    pub arms: Vec<MatchArm>,
+}
+
+/// try/catch expression (eg. "try { risky() } catch (err) { error_message(err) }")
+#[derive(Debug, Clone)]
+pub struct TryCatchExpression {
+    /// 'try' token
+    pub token: Token,
+    /// block run first
+    pub try_block: BlockStatement,
+    /// name the error is bound to in the catch block
+    pub error_name: Identifier,
+    /// block run in place of the try block's result if that is an error
+    pub catch_block: BlockStatement,
+}
+
+/// a single `pattern => body` arm of a match expression
+#[derive(Debug)]
+pub struct MatchArm {
+    /// value compared against the subject, or a wildcard
+    pub pattern: Box<dyn Expression>,
+    /// expression evaluated when the pattern matches
+    pub body: Box<dyn Expression>,
+}
+
+/// array destructuring pattern (eg. "[a, _, c]")
+#[derive(Debug, Clone)]
+pub struct ArrayDestructurePattern {
+    /// '[' token
+    pub token: Token,
+    /// names bound to the elements in order, None where `_` skips one
+    pub elements: Vec<Option<Identifier>>,
+}
+
+/// hash destructuring pattern (eg. "{x, y: b}")
+#[derive(Debug, Clone)]
+pub struct HashDestructurePattern {
+    /// '{' token
+    pub token: Token,
+    /// each key looked up in the hash, paired with the name its value is
+    /// bound to; `{x}` is shorthand for `{x: x}`
+    pub pairs: Vec<(Identifier, Identifier)>,
+}
+
+/// wildcard pattern "_", which matches any value
+#[derive(Debug, Clone)]
+pub struct WildcardExpression {
+    /// '_' token
+    pub token: Token,
+}
+
+/// index expression (eg. "myArray[1]", "myHash["key"]")
+#[derive(Debug)]
+pub struct IndexExpression {
+    /// '[' token
+    pub token: Token,
+    /// the expression being indexed
+    pub left: Box<dyn Expression>,
+    /// the index expression
+    pub index: Box<dyn Expression>,
+}
+
+/// index assignment expression (eg. "myArray[1] = 5")
+#[derive(Debug)]
+pub struct IndexAssignExpression {
+    /// '=' token
+    pub token: Token,
+    /// the indexed location being assigned to
+    pub target: IndexExpression,
+    /// the value being assigned
+    pub value: Box<dyn Expression>,
+}
+
+/// method call expression (eg. "name.upper()", "[1, 2].push(3)")
+#[derive(Debug)]
+pub struct MethodCallExpression {
+    /// '.' token
+    pub token: Token,
+    /// the expression the method is called on
+    pub object: Box<dyn Expression>,
+    /// the method name
+    pub method: Identifier,
+    /// argument expressions
+    pub arguments: Vec<Box<dyn Expression>>,
+}
+
+impl Node for SpreadExpression {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
+    }
+
+    fn span(&self) -> Span {
+        Span::from_token(&self.token).to(self.value.span())
+    }
+}
+
 impl Node for CallExpression {
     fn token_literal(&self) -> String {
         self.token.literal.clone()
     }
+
+    fn span(&self) -> Span {
+        match self.arguments.last() {
+            Some(last) => self.function.span().to(last.span()),
+            None => self.function.span().to(Span::from_token(&self.token)),
+        }
+    }
 }
 
 impl Node for FunctionLiteral {
     fn token_literal(&self) -> String {
         self.token.literal.clone()
     }
+
+    fn span(&self) -> Span {
+        Span::from_token(&self.token).to(self.body.span())
+    }
 }
 
 impl Node for IfExpression {
     fn token_literal(&self) -> String {
         self.token.literal.clone()
     }
+
+    fn span(&self) -> Span {
+        let last = self.alternative.as_ref().unwrap_or(&self.consequence);
+        Span::from_token(&self.token).to(last.span())
+    }
 }
 
 impl Node for BlockStatement {
     fn token_literal(&self) -> String {
         self.token.literal.clone()
     }
+
+    fn span(&self) -> Span {
+        let start = Span::from_token(&self.token);
+        match self.statements.last() {
+            Some(last) => start.to(last.span()),
+            None => start,
+        }
+    }
 }
 
 impl Node for Boolean {
     fn token_literal(&self) -> String {
         self.token.literal.clone()
     }
+
+    fn span(&self) -> Span {
+        Span::from_token(&self.token)
+    }
+}
+
+impl Node for NullLiteral {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
+    }
+
+    fn span(&self) -> Span {
+        Span::from_token(&self.token)
+    }
 }
 
 impl Node for ExpressionStatement {
     fn token_literal(&self) -> String {
         self.token.literal.clone()
     }
+
+    fn span(&self) -> Span {
+        self.expression.span()
+    }
 }
 
 impl Node for DummyExpression {
     fn token_literal(&self) -> String {
         String::new()
     }
+
+    fn span(&self) -> Span {
+        Span::default()
+    }
 }
 
 impl Node for PrefixExpression {
     fn token_literal(&self) -> String {
         self.token.literal.clone()
     }
+
+    fn span(&self) -> Span {
+        Span::from_token(&self.token).to(self.right.span())
+    }
+}
+
+impl Node for TypeofExpression {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
+    }
+
+    fn span(&self) -> Span {
+        Span::from_token(&self.token).to(self.expr.span())
+    }
 }
 
 impl Node for IntegerLiteral {
     fn token_literal(&self) -> String {
         self.token.literal.clone()
     }
+
+    fn span(&self) -> Span {
+        Span::from_token(&self.token)
+    }
+}
+
+impl Node for FloatLiteral {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
+    }
+
+    fn span(&self) -> Span {
+        Span::from_token(&self.token)
+    }
 }
 
 impl Node for InfixExpression {
     fn token_literal(&self) -> String {
         self.token.literal.clone()
     }
+
+    fn span(&self) -> Span {
+        self.left.span().to(self.right.span())
+    }
+}
+
+impl Program {
+    /// Number of top-level statements
+    pub fn statement_count(&self) -> usize {
+        self.statements.len()
+    }
+
+    /// Whether the program has no statements
+    pub fn is_empty(&self) -> bool {
+        self.statements.is_empty()
+    }
+
+    /// The tokens of the program in source order, followed by Eof
+    ///
+    /// Tokens the nodes keep are returned as parsed. Punctuation the parser
+    /// did not keep is rebuilt without a position, and parentheses appear
+    /// only where the grouping needs them. The optional `;` after an
+    /// expression statement is not recorded, so it is left out.
+    pub fn to_tokens(&self) -> Vec<Token> {
+        let mut writer = TokenWriter::default();
+        writer.visit_program(self);
+        writer.tokens
+    }
 }
 
 impl Node for Program {
@@ -249,438 +676,3603 @@ impl Node for Program {
             String::new()
         }
     }
+
+    fn span(&self) -> Span {
+        match (self.statements.first(), self.statements.last()) {
+            (Some(first), Some(last)) => first.span().to(last.span()),
+            _ => Span::default(),
+        }
+    }
 }
 
 impl Node for Identifier {
     fn token_literal(&self) -> String {
         self.token.literal.clone()
     }
+
+    fn span(&self) -> Span {
+        Span::from_token(&self.token)
+    }
 }
 
-impl Node for LetStatement {
+impl Node for WhileStatement {
     fn token_literal(&self) -> String {
         self.token.literal.clone()
     }
+
+    fn span(&self) -> Span {
+        Span::from_token(&self.token).to(self.body.span())
+    }
 }
 
-impl Node for ReturnStatement {
+impl Node for DoWhileStatement {
     fn token_literal(&self) -> String {
         self.token.literal.clone()
     }
+
+    fn span(&self) -> Span {
+        Span::from_token(&self.token).to(self.condition.span())
+    }
 }
 
-impl Node for StringLiteral {
+impl Node for LoopStatement {
     fn token_literal(&self) -> String {
         self.token.literal.clone()
     }
-}
-
-impl Statement for ExpressionStatement {
-    fn statement_node(&self) {}
 
-    fn as_any(&self) -> &dyn Any {
-        self
+    fn span(&self) -> Span {
+        Span::from_token(&self.token).to(self.body.span())
     }
 }
 
-impl Statement for BlockStatement {
-    fn statement_node(&self) {}
+impl Node for WhileLetStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
+    }
 
-    fn as_any(&self) -> &dyn Any {
-        self
+    fn span(&self) -> Span {
+        Span::from_token(&self.token).to(self.body.span())
     }
 }
 
-impl Statement for LetStatement {
-    fn statement_node(&self) {}
+impl Node for ForStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
+    }
 
-    fn as_any(&self) -> &dyn Any {
-        self
+    fn span(&self) -> Span {
+        Span::from_token(&self.token).to(self.body.span())
     }
 }
 
-impl Statement for ReturnStatement {
-    fn statement_node(&self) {}
+impl Node for BreakStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
+    }
 
-    fn as_any(&self) -> &dyn Any {
-        self
+    fn span(&self) -> Span {
+        Span::from_token(&self.token)
     }
 }
 
-impl Expression for StringLiteral {
-    fn expression_node(&self) {}
-
-    fn as_any(&self) -> &dyn Any {
-        self
+impl Node for LabeledStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
     }
 
-    fn clone_box(&self) -> Box<dyn Expression> {
-        Box::new(StringLiteral {
-            token: self.token.clone(),
-            value: self.value.clone(),
-        })
+    fn span(&self) -> Span {
+        Span::from_token(&self.token).to(self.body.span())
     }
 }
 
-impl Expression for DummyExpression {
-    fn expression_node(&self) {}
-
-    fn as_any(&self) -> &dyn Any {
-        self
+impl Node for ContinueStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
     }
 
-    fn clone_box(&self) -> Box<dyn Expression> {
-        Box::new(DummyExpression)
+    fn span(&self) -> Span {
+        Span::from_token(&self.token)
     }
 }
 
-impl Expression for PrefixExpression {
-    fn expression_node(&self) {}
-
-    fn as_any(&self) -> &dyn Any {
-        self
+impl Node for ConstStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
     }
 
-    fn clone_box(&self) -> Box<dyn Expression> {
-        Box::new(PrefixExpression {
-            token: self.token.clone(),
-            operator: self.operator.clone(),
-            right: self.right.clone_box(),
-        })
+    fn span(&self) -> Span {
+        Span::from_token(&self.token).to(self.value.span())
     }
 }
 
-impl Expression for InfixExpression {
-    fn expression_node(&self) {}
+impl Node for AssignStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
+    }
+
+    fn span(&self) -> Span {
+        self.target.span().to(self.value.span())
+    }
+}
+
+impl Node for DestructureStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
+    }
+
+    fn span(&self) -> Span {
+        Span::from_token(&self.token).to(self.value.span())
+    }
+}
+
+impl Node for LetStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
+    }
+
+    fn span(&self) -> Span {
+        let start = Span::from_token(&self.token);
+        match &self.value {
+            Some(value) => start.to(value.span()),
+            None => start.to(self.name.span()),
+        }
+    }
+}
+
+impl Node for ReturnStatement {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
+    }
+
+    fn span(&self) -> Span {
+        let start = Span::from_token(&self.token);
+        match &self.return_value {
+            Some(value) => start.to(value.span()),
+            None => start,
+        }
+    }
+}
+
+impl Node for ArrayLiteral {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
+    }
+
+    fn span(&self) -> Span {
+        let start = Span::from_token(&self.token);
+        match self.elements.last() {
+            Some(last) => start.to(last.span()),
+            None => start,
+        }
+    }
+}
+
+impl Node for TryCatchExpression {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
+    }
+
+    fn span(&self) -> Span {
+        Span::from_token(&self.token).to(self.catch_block.span())
+    }
+}
+
+impl Node for MatchExpression {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
+    }
+
+    fn span(&self) -> Span {
+        let start = Span::from_token(&self.token);
+        match self.arms.last() {
+            Some(arm) => start.to(arm.body.span()),
+            None => start.to(self.subject.span()),
+        }
+    }
+}
+
+impl Node for ArrayDestructurePattern {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
+    }
+
+    fn span(&self) -> Span {
+        Span::from_token(&self.token)
+    }
+}
+
+impl Node for HashDestructurePattern {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
+    }
+
+    fn span(&self) -> Span {
+        Span::from_token(&self.token)
+    }
+}
+
+impl Node for WildcardExpression {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
+    }
+
+    fn span(&self) -> Span {
+        Span::from_token(&self.token)
+    }
+}
+
+impl Node for HashLiteral {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
+    }
+
+    fn span(&self) -> Span {
+        let start = Span::from_token(&self.token);
+        match self.pairs.last() {
+            Some((_, value)) => start.to(value.span()),
+            None => start,
+        }
+    }
+}
+
+impl Node for IndexExpression {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
+    }
+
+    fn span(&self) -> Span {
+        self.left.span().to(self.index.span())
+    }
+}
+
+impl Node for IndexAssignExpression {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
+    }
+
+    fn span(&self) -> Span {
+        self.target.span().to(self.value.span())
+    }
+}
+
+impl Node for MethodCallExpression {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
+    }
+
+    fn span(&self) -> Span {
+        match self.arguments.last() {
+            Some(last) => self.object.span().to(last.span()),
+            None => self.object.span().to(self.method.span()),
+        }
+    }
+}
+
+impl Node for StringLiteral {
+    fn token_literal(&self) -> String {
+        self.token.literal.clone()
+    }
+
+    fn span(&self) -> Span {
+        Span::from_token(&self.token)
+    }
+}
+
+impl Statement for ExpressionStatement {
+    fn statement_node(&self) {}
 
     fn as_any(&self) -> &dyn Any {
         self
     }
 
-    fn clone_box(&self) -> Box<dyn Expression> {
-        Box::new(InfixExpression {
-            token: self.token.clone(),
-            left: self.left.clone_box(),
-            operator: self.operator.clone(),
-            right: self.right.clone_box(),
-        })
+    fn clone_box(&self) -> Box<dyn Statement> {
+        Box::new(self.clone())
     }
 }
 
-impl Expression for IntegerLiteral {
-    fn expression_node(&self) {}
+impl Statement for BlockStatement {
+    fn statement_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Statement> {
+        Box::new(self.clone())
+    }
+}
+
+impl Statement for ConstStatement {
+    fn statement_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Statement> {
+        Box::new(self.clone())
+    }
+}
+
+impl Statement for AssignStatement {
+    fn statement_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Statement> {
+        Box::new(self.clone())
+    }
+}
+
+impl Statement for DestructureStatement {
+    fn statement_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Statement> {
+        Box::new(self.clone())
+    }
+}
+
+impl Statement for LetStatement {
+    fn statement_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Statement> {
+        Box::new(self.clone())
+    }
+}
+
+impl Statement for ReturnStatement {
+    fn statement_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Statement> {
+        Box::new(self.clone())
+    }
+}
+
+impl Statement for WhileStatement {
+    fn statement_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Statement> {
+        Box::new(self.clone())
+    }
+}
+
+impl Statement for DoWhileStatement {
+    fn statement_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Statement> {
+        Box::new(self.clone())
+    }
+}
+
+impl Statement for LoopStatement {
+    fn statement_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Statement> {
+        Box::new(self.clone())
+    }
+}
+
+impl Statement for WhileLetStatement {
+    fn statement_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Statement> {
+        Box::new(self.clone())
+    }
+}
+
+impl Statement for ForStatement {
+    fn statement_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Statement> {
+        Box::new(self.clone())
+    }
+}
+
+impl Statement for BreakStatement {
+    fn statement_node(&self) {}
 
     fn as_any(&self) -> &dyn Any {
         self
     }
 
-    fn clone_box(&self) -> Box<dyn Expression> {
-        Box::new(IntegerLiteral {
-            token: self.token.clone(),
-            value: self.value.clone(),
-        })
+    fn clone_box(&self) -> Box<dyn Statement> {
+        Box::new(self.clone())
+    }
+}
+
+impl Statement for LabeledStatement {
+    fn statement_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Statement> {
+        Box::new(self.clone())
+    }
+}
+
+impl Statement for ContinueStatement {
+    fn statement_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Statement> {
+        Box::new(self.clone())
+    }
+}
+
+impl Expression for StringLiteral {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        Box::new(StringLiteral {
+            token: self.token.clone(),
+            value: self.value.clone(),
+        })
+    }
+}
+
+impl Expression for DummyExpression {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        Box::new(DummyExpression)
+    }
+}
+
+impl Expression for PrefixExpression {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        Box::new(PrefixExpression {
+            token: self.token.clone(),
+            operator: self.operator.clone(),
+            right: self.right.clone_box(),
+        })
+    }
+}
+
+impl Expression for TypeofExpression {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        Box::new(TypeofExpression {
+            token: self.token.clone(),
+            expr: self.expr.clone_box(),
+        })
+    }
+}
+
+impl Expression for InfixExpression {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        Box::new(InfixExpression {
+            token: self.token.clone(),
+            left: self.left.clone_box(),
+            operator: self.operator.clone(),
+            right: self.right.clone_box(),
+        })
+    }
+}
+
+impl Expression for IntegerLiteral {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        Box::new(IntegerLiteral {
+            token: self.token.clone(),
+            value: self.value,
+        })
+    }
+}
+
+impl Expression for FloatLiteral {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        Box::new(FloatLiteral {
+            token: self.token.clone(),
+            value: self.value,
+        })
+    }
+}
+
+impl Expression for Identifier {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        Box::new(self.clone())
+    }
+}
+
+impl Expression for Boolean {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        Box::new(Boolean {
+            token: self.token.clone(),
+            value: self.value,
+        })
+    }
+}
+
+impl Expression for NullLiteral {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        Box::new(self.clone())
+    }
+}
+
+impl Expression for IfExpression {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        Box::new(IfExpression {
+            token: self.token.clone(),
+            condition: self.condition.clone_box(),
+            consequence: self.consequence.clone(),
+            alternative: self.alternative.clone(),
+        })
+    }
+}
+
+impl Expression for FunctionLiteral {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        Box::new(self.clone())
+    }
+}
+
+impl Expression for SpreadExpression {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        Box::new(SpreadExpression {
+            token: self.token.clone(),
+            value: self.value.clone_box(),
+        })
+    }
+}
+
+impl Expression for CallExpression {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        let mut arguments = Vec::new();
+        for arg in &self.arguments {
+            arguments.push(arg.clone_box());
+        }
+
+        Box::new(CallExpression {
+            token: self.token.clone(),
+            function: self.function.clone_box(),
+            arguments,
+        })
+    }
+}
+
+impl Expression for ArrayLiteral {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        let elements = self.elements.iter().map(|e| e.clone_box()).collect();
+
+        Box::new(ArrayLiteral {
+            token: self.token.clone(),
+            elements,
+        })
+    }
+}
+
+impl Expression for MatchExpression {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        Box::new(MatchExpression {
+            token: self.token.clone(),
+            subject: self.subject.clone_box(),
+            arms: self.arms.clone(),
+        })
+    }
+}
+
+impl Expression for TryCatchExpression {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        Box::new(self.clone())
+    }
+}
+
+impl Expression for ArrayDestructurePattern {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        Box::new(self.clone())
+    }
+}
+
+impl Expression for HashDestructurePattern {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        Box::new(self.clone())
+    }
+}
+
+impl Expression for WildcardExpression {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        Box::new(self.clone())
+    }
+}
+
+impl Expression for HashLiteral {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        let pairs = self
+            .pairs
+            .iter()
+            .map(|(k, v)| (k.clone_box(), v.clone_box()))
+            .collect();
+
+        Box::new(HashLiteral {
+            token: self.token.clone(),
+            pairs,
+        })
+    }
+}
+
+impl Expression for MethodCallExpression {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        Box::new(MethodCallExpression {
+            token: self.token.clone(),
+            object: self.object.clone_box(),
+            method: self.method.clone(),
+            arguments: self.arguments.iter().map(|arg| arg.clone_box()).collect(),
+        })
+    }
+}
+
+impl Expression for IndexExpression {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        Box::new(self.clone())
+    }
+}
+
+impl Expression for IndexAssignExpression {
+    fn expression_node(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Expression> {
+        Box::new(IndexAssignExpression {
+            token: self.token.clone(),
+            target: self.target.clone(),
+            value: self.value.clone_box(),
+        })
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for s in &self.statements {
+            write!(f, "{}", s)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for dyn Statement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(stmt) = self.as_any().downcast_ref::<ExpressionStatement>() {
+            return write!(f, "{}", stmt);
+        }
+        if let Some(stmt) = self.as_any().downcast_ref::<LetStatement>() {
+            return write!(f, "{}", stmt);
+        }
+        if let Some(stmt) = self.as_any().downcast_ref::<DestructureStatement>() {
+            return write!(f, "{}", stmt);
+        }
+        if let Some(stmt) = self.as_any().downcast_ref::<ConstStatement>() {
+            return write!(f, "{}", stmt);
+        }
+        if let Some(stmt) = self.as_any().downcast_ref::<AssignStatement>() {
+            return write!(f, "{}", stmt);
+        }
+        if let Some(stmt) = self.as_any().downcast_ref::<ReturnStatement>() {
+            return write!(f, "{}", stmt);
+        }
+        if let Some(stmt) = self.as_any().downcast_ref::<BlockStatement>() {
+            return write!(f, "{}", stmt);
+        }
+        if let Some(expr) = self.as_any().downcast_ref::<IfExpression>() {
+            return write!(f, "{}", expr);
+        }
+        if let Some(stmt) = self.as_any().downcast_ref::<WhileStatement>() {
+            return write!(f, "{}", stmt);
+        }
+        if let Some(stmt) = self.as_any().downcast_ref::<WhileLetStatement>() {
+            return write!(f, "{}", stmt);
+        }
+        if let Some(stmt) = self.as_any().downcast_ref::<DoWhileStatement>() {
+            return write!(f, "{}", stmt);
+        }
+        if let Some(stmt) = self.as_any().downcast_ref::<LoopStatement>() {
+            return write!(f, "{}", stmt);
+        }
+        if let Some(stmt) = self.as_any().downcast_ref::<ForStatement>() {
+            return write!(f, "{}", stmt);
+        }
+        if let Some(stmt) = self.as_any().downcast_ref::<BreakStatement>() {
+            return write!(f, "{}", stmt);
+        }
+        if let Some(stmt) = self.as_any().downcast_ref::<ContinueStatement>() {
+            return write!(f, "{}", stmt);
+        }
+        if let Some(stmt) = self.as_any().downcast_ref::<LabeledStatement>() {
+            return write!(f, "{}", stmt);
+        }
+        write!(f, "{}", self.token_literal())
+    }
+}
+
+impl fmt::Display for ConstStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "const {} = {};", self.name, self.value)
+    }
+}
+
+impl fmt::Display for AssignStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {};", self.target, self.value)
+    }
+}
+
+impl fmt::Display for LetStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} = ",
+            self.token_literal(),
+            self.name.token_literal()
+        )?;
+        if let Some(value) = &self.value {
+            write!(f, "{}", value)?;
+        }
+        write!(f, ";")
+    }
+}
+
+impl fmt::Display for DestructureStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} = {};",
+            self.token_literal(),
+            self.pattern,
+            self.value
+        )
+    }
+}
+
+impl fmt::Display for ReturnStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.token_literal())?;
+        if let Some(value) = &self.return_value {
+            write!(f, "{}", value)?;
+        }
+        write!(f, ";")
+    }
+}
+
+impl fmt::Display for DummyExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "")
+    }
+}
+
+impl fmt::Display for ExpressionStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.expression)
+    }
+}
+
+impl fmt::Display for dyn Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(expr) = self.as_any().downcast_ref::<TypeofExpression>() {
+            return write!(f, "{}", expr);
+        }
+        if let Some(expr) = self.as_any().downcast_ref::<PrefixExpression>() {
+            return write!(f, "{}", expr);
+        }
+        if let Some(expr) = self.as_any().downcast_ref::<InfixExpression>() {
+            return write!(f, "{}", expr);
+        }
+        if let Some(expr) = self.as_any().downcast_ref::<IntegerLiteral>() {
+            return write!(f, "{}", expr);
+        }
+        if let Some(expr) = self.as_any().downcast_ref::<FloatLiteral>() {
+            return write!(f, "{}", expr);
+        }
+        if let Some(expr) = self.as_any().downcast_ref::<Identifier>() {
+            return write!(f, "{}", expr);
+        }
+        if let Some(expr) = self.as_any().downcast_ref::<Boolean>() {
+            return write!(f, "{}", expr);
+        }
+        if let Some(expr) = self.as_any().downcast_ref::<NullLiteral>() {
+            return write!(f, "{}", expr);
+        }
+        if let Some(expr) = self.as_any().downcast_ref::<IfExpression>() {
+            return write!(f, "{}", expr);
+        }
+        if let Some(expr) = self.as_any().downcast_ref::<FunctionLiteral>() {
+            return write!(f, "{}", expr);
+        }
+        if let Some(expr) = self.as_any().downcast_ref::<CallExpression>() {
+            return write!(f, "{}", expr);
+        }
+        if let Some(expr) = self.as_any().downcast_ref::<SpreadExpression>() {
+            return write!(f, "{}", expr);
+        }
+        if let Some(expr) = self.as_any().downcast_ref::<ArrayLiteral>() {
+            return write!(f, "{}", expr);
+        }
+        if let Some(expr) = self.as_any().downcast_ref::<MatchExpression>() {
+            return write!(f, "{}", expr);
+        }
+        if let Some(expr) = self.as_any().downcast_ref::<TryCatchExpression>() {
+            return write!(f, "{}", expr);
+        }
+        if let Some(expr) = self.as_any().downcast_ref::<ArrayDestructurePattern>() {
+            return write!(f, "{}", expr);
+        }
+        if let Some(expr) = self.as_any().downcast_ref::<HashDestructurePattern>() {
+            return write!(f, "{}", expr);
+        }
+        if let Some(expr) = self.as_any().downcast_ref::<WildcardExpression>() {
+            return write!(f, "{}", expr);
+        }
+        if let Some(expr) = self.as_any().downcast_ref::<HashLiteral>() {
+            return write!(f, "{}", expr);
+        }
+        if let Some(expr) = self.as_any().downcast_ref::<IndexExpression>() {
+            return write!(f, "{}", expr);
+        }
+        if let Some(expr) = self.as_any().downcast_ref::<MethodCallExpression>() {
+            return write!(f, "{}", expr);
+        }
+        if let Some(expr) = self.as_any().downcast_ref::<IndexAssignExpression>() {
+            return write!(f, "{}", expr);
+        }
+        write!(f, "{}", self.token_literal())
+    }
+}
+
+impl fmt::Display for PrefixExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}{})", self.operator, self.right)
+    }
+}
+
+impl fmt::Display for TypeofExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(typeof {})", self.expr)
+    }
+}
+
+impl fmt::Display for InfixExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({} {} {})", self.left, self.operator, self.right)
+    }
+}
+
+impl fmt::Display for IntegerLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl fmt::Display for FloatLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.value)
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl fmt::Display for Boolean {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl fmt::Display for NullLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "null")
+    }
+}
+
+impl fmt::Display for BlockStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for stmt in &self.statements {
+            write!(f, "{}", stmt)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for IfExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "if{} {}", self.condition, self.consequence)?;
+
+        if let Some(alt) = &self.alternative {
+            write!(f, "else {}", alt)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for WhileStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "while{} {}", self.condition, self.body)
+    }
+}
+
+impl fmt::Display for DoWhileStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "do {} while {};", self.body, self.condition)
+    }
+}
+
+impl fmt::Display for LoopStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "loop {}", self.body)
+    }
+}
+
+impl fmt::Display for WhileLetStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "while let {} = {} {}",
+            self.pattern, self.expression, self.body
+        )
+    }
+}
+
+impl fmt::Display for ForStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "for ({} in {}) {}",
+            self.iterator_name, self.iterable, self.body
+        )
+    }
+}
+
+impl fmt::Display for BreakStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.label {
+            Some(label) => write!(f, "break '{};", label),
+            None => write!(f, "break;"),
+        }
+    }
+}
+
+impl fmt::Display for ContinueStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.label {
+            Some(label) => write!(f, "continue '{};", label),
+            None => write!(f, "continue;"),
+        }
+    }
+}
+
+impl fmt::Display for LabeledStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}: {}", self.label, self.body)
+    }
+}
+
+impl fmt::Display for FunctionLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut params: Vec<String> = self.parameters.iter().map(|p| p.to_string()).collect();
+        if let Some(rest) = &self.rest {
+            params.push(format!("...{}", rest));
+        }
+
+        write!(f, "{}", self.token_literal())?;
+        if let Some(name) = &self.name {
+            write!(f, " {}", name)?;
+        }
+        write!(f, "({}) {}", params.join(", "), self.body)
+    }
+}
+
+impl fmt::Display for CallExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args: Vec<String> = self.arguments.iter().map(|a| a.to_string()).collect();
+
+        write!(f, "{}({})", self.function, args.join(", "))
+    }
+}
+
+impl fmt::Display for StringLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl fmt::Display for ArrayLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let elements: Vec<String> = self.elements.iter().map(|e| e.to_string()).collect();
+
+        write!(f, "[{}]", elements.join(", "))
+    }
+}
+
+impl fmt::Display for MatchExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let arms: Vec<String> = self
+            .arms
+            .iter()
+            .map(|arm| format!("{} => {}", arm.pattern, arm.body))
+            .collect();
+
+        write!(f, "match ({}) {{ {} }}", self.subject, arms.join(", "))
+    }
+}
+
+impl fmt::Display for TryCatchExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "try {} catch ({}) {}",
+            self.try_block, self.error_name, self.catch_block
+        )
+    }
+}
+
+impl fmt::Display for ArrayDestructurePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let elements: Vec<String> = self
+            .elements
+            .iter()
+            .map(|name| match name {
+                Some(name) => name.to_string(),
+                None => String::from("_"),
+            })
+            .collect();
+
+        write!(f, "[{}]", elements.join(", "))
+    }
+}
+
+impl fmt::Display for SpreadExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "...{}", self.value)
+    }
+}
+
+impl fmt::Display for HashDestructurePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pairs: Vec<String> = self
+            .pairs
+            .iter()
+            .map(|(key, name)| {
+                if key.value == name.value {
+                    key.to_string()
+                } else {
+                    format!("{}: {}", key, name)
+                }
+            })
+            .collect();
+
+        write!(f, "{{{}}}", pairs.join(", "))
+    }
+}
+
+impl fmt::Display for WildcardExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "_")
+    }
+}
+
+impl fmt::Display for HashLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pairs: Vec<String> = self
+            .pairs
+            .iter()
+            .map(|(k, v)| format!("{}:{}", k, v))
+            .collect();
+
+        write!(f, "{{{}}}", pairs.join(", "))
+    }
+}
+
+impl fmt::Display for MethodCallExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args: Vec<String> = self.arguments.iter().map(|a| a.to_string()).collect();
+
+        write!(f, "{}.{}({})", self.object, self.method, args.join(", "))
+    }
+}
+
+impl fmt::Display for IndexExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}[{}])", self.left, self.index)
+    }
+}
+
+impl fmt::Display for IndexAssignExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({} = {})", self.target, self.value)
+    }
+}
+
+/// --------------------
+/// Clone Implementation
+/// --------------------
+impl Clone for MatchArm {
+    fn clone(&self) -> Self {
+        MatchArm {
+            pattern: self.pattern.clone_box(),
+            body: self.body.clone_box(),
+        }
+    }
+}
+
+impl Clone for IndexExpression {
+    fn clone(&self) -> Self {
+        IndexExpression {
+            token: self.token.clone(),
+            left: self.left.clone_box(),
+            index: self.index.clone_box(),
+        }
+    }
+}
+
+impl Clone for BlockStatement {
+    fn clone(&self) -> Self {
+        BlockStatement {
+            token: self.token.clone(),
+            statements: self
+                .statements
+                .iter()
+                .map(|stmt| stmt.clone_box())
+                .collect(),
+        }
+    }
+}
+
+impl Clone for ExpressionStatement {
+    fn clone(&self) -> Self {
+        ExpressionStatement {
+            token: self.token.clone(),
+            expression: self.expression.clone_box(),
+        }
+    }
+}
+
+impl Clone for LetStatement {
+    fn clone(&self) -> Self {
+        LetStatement {
+            token: self.token.clone(),
+            name: self.name.clone(),
+            value: self.value.as_ref().map(|expr| expr.clone_box()),
+        }
+    }
+}
+
+impl Clone for DestructureStatement {
+    fn clone(&self) -> Self {
+        DestructureStatement {
+            token: self.token.clone(),
+            pattern: self.pattern.clone_box(),
+            value: self.value.clone_box(),
+        }
+    }
+}
+
+impl Clone for LabeledStatement {
+    fn clone(&self) -> Self {
+        LabeledStatement {
+            token: self.token.clone(),
+            label: self.label.clone(),
+            body: self.body.clone_box(),
+        }
+    }
+}
+
+impl Clone for ConstStatement {
+    fn clone(&self) -> Self {
+        ConstStatement {
+            token: self.token.clone(),
+            name: self.name.clone(),
+            value: self.value.clone_box(),
+        }
+    }
+}
+
+impl Clone for AssignStatement {
+    fn clone(&self) -> Self {
+        AssignStatement {
+            token: self.token.clone(),
+            target: self.target.clone(),
+            value: self.value.clone_box(),
+        }
+    }
+}
+
+impl Clone for ReturnStatement {
+    fn clone(&self) -> Self {
+        ReturnStatement {
+            token: self.token.clone(),
+            return_value: self.return_value.as_ref().map(|expr| expr.clone_box()),
+        }
+    }
+}
+
+impl Clone for FunctionLiteral {
+    fn clone(&self) -> Self {
+        FunctionLiteral {
+            token: self.token.clone(),
+            name: self.name.clone(),
+            parameters: self.parameters.clone(),
+            rest: self.rest.clone(),
+            body: self.body.clone(),
+        }
+    }
+}
+
+impl Clone for WhileStatement {
+    fn clone(&self) -> Self {
+        WhileStatement {
+            token: self.token.clone(),
+            condition: self.condition.clone_box(),
+            body: self.body.clone(),
+        }
+    }
+}
+
+impl Clone for DoWhileStatement {
+    fn clone(&self) -> Self {
+        DoWhileStatement {
+            token: self.token.clone(),
+            body: self.body.clone(),
+            condition: self.condition.clone_box(),
+        }
+    }
+}
+
+impl Clone for WhileLetStatement {
+    fn clone(&self) -> Self {
+        WhileLetStatement {
+            token: self.token.clone(),
+            pattern: self.pattern.clone(),
+            expression: self.expression.clone_box(),
+            body: self.body.clone(),
+        }
+    }
+}
+
+impl Clone for ForStatement {
+    fn clone(&self) -> Self {
+        ForStatement {
+            token: self.token.clone(),
+            iterator_name: self.iterator_name.clone(),
+            iterable: self.iterable.clone_box(),
+            body: self.body.clone(),
+        }
+    }
+}
+
+/// Equality
+///
+/// Nodes compare by structure: operators, values and children. Tokens, and
+/// with them source positions, are ignored, so the same code parsed twice or
+/// with different spacing compares equal.
+impl PartialEq for dyn Expression {
+    fn eq(&self, other: &Self) -> bool {
+        expr_eq(self, other)
+    }
+}
+
+impl PartialEq for dyn Statement {
+    fn eq(&self, other: &Self) -> bool {
+        stmt_eq(self, other)
+    }
+}
+
+fn any_eq<T: PartialEq + 'static>(a: &dyn Any, b: &dyn Any) -> bool {
+    matches!(
+        (a.downcast_ref::<T>(), b.downcast_ref::<T>()),
+        (Some(a), Some(b)) if a == b
+    )
+}
+
+/// Compares two expressions of any concrete type
+pub fn expr_eq(a: &dyn Expression, b: &dyn Expression) -> bool {
+    let (a, b) = (a.as_any(), b.as_any());
+    if a.type_id() != b.type_id() {
+        return false;
+    }
+
+    any_eq::<Identifier>(a, b)
+        || any_eq::<IntegerLiteral>(a, b)
+        || any_eq::<FloatLiteral>(a, b)
+        || any_eq::<StringLiteral>(a, b)
+        || any_eq::<Boolean>(a, b)
+        || any_eq::<PrefixExpression>(a, b)
+        || any_eq::<InfixExpression>(a, b)
+        || any_eq::<TypeofExpression>(a, b)
+        || any_eq::<IfExpression>(a, b)
+        || any_eq::<FunctionLiteral>(a, b)
+        || any_eq::<CallExpression>(a, b)
+        || any_eq::<SpreadExpression>(a, b)
+        || any_eq::<ArrayLiteral>(a, b)
+        || any_eq::<HashLiteral>(a, b)
+        || any_eq::<MatchExpression>(a, b)
+        || any_eq::<TryCatchExpression>(a, b)
+        || any_eq::<IndexExpression>(a, b)
+        || any_eq::<IndexAssignExpression>(a, b)
+        || any_eq::<MethodCallExpression>(a, b)
+        || any_eq::<ArrayDestructurePattern>(a, b)
+        || any_eq::<HashDestructurePattern>(a, b)
+        || a.is::<NullLiteral>()
+        || a.is::<WildcardExpression>()
+        || a.is::<DummyExpression>()
+}
+
+/// Compares two statements of any concrete type
+pub fn stmt_eq(a: &dyn Statement, b: &dyn Statement) -> bool {
+    let (a, b) = (a.as_any(), b.as_any());
+    if a.type_id() != b.type_id() {
+        return false;
+    }
+
+    any_eq::<ExpressionStatement>(a, b)
+        || any_eq::<LetStatement>(a, b)
+        || any_eq::<DestructureStatement>(a, b)
+        || any_eq::<ConstStatement>(a, b)
+        || any_eq::<AssignStatement>(a, b)
+        || any_eq::<ReturnStatement>(a, b)
+        || any_eq::<BlockStatement>(a, b)
+        || any_eq::<WhileStatement>(a, b)
+        || any_eq::<DoWhileStatement>(a, b)
+        || any_eq::<LoopStatement>(a, b)
+        || any_eq::<WhileLetStatement>(a, b)
+        || any_eq::<ForStatement>(a, b)
+        || any_eq::<BreakStatement>(a, b)
+        || any_eq::<ContinueStatement>(a, b)
+        || any_eq::<LabeledStatement>(a, b)
+}
+
+impl PartialEq for Program {
+    fn eq(&self, other: &Self) -> bool {
+        self.statements == other.statements
+    }
+}
+
+impl PartialEq for ExpressionStatement {
+    fn eq(&self, other: &Self) -> bool {
+        *self.expression == *other.expression
+    }
+}
+
+impl PartialEq for LetStatement {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.value == other.value
+    }
+}
+
+impl PartialEq for DestructureStatement {
+    fn eq(&self, other: &Self) -> bool {
+        *self.pattern == *other.pattern && *self.value == *other.value
+    }
+}
+
+impl PartialEq for ConstStatement {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && *self.value == *other.value
+    }
+}
+
+impl PartialEq for AssignStatement {
+    fn eq(&self, other: &Self) -> bool {
+        self.target == other.target && *self.value == *other.value
+    }
+}
+
+impl PartialEq for ReturnStatement {
+    fn eq(&self, other: &Self) -> bool {
+        self.return_value == other.return_value
+    }
+}
+
+impl PartialEq for BlockStatement {
+    fn eq(&self, other: &Self) -> bool {
+        self.statements == other.statements
+    }
+}
+
+impl PartialEq for WhileStatement {
+    fn eq(&self, other: &Self) -> bool {
+        *self.condition == *other.condition && self.body == other.body
+    }
+}
+
+impl PartialEq for DoWhileStatement {
+    fn eq(&self, other: &Self) -> bool {
+        self.body == other.body && *self.condition == *other.condition
+    }
+}
+
+impl PartialEq for LoopStatement {
+    fn eq(&self, other: &Self) -> bool {
+        self.body == other.body
+    }
+}
+
+impl PartialEq for WhileLetStatement {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+            && *self.expression == *other.expression
+            && self.body == other.body
+    }
+}
+
+impl PartialEq for ForStatement {
+    fn eq(&self, other: &Self) -> bool {
+        self.iterator_name == other.iterator_name
+            && *self.iterable == *other.iterable
+            && self.body == other.body
+    }
+}
+
+impl PartialEq for BreakStatement {
+    fn eq(&self, other: &Self) -> bool {
+        self.label == other.label
+    }
+}
+
+impl PartialEq for ContinueStatement {
+    fn eq(&self, other: &Self) -> bool {
+        self.label == other.label
+    }
+}
+
+impl PartialEq for LabeledStatement {
+    fn eq(&self, other: &Self) -> bool {
+        self.label == other.label && *self.body == *other.body
+    }
+}
+
+impl PartialEq for Identifier {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl PartialEq for IntegerLiteral {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl PartialEq for FloatLiteral {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl PartialEq for StringLiteral {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl PartialEq for Boolean {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl PartialEq for PrefixExpression {
+    fn eq(&self, other: &Self) -> bool {
+        self.operator == other.operator && *self.right == *other.right
+    }
+}
+
+impl PartialEq for InfixExpression {
+    fn eq(&self, other: &Self) -> bool {
+        self.operator == other.operator && *self.left == *other.left && *self.right == *other.right
+    }
+}
+
+impl PartialEq for TypeofExpression {
+    fn eq(&self, other: &Self) -> bool {
+        *self.expr == *other.expr
+    }
+}
+
+impl PartialEq for IfExpression {
+    fn eq(&self, other: &Self) -> bool {
+        *self.condition == *other.condition
+            && self.consequence == other.consequence
+            && self.alternative == other.alternative
+    }
+}
+
+impl PartialEq for FunctionLiteral {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.parameters == other.parameters
+            && self.rest == other.rest
+            && self.body == other.body
+    }
+}
+
+impl PartialEq for CallExpression {
+    fn eq(&self, other: &Self) -> bool {
+        *self.function == *other.function && self.arguments == other.arguments
+    }
+}
+
+impl PartialEq for SpreadExpression {
+    fn eq(&self, other: &Self) -> bool {
+        *self.value == *other.value
+    }
+}
+
+impl PartialEq for ArrayLiteral {
+    fn eq(&self, other: &Self) -> bool {
+        self.elements == other.elements
+    }
+}
+
+impl PartialEq for HashLiteral {
+    fn eq(&self, other: &Self) -> bool {
+        self.pairs == other.pairs
+    }
+}
+
+impl PartialEq for MatchExpression {
+    fn eq(&self, other: &Self) -> bool {
+        *self.subject == *other.subject && self.arms == other.arms
+    }
+}
+
+impl PartialEq for TryCatchExpression {
+    fn eq(&self, other: &Self) -> bool {
+        self.try_block == other.try_block
+            && self.error_name == other.error_name
+            && self.catch_block == other.catch_block
+    }
+}
+
+impl PartialEq for MatchArm {
+    fn eq(&self, other: &Self) -> bool {
+        *self.pattern == *other.pattern && *self.body == *other.body
+    }
+}
+
+impl PartialEq for IndexExpression {
+    fn eq(&self, other: &Self) -> bool {
+        *self.left == *other.left && *self.index == *other.index
+    }
+}
+
+impl PartialEq for IndexAssignExpression {
+    fn eq(&self, other: &Self) -> bool {
+        self.target == other.target && *self.value == *other.value
+    }
+}
+
+impl PartialEq for ArrayDestructurePattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.elements == other.elements
+    }
+}
+
+impl PartialEq for HashDestructurePattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.pairs == other.pairs
+    }
+}
+
+impl PartialEq for MethodCallExpression {
+    fn eq(&self, other: &Self) -> bool {
+        *self.object == *other.object
+            && self.method == other.method
+            && self.arguments == other.arguments
+    }
+}
+
+/// Visitor
+///
+/// Read-only traversal of an AST. Every `visit_*` method defaults to calling
+/// the matching `walk_*` function, which visits the node's children, so an
+/// implementation overrides only the nodes it is interested in and calls
+/// `walk_*` itself to keep descending below them.
+pub trait Visitor {
+    fn visit_program(&mut self, program: &Program) {
+        walk_program(self, program);
+    }
+
+    /// Dispatches to the visit method for the statement's concrete type
+    fn visit_statement(&mut self, statement: &dyn Statement) {
+        walk_statement(self, statement);
+    }
+
+    /// Dispatches to the visit method for the expression's concrete type
+    fn visit_expression(&mut self, expression: &dyn Expression) {
+        walk_expression(self, expression);
+    }
+
+    fn visit_let_statement(&mut self, stmt: &LetStatement) {
+        walk_let_statement(self, stmt);
+    }
+
+    fn visit_destructure_statement(&mut self, stmt: &DestructureStatement) {
+        walk_destructure_statement(self, stmt);
+    }
+
+    fn visit_const_statement(&mut self, stmt: &ConstStatement) {
+        walk_const_statement(self, stmt);
+    }
+
+    fn visit_assign_statement(&mut self, stmt: &AssignStatement) {
+        walk_assign_statement(self, stmt);
+    }
+
+    fn visit_return_statement(&mut self, stmt: &ReturnStatement) {
+        walk_return_statement(self, stmt);
+    }
+
+    fn visit_expression_statement(&mut self, stmt: &ExpressionStatement) {
+        walk_expression_statement(self, stmt);
+    }
+
+    fn visit_block_statement(&mut self, block: &BlockStatement) {
+        walk_block_statement(self, block);
+    }
+
+    fn visit_while_statement(&mut self, stmt: &WhileStatement) {
+        walk_while_statement(self, stmt);
+    }
+
+    fn visit_do_while_statement(&mut self, stmt: &DoWhileStatement) {
+        walk_do_while_statement(self, stmt);
+    }
+
+    fn visit_loop_statement(&mut self, stmt: &LoopStatement) {
+        walk_loop_statement(self, stmt);
+    }
+
+    fn visit_while_let_statement(&mut self, stmt: &WhileLetStatement) {
+        walk_while_let_statement(self, stmt);
+    }
+
+    fn visit_for_statement(&mut self, stmt: &ForStatement) {
+        walk_for_statement(self, stmt);
+    }
+
+    fn visit_break_statement(&mut self, _stmt: &BreakStatement) {}
+
+    fn visit_continue_statement(&mut self, _stmt: &ContinueStatement) {}
+
+    fn visit_labeled_statement(&mut self, stmt: &LabeledStatement) {
+        walk_labeled_statement(self, stmt);
+    }
+
+    fn visit_identifier(&mut self, _ident: &Identifier) {}
+
+    fn visit_integer_literal(&mut self, _lit: &IntegerLiteral) {}
+
+    fn visit_float_literal(&mut self, _lit: &FloatLiteral) {}
+
+    fn visit_string_literal(&mut self, _lit: &StringLiteral) {}
+
+    fn visit_boolean(&mut self, _boolean: &Boolean) {}
+
+    fn visit_null_literal(&mut self, _lit: &NullLiteral) {}
+
+    fn visit_prefix_expression(&mut self, expr: &PrefixExpression) {
+        walk_prefix_expression(self, expr);
+    }
+
+    fn visit_infix_expression(&mut self, expr: &InfixExpression) {
+        walk_infix_expression(self, expr);
+    }
+
+    fn visit_typeof_expression(&mut self, expr: &TypeofExpression) {
+        walk_typeof_expression(self, expr);
+    }
+
+    fn visit_if_expression(&mut self, expr: &IfExpression) {
+        walk_if_expression(self, expr);
+    }
+
+    fn visit_function_literal(&mut self, func: &FunctionLiteral) {
+        walk_function_literal(self, func);
+    }
+
+    fn visit_call_expression(&mut self, call: &CallExpression) {
+        walk_call_expression(self, call);
+    }
+
+    fn visit_spread_expression(&mut self, expr: &SpreadExpression) {
+        walk_spread_expression(self, expr);
+    }
+
+    fn visit_array_literal(&mut self, array: &ArrayLiteral) {
+        walk_array_literal(self, array);
+    }
+
+    fn visit_hash_literal(&mut self, hash: &HashLiteral) {
+        walk_hash_literal(self, hash);
+    }
+
+    fn visit_match_expression(&mut self, expr: &MatchExpression) {
+        walk_match_expression(self, expr);
+    }
+
+    fn visit_try_catch_expression(&mut self, expr: &TryCatchExpression) {
+        walk_try_catch_expression(self, expr);
+    }
+
+    fn visit_array_destructure_pattern(&mut self, pattern: &ArrayDestructurePattern) {
+        walk_array_destructure_pattern(self, pattern);
+    }
+
+    fn visit_hash_destructure_pattern(&mut self, pattern: &HashDestructurePattern) {
+        walk_hash_destructure_pattern(self, pattern);
+    }
+
+    fn visit_wildcard_expression(&mut self, _expr: &WildcardExpression) {}
+
+    fn visit_index_expression(&mut self, expr: &IndexExpression) {
+        walk_index_expression(self, expr);
+    }
+
+    fn visit_index_assign_expression(&mut self, expr: &IndexAssignExpression) {
+        walk_index_assign_expression(self, expr);
+    }
+
+    fn visit_method_call_expression(&mut self, expr: &MethodCallExpression) {
+        walk_method_call_expression(self, expr);
+    }
+}
+
+pub fn walk_program<V: Visitor + ?Sized>(visitor: &mut V, program: &Program) {
+    for statement in &program.statements {
+        visitor.visit_statement(statement.as_ref());
+    }
+}
+
+pub fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &dyn Statement) {
+    let any = statement.as_any();
+
+    if let Some(stmt) = any.downcast_ref::<ExpressionStatement>() {
+        visitor.visit_expression_statement(stmt);
+    } else if let Some(stmt) = any.downcast_ref::<LetStatement>() {
+        visitor.visit_let_statement(stmt);
+    } else if let Some(stmt) = any.downcast_ref::<DestructureStatement>() {
+        visitor.visit_destructure_statement(stmt);
+    } else if let Some(stmt) = any.downcast_ref::<ConstStatement>() {
+        visitor.visit_const_statement(stmt);
+    } else if let Some(stmt) = any.downcast_ref::<AssignStatement>() {
+        visitor.visit_assign_statement(stmt);
+    } else if let Some(stmt) = any.downcast_ref::<ReturnStatement>() {
+        visitor.visit_return_statement(stmt);
+    } else if let Some(block) = any.downcast_ref::<BlockStatement>() {
+        visitor.visit_block_statement(block);
+    } else if let Some(stmt) = any.downcast_ref::<WhileStatement>() {
+        visitor.visit_while_statement(stmt);
+    } else if let Some(stmt) = any.downcast_ref::<DoWhileStatement>() {
+        visitor.visit_do_while_statement(stmt);
+    } else if let Some(stmt) = any.downcast_ref::<LoopStatement>() {
+        visitor.visit_loop_statement(stmt);
+    } else if let Some(stmt) = any.downcast_ref::<WhileLetStatement>() {
+        visitor.visit_while_let_statement(stmt);
+    } else if let Some(stmt) = any.downcast_ref::<ForStatement>() {
+        visitor.visit_for_statement(stmt);
+    } else if let Some(stmt) = any.downcast_ref::<BreakStatement>() {
+        visitor.visit_break_statement(stmt);
+    } else if let Some(stmt) = any.downcast_ref::<ContinueStatement>() {
+        visitor.visit_continue_statement(stmt);
+    } else if let Some(stmt) = any.downcast_ref::<LabeledStatement>() {
+        visitor.visit_labeled_statement(stmt);
+    }
+}
+
+pub fn walk_expression<V: Visitor + ?Sized>(visitor: &mut V, expression: &dyn Expression) {
+    let any = expression.as_any();
+
+    if let Some(ident) = any.downcast_ref::<Identifier>() {
+        visitor.visit_identifier(ident);
+    } else if let Some(lit) = any.downcast_ref::<IntegerLiteral>() {
+        visitor.visit_integer_literal(lit);
+    } else if let Some(lit) = any.downcast_ref::<FloatLiteral>() {
+        visitor.visit_float_literal(lit);
+    } else if let Some(lit) = any.downcast_ref::<StringLiteral>() {
+        visitor.visit_string_literal(lit);
+    } else if let Some(boolean) = any.downcast_ref::<Boolean>() {
+        visitor.visit_boolean(boolean);
+    } else if let Some(lit) = any.downcast_ref::<NullLiteral>() {
+        visitor.visit_null_literal(lit);
+    } else if let Some(expr) = any.downcast_ref::<PrefixExpression>() {
+        visitor.visit_prefix_expression(expr);
+    } else if let Some(expr) = any.downcast_ref::<InfixExpression>() {
+        visitor.visit_infix_expression(expr);
+    } else if let Some(expr) = any.downcast_ref::<TypeofExpression>() {
+        visitor.visit_typeof_expression(expr);
+    } else if let Some(expr) = any.downcast_ref::<IfExpression>() {
+        visitor.visit_if_expression(expr);
+    } else if let Some(func) = any.downcast_ref::<FunctionLiteral>() {
+        visitor.visit_function_literal(func);
+    } else if let Some(call) = any.downcast_ref::<CallExpression>() {
+        visitor.visit_call_expression(call);
+    } else if let Some(expr) = any.downcast_ref::<SpreadExpression>() {
+        visitor.visit_spread_expression(expr);
+    } else if let Some(array) = any.downcast_ref::<ArrayLiteral>() {
+        visitor.visit_array_literal(array);
+    } else if let Some(hash) = any.downcast_ref::<HashLiteral>() {
+        visitor.visit_hash_literal(hash);
+    } else if let Some(expr) = any.downcast_ref::<MatchExpression>() {
+        visitor.visit_match_expression(expr);
+    } else if let Some(expr) = any.downcast_ref::<TryCatchExpression>() {
+        visitor.visit_try_catch_expression(expr);
+    } else if let Some(pattern) = any.downcast_ref::<ArrayDestructurePattern>() {
+        visitor.visit_array_destructure_pattern(pattern);
+    } else if let Some(pattern) = any.downcast_ref::<HashDestructurePattern>() {
+        visitor.visit_hash_destructure_pattern(pattern);
+    } else if let Some(expr) = any.downcast_ref::<WildcardExpression>() {
+        visitor.visit_wildcard_expression(expr);
+    } else if let Some(expr) = any.downcast_ref::<IndexExpression>() {
+        visitor.visit_index_expression(expr);
+    } else if let Some(expr) = any.downcast_ref::<IndexAssignExpression>() {
+        visitor.visit_index_assign_expression(expr);
+    } else if let Some(expr) = any.downcast_ref::<MethodCallExpression>() {
+        visitor.visit_method_call_expression(expr);
+    }
+}
+
+pub fn walk_let_statement<V: Visitor + ?Sized>(visitor: &mut V, stmt: &LetStatement) {
+    visitor.visit_identifier(&stmt.name);
+    if let Some(value) = &stmt.value {
+        visitor.visit_expression(value.as_ref());
+    }
+}
+
+pub fn walk_destructure_statement<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    stmt: &DestructureStatement,
+) {
+    visitor.visit_expression(stmt.pattern.as_ref());
+    visitor.visit_expression(stmt.value.as_ref());
+}
+
+pub fn walk_const_statement<V: Visitor + ?Sized>(visitor: &mut V, stmt: &ConstStatement) {
+    visitor.visit_identifier(&stmt.name);
+    visitor.visit_expression(stmt.value.as_ref());
+}
+
+pub fn walk_assign_statement<V: Visitor + ?Sized>(visitor: &mut V, stmt: &AssignStatement) {
+    visitor.visit_identifier(&stmt.target);
+    visitor.visit_expression(stmt.value.as_ref());
+}
+
+pub fn walk_return_statement<V: Visitor + ?Sized>(visitor: &mut V, stmt: &ReturnStatement) {
+    if let Some(value) = &stmt.return_value {
+        visitor.visit_expression(value.as_ref());
+    }
+}
+
+pub fn walk_expression_statement<V: Visitor + ?Sized>(visitor: &mut V, stmt: &ExpressionStatement) {
+    visitor.visit_expression(stmt.expression.as_ref());
+}
+
+pub fn walk_block_statement<V: Visitor + ?Sized>(visitor: &mut V, block: &BlockStatement) {
+    for statement in &block.statements {
+        visitor.visit_statement(statement.as_ref());
+    }
+}
+
+pub fn walk_while_statement<V: Visitor + ?Sized>(visitor: &mut V, stmt: &WhileStatement) {
+    visitor.visit_expression(stmt.condition.as_ref());
+    visitor.visit_block_statement(&stmt.body);
+}
+
+pub fn walk_do_while_statement<V: Visitor + ?Sized>(visitor: &mut V, stmt: &DoWhileStatement) {
+    visitor.visit_block_statement(&stmt.body);
+    visitor.visit_expression(stmt.condition.as_ref());
+}
+
+pub fn walk_loop_statement<V: Visitor + ?Sized>(visitor: &mut V, stmt: &LoopStatement) {
+    visitor.visit_block_statement(&stmt.body);
+}
+
+pub fn walk_while_let_statement<V: Visitor + ?Sized>(visitor: &mut V, stmt: &WhileLetStatement) {
+    visitor.visit_identifier(&stmt.pattern);
+    visitor.visit_expression(stmt.expression.as_ref());
+    visitor.visit_block_statement(&stmt.body);
+}
+
+pub fn walk_for_statement<V: Visitor + ?Sized>(visitor: &mut V, stmt: &ForStatement) {
+    visitor.visit_identifier(&stmt.iterator_name);
+    visitor.visit_expression(stmt.iterable.as_ref());
+    visitor.visit_block_statement(&stmt.body);
+}
+
+pub fn walk_labeled_statement<V: Visitor + ?Sized>(visitor: &mut V, stmt: &LabeledStatement) {
+    visitor.visit_statement(stmt.body.as_ref());
+}
+
+pub fn walk_prefix_expression<V: Visitor + ?Sized>(visitor: &mut V, expr: &PrefixExpression) {
+    visitor.visit_expression(expr.right.as_ref());
+}
+
+pub fn walk_infix_expression<V: Visitor + ?Sized>(visitor: &mut V, expr: &InfixExpression) {
+    visitor.visit_expression(expr.left.as_ref());
+    visitor.visit_expression(expr.right.as_ref());
+}
+
+pub fn walk_typeof_expression<V: Visitor + ?Sized>(visitor: &mut V, expr: &TypeofExpression) {
+    visitor.visit_expression(expr.expr.as_ref());
+}
+
+pub fn walk_if_expression<V: Visitor + ?Sized>(visitor: &mut V, expr: &IfExpression) {
+    visitor.visit_expression(expr.condition.as_ref());
+    visitor.visit_block_statement(&expr.consequence);
+    if let Some(alternative) = &expr.alternative {
+        visitor.visit_block_statement(alternative);
+    }
+}
+
+pub fn walk_function_literal<V: Visitor + ?Sized>(visitor: &mut V, func: &FunctionLiteral) {
+    if let Some(name) = &func.name {
+        visitor.visit_identifier(name);
+    }
+    for param in &func.parameters {
+        visitor.visit_identifier(param);
+    }
+    if let Some(rest) = &func.rest {
+        visitor.visit_identifier(rest);
+    }
+    visitor.visit_block_statement(&func.body);
+}
+
+pub fn walk_call_expression<V: Visitor + ?Sized>(visitor: &mut V, call: &CallExpression) {
+    visitor.visit_expression(call.function.as_ref());
+    for arg in &call.arguments {
+        visitor.visit_expression(arg.as_ref());
+    }
+}
+
+pub fn walk_spread_expression<V: Visitor + ?Sized>(visitor: &mut V, expr: &SpreadExpression) {
+    visitor.visit_expression(expr.value.as_ref());
+}
+
+pub fn walk_array_literal<V: Visitor + ?Sized>(visitor: &mut V, array: &ArrayLiteral) {
+    for element in &array.elements {
+        visitor.visit_expression(element.as_ref());
+    }
+}
+
+pub fn walk_hash_literal<V: Visitor + ?Sized>(visitor: &mut V, hash: &HashLiteral) {
+    for (key, value) in &hash.pairs {
+        visitor.visit_expression(key.as_ref());
+        visitor.visit_expression(value.as_ref());
+    }
+}
+
+pub fn walk_match_expression<V: Visitor + ?Sized>(visitor: &mut V, expr: &MatchExpression) {
+    visitor.visit_expression(expr.subject.as_ref());
+    for arm in &expr.arms {
+        visitor.visit_expression(arm.pattern.as_ref());
+        visitor.visit_expression(arm.body.as_ref());
+    }
+}
+
+pub fn walk_try_catch_expression<V: Visitor + ?Sized>(visitor: &mut V, expr: &TryCatchExpression) {
+    visitor.visit_block_statement(&expr.try_block);
+    visitor.visit_identifier(&expr.error_name);
+    visitor.visit_block_statement(&expr.catch_block);
+}
+
+pub fn walk_index_expression<V: Visitor + ?Sized>(visitor: &mut V, expr: &IndexExpression) {
+    visitor.visit_expression(expr.left.as_ref());
+    visitor.visit_expression(expr.index.as_ref());
+}
+
+pub fn walk_index_assign_expression<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    expr: &IndexAssignExpression,
+) {
+    visitor.visit_index_expression(&expr.target);
+    visitor.visit_expression(expr.value.as_ref());
+}
+
+pub fn walk_array_destructure_pattern<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    pattern: &ArrayDestructurePattern,
+) {
+    for name in pattern.elements.iter().flatten() {
+        visitor.visit_identifier(name);
+    }
+}
+
+pub fn walk_hash_destructure_pattern<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    pattern: &HashDestructurePattern,
+) {
+    for (key, name) in &pattern.pairs {
+        visitor.visit_identifier(key);
+        visitor.visit_identifier(name);
+    }
+}
+
+pub fn walk_method_call_expression<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    expr: &MethodCallExpression,
+) {
+    visitor.visit_expression(expr.object.as_ref());
+    visitor.visit_identifier(&expr.method);
+    for arg in &expr.arguments {
+        visitor.visit_expression(arg.as_ref());
+    }
+}
+
+/// Renders an AST as an indented tree, one node per line
+///
+/// ```text
+/// Program
+///   LetStatement
+///     Identifier x
+///     InfixExpression +
+///       IntegerLiteral 1
+///       IntegerLiteral 2
+/// ```
+#[derive(Debug, Default)]
+pub struct PrintVisitor {
+    depth: usize,
+    output: String,
+}
+
+impl PrintVisitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renders `program` as a tree
+    pub fn print(program: &Program) -> String {
+        let mut visitor = PrintVisitor::new();
+        visitor.visit_program(program);
+        visitor.output
+    }
+
+    /// The tree rendered so far
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+
+    /// Writes a line for a node, then visits its children one level deeper
+    fn node(&mut self, label: &str, walk: impl FnOnce(&mut Self)) {
+        self.output.push_str(&"  ".repeat(self.depth));
+        self.output.push_str(label);
+        self.output.push('\n');
+
+        self.depth += 1;
+        walk(self);
+        self.depth -= 1;
+    }
+}
+
+impl Visitor for PrintVisitor {
+    fn visit_program(&mut self, program: &Program) {
+        self.node("Program", |v| walk_program(v, program));
+    }
+
+    fn visit_let_statement(&mut self, stmt: &LetStatement) {
+        self.node("LetStatement", |v| walk_let_statement(v, stmt));
+    }
+
+    fn visit_destructure_statement(&mut self, stmt: &DestructureStatement) {
+        self.node("DestructureStatement", |v| {
+            walk_destructure_statement(v, stmt)
+        });
+    }
+
+    fn visit_const_statement(&mut self, stmt: &ConstStatement) {
+        self.node("ConstStatement", |v| walk_const_statement(v, stmt));
+    }
+
+    fn visit_assign_statement(&mut self, stmt: &AssignStatement) {
+        self.node("AssignStatement", |v| walk_assign_statement(v, stmt));
+    }
+
+    fn visit_return_statement(&mut self, stmt: &ReturnStatement) {
+        self.node("ReturnStatement", |v| walk_return_statement(v, stmt));
+    }
+
+    fn visit_expression_statement(&mut self, stmt: &ExpressionStatement) {
+        self.node("ExpressionStatement", |v| {
+            walk_expression_statement(v, stmt)
+        });
+    }
+
+    fn visit_block_statement(&mut self, block: &BlockStatement) {
+        self.node("BlockStatement", |v| walk_block_statement(v, block));
+    }
+
+    fn visit_while_statement(&mut self, stmt: &WhileStatement) {
+        self.node("WhileStatement", |v| walk_while_statement(v, stmt));
+    }
+
+    fn visit_do_while_statement(&mut self, stmt: &DoWhileStatement) {
+        self.node("DoWhileStatement", |v| walk_do_while_statement(v, stmt));
+    }
+
+    fn visit_loop_statement(&mut self, stmt: &LoopStatement) {
+        self.node("LoopStatement", |v| walk_loop_statement(v, stmt));
+    }
+
+    fn visit_while_let_statement(&mut self, stmt: &WhileLetStatement) {
+        self.node("WhileLetStatement", |v| walk_while_let_statement(v, stmt));
+    }
+
+    fn visit_for_statement(&mut self, stmt: &ForStatement) {
+        self.node("ForStatement", |v| walk_for_statement(v, stmt));
+    }
+
+    fn visit_break_statement(&mut self, stmt: &BreakStatement) {
+        match &stmt.label {
+            Some(label) => self.node(&format!("BreakStatement '{}", label), |_| {}),
+            None => self.node("BreakStatement", |_| {}),
+        }
+    }
+
+    fn visit_continue_statement(&mut self, stmt: &ContinueStatement) {
+        match &stmt.label {
+            Some(label) => self.node(&format!("ContinueStatement '{}", label), |_| {}),
+            None => self.node("ContinueStatement", |_| {}),
+        }
+    }
+
+    fn visit_labeled_statement(&mut self, stmt: &LabeledStatement) {
+        self.node(&format!("LabeledStatement '{}", stmt.label), |v| {
+            walk_labeled_statement(v, stmt)
+        });
+    }
+
+    fn visit_identifier(&mut self, ident: &Identifier) {
+        self.node(&format!("Identifier {}", ident.value), |_| {});
+    }
+
+    fn visit_integer_literal(&mut self, lit: &IntegerLiteral) {
+        self.node(&format!("IntegerLiteral {}", lit.value), |_| {});
+    }
+
+    fn visit_float_literal(&mut self, lit: &FloatLiteral) {
+        self.node(&format!("FloatLiteral {}", lit.token.literal), |_| {});
+    }
+
+    fn visit_string_literal(&mut self, lit: &StringLiteral) {
+        self.node(&format!("StringLiteral {:?}", lit.value), |_| {});
+    }
+
+    fn visit_boolean(&mut self, boolean: &Boolean) {
+        self.node(&format!("Boolean {}", boolean.value), |_| {});
+    }
+
+    fn visit_null_literal(&mut self, _lit: &NullLiteral) {
+        self.node("NullLiteral", |_| {});
+    }
+
+    fn visit_prefix_expression(&mut self, expr: &PrefixExpression) {
+        self.node(&format!("PrefixExpression {}", expr.operator), |v| {
+            walk_prefix_expression(v, expr)
+        });
+    }
+
+    fn visit_infix_expression(&mut self, expr: &InfixExpression) {
+        self.node(&format!("InfixExpression {}", expr.operator), |v| {
+            walk_infix_expression(v, expr)
+        });
+    }
+
+    fn visit_typeof_expression(&mut self, expr: &TypeofExpression) {
+        self.node("TypeofExpression", |v| walk_typeof_expression(v, expr));
+    }
+
+    fn visit_if_expression(&mut self, expr: &IfExpression) {
+        self.node("IfExpression", |v| walk_if_expression(v, expr));
+    }
+
+    fn visit_function_literal(&mut self, func: &FunctionLiteral) {
+        self.node("FunctionLiteral", |v| walk_function_literal(v, func));
+    }
+
+    fn visit_call_expression(&mut self, call: &CallExpression) {
+        self.node("CallExpression", |v| walk_call_expression(v, call));
+    }
+
+    fn visit_spread_expression(&mut self, expr: &SpreadExpression) {
+        self.node("SpreadExpression", |v| walk_spread_expression(v, expr));
+    }
+
+    fn visit_array_literal(&mut self, array: &ArrayLiteral) {
+        self.node("ArrayLiteral", |v| walk_array_literal(v, array));
+    }
+
+    fn visit_hash_literal(&mut self, hash: &HashLiteral) {
+        self.node("HashLiteral", |v| walk_hash_literal(v, hash));
+    }
+
+    fn visit_match_expression(&mut self, expr: &MatchExpression) {
+        self.node("MatchExpression", |v| walk_match_expression(v, expr));
+    }
+
+    fn visit_try_catch_expression(&mut self, expr: &TryCatchExpression) {
+        self.node("TryCatchExpression", |v| walk_try_catch_expression(v, expr));
+    }
+
+    fn visit_array_destructure_pattern(&mut self, pattern: &ArrayDestructurePattern) {
+        self.node("ArrayDestructurePattern", |v| {
+            walk_array_destructure_pattern(v, pattern)
+        });
+    }
+
+    fn visit_hash_destructure_pattern(&mut self, pattern: &HashDestructurePattern) {
+        self.node("HashDestructurePattern", |v| {
+            walk_hash_destructure_pattern(v, pattern)
+        });
+    }
+
+    fn visit_wildcard_expression(&mut self, _expr: &WildcardExpression) {
+        self.node("WildcardExpression", |_| {});
+    }
+
+    fn visit_index_expression(&mut self, expr: &IndexExpression) {
+        self.node("IndexExpression", |v| walk_index_expression(v, expr));
+    }
+
+    fn visit_index_assign_expression(&mut self, expr: &IndexAssignExpression) {
+        self.node("IndexAssignExpression", |v| {
+            walk_index_assign_expression(v, expr)
+        });
+    }
+
+    fn visit_method_call_expression(&mut self, expr: &MethodCallExpression) {
+        self.node("MethodCallExpression", |v| {
+            walk_method_call_expression(v, expr)
+        });
+    }
+}
+
+/// Writes out the tokens of an AST in source order
+///
+/// Nodes keep their leading token, which is written as is so literals and
+/// positions survive. Punctuation the parser consumed without storing, such
+/// as closing brackets, commas and semicolons, is written as new tokens with
+/// no position. Parentheses are written only where the tree could not be
+/// parsed back without them.
+#[derive(Debug, Default)]
+struct TokenWriter {
+    tokens: Vec<Token>,
+}
+
+impl TokenWriter {
+    fn token(&mut self, token: &Token) {
+        self.tokens.push(token.clone());
+    }
+
+    fn punct(&mut self, token_type: TokenType, literal: &str) {
+        self.tokens
+            .push(Token::new(token_type, literal.to_string()));
+    }
+
+    fn identifier(&mut self, ident: &Identifier) {
+        self.token(&ident.token);
+    }
+
+    /// Writes `expr`, in parentheses if `grouped`
+    fn operand(&mut self, expr: &dyn Expression, grouped: bool) {
+        if grouped {
+            self.punct(TokenType::Lparen, "(");
+        }
+        self.visit_expression(expr);
+        if grouped {
+            self.punct(TokenType::Rparen, ")");
+        }
+    }
+
+    /// Writes `items` separated by commas
+    fn list<T>(&mut self, items: &[T], mut write: impl FnMut(&mut Self, &T)) {
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                self.punct(TokenType::Comma, ",");
+            }
+            write(self, item);
+        }
+    }
+
+    fn arguments(&mut self, args: &[Box<dyn Expression>]) {
+        self.punct(TokenType::Lparen, "(");
+        self.list(args, |w, arg| w.visit_expression(arg.as_ref()));
+        self.punct(TokenType::Rparen, ")");
+    }
+
+    fn label(&mut self, label: &Option<String>) {
+        if let Some(label) = label {
+            self.punct(TokenType::Label, label);
+        }
+    }
+
+    fn semicolon(&mut self) {
+        self.punct(TokenType::Semicolon, ";");
+    }
+}
+
+/// How tightly an expression holds together when it is the operand of
+/// another: an operator's precedence, or the highest for anything else
+fn binding_precedence(expr: &dyn Expression) -> Precedence {
+    let any = expr.as_any();
+    if let Some(infix) = any.downcast_ref::<InfixExpression>() {
+        Precedence::from_token_type(&infix.token.token_type)
+    } else if any.is::<IndexAssignExpression>() {
+        Precedence::Assign
+    } else if any.is::<PrefixExpression>() || any.is::<TypeofExpression>() {
+        Precedence::Prefix
+    } else {
+        Precedence::Call
+    }
+}
+
+impl Visitor for TokenWriter {
+    fn visit_program(&mut self, program: &Program) {
+        walk_program(self, program);
+        self.punct(TokenType::Eof, "");
+    }
+
+    fn visit_let_statement(&mut self, stmt: &LetStatement) {
+        // `fn name() {}` is parsed as a let whose 'let' token takes the
+        // position of the 'fn' token
+        if let Some(func) = stmt
+            .value
+            .as_ref()
+            .and_then(|value| value.as_any().downcast_ref::<FunctionLiteral>())
+        {
+            if (func.token.line, func.token.column) == (stmt.token.line, stmt.token.column) {
+                self.visit_function_literal(func);
+                return;
+            }
+        }
+
+        self.token(&stmt.token);
+        self.identifier(&stmt.name);
+        if let Some(value) = &stmt.value {
+            self.punct(TokenType::Assign, "=");
+            self.visit_expression(value.as_ref());
+        }
+        self.semicolon();
+    }
+
+    fn visit_destructure_statement(&mut self, stmt: &DestructureStatement) {
+        self.token(&stmt.token);
+        self.visit_expression(stmt.pattern.as_ref());
+        self.punct(TokenType::Assign, "=");
+        self.visit_expression(stmt.value.as_ref());
+        self.semicolon();
+    }
+
+    fn visit_const_statement(&mut self, stmt: &ConstStatement) {
+        self.token(&stmt.token);
+        self.identifier(&stmt.name);
+        self.punct(TokenType::Assign, "=");
+        self.visit_expression(stmt.value.as_ref());
+        self.semicolon();
+    }
+
+    fn visit_assign_statement(&mut self, stmt: &AssignStatement) {
+        self.identifier(&stmt.target);
+        self.token(&stmt.token);
+        self.visit_expression(stmt.value.as_ref());
+        self.semicolon();
+    }
+
+    fn visit_return_statement(&mut self, stmt: &ReturnStatement) {
+        self.token(&stmt.token);
+        if let Some(value) = &stmt.return_value {
+            self.visit_expression(value.as_ref());
+        }
+        self.semicolon();
+    }
+
+    fn visit_block_statement(&mut self, block: &BlockStatement) {
+        self.token(&block.token);
+        walk_block_statement(self, block);
+        self.punct(TokenType::Rbrace, "}");
+    }
+
+    fn visit_while_statement(&mut self, stmt: &WhileStatement) {
+        self.token(&stmt.token);
+        self.operand(stmt.condition.as_ref(), true);
+        self.visit_block_statement(&stmt.body);
+    }
+
+    fn visit_do_while_statement(&mut self, stmt: &DoWhileStatement) {
+        self.token(&stmt.token);
+        self.visit_block_statement(&stmt.body);
+        self.punct(TokenType::While, "while");
+        self.operand(stmt.condition.as_ref(), true);
+        self.semicolon();
+    }
+
+    fn visit_loop_statement(&mut self, stmt: &LoopStatement) {
+        self.token(&stmt.token);
+        self.visit_block_statement(&stmt.body);
+    }
+
+    fn visit_while_let_statement(&mut self, stmt: &WhileLetStatement) {
+        self.token(&stmt.token);
+        self.punct(TokenType::Let, "let");
+        self.identifier(&stmt.pattern);
+        self.punct(TokenType::Assign, "=");
+        self.visit_expression(stmt.expression.as_ref());
+        self.visit_block_statement(&stmt.body);
+    }
+
+    fn visit_for_statement(&mut self, stmt: &ForStatement) {
+        self.token(&stmt.token);
+        self.punct(TokenType::Lparen, "(");
+        self.identifier(&stmt.iterator_name);
+        self.punct(TokenType::In, "in");
+        self.visit_expression(stmt.iterable.as_ref());
+        self.punct(TokenType::Rparen, ")");
+        self.visit_block_statement(&stmt.body);
+    }
+
+    fn visit_break_statement(&mut self, stmt: &BreakStatement) {
+        self.token(&stmt.token);
+        self.label(&stmt.label);
+        self.semicolon();
+    }
+
+    fn visit_continue_statement(&mut self, stmt: &ContinueStatement) {
+        self.token(&stmt.token);
+        self.label(&stmt.label);
+        self.semicolon();
+    }
+
+    fn visit_labeled_statement(&mut self, stmt: &LabeledStatement) {
+        self.token(&stmt.token);
+        self.punct(TokenType::Colon, ":");
+        self.visit_statement(stmt.body.as_ref());
+    }
+
+    fn visit_identifier(&mut self, ident: &Identifier) {
+        self.identifier(ident);
+    }
+
+    fn visit_integer_literal(&mut self, lit: &IntegerLiteral) {
+        self.token(&lit.token);
+    }
+
+    fn visit_float_literal(&mut self, lit: &FloatLiteral) {
+        self.token(&lit.token);
+    }
+
+    fn visit_string_literal(&mut self, lit: &StringLiteral) {
+        self.token(&lit.token);
+    }
+
+    fn visit_boolean(&mut self, boolean: &Boolean) {
+        self.token(&boolean.token);
+    }
+
+    fn visit_null_literal(&mut self, lit: &NullLiteral) {
+        self.token(&lit.token);
+    }
+
+    fn visit_prefix_expression(&mut self, expr: &PrefixExpression) {
+        self.token(&expr.token);
+        let grouped = binding_precedence(expr.right.as_ref()) < Precedence::Prefix;
+        self.operand(expr.right.as_ref(), grouped);
+    }
+
+    fn visit_infix_expression(&mut self, expr: &InfixExpression) {
+        // operators group to the left, except `**` which groups to the right
+        let precedence = Precedence::from_token_type(&expr.token.token_type);
+        let right_assoc = precedence == Precedence::Power;
+
+        let left = binding_precedence(expr.left.as_ref());
+        self.operand(
+            expr.left.as_ref(),
+            left < precedence || (left == precedence && right_assoc),
+        );
+        self.token(&expr.token);
+        let right = binding_precedence(expr.right.as_ref());
+        self.operand(
+            expr.right.as_ref(),
+            right < precedence || (right == precedence && !right_assoc),
+        );
+    }
+
+    fn visit_typeof_expression(&mut self, expr: &TypeofExpression) {
+        self.token(&expr.token);
+        let grouped = binding_precedence(expr.expr.as_ref()) < Precedence::Prefix;
+        self.operand(expr.expr.as_ref(), grouped);
+    }
+
+    fn visit_if_expression(&mut self, expr: &IfExpression) {
+        self.token(&expr.token);
+        self.operand(expr.condition.as_ref(), true);
+
+        // `unless` is parsed with its branches swapped, and its body is empty
+        // if it has no else
+        if expr.token.token_type == TokenType::Unless {
+            if let Some(body) = &expr.alternative {
+                self.visit_block_statement(body);
+            }
+            if !expr.consequence.statements.is_empty() {
+                self.punct(TokenType::Else, "else");
+                self.visit_block_statement(&expr.consequence);
+            }
+            return;
+        }
+
+        self.visit_block_statement(&expr.consequence);
+        if let Some(alternative) = &expr.alternative {
+            self.punct(TokenType::Else, "else");
+            self.visit_block_statement(alternative);
+        }
+    }
+
+    fn visit_function_literal(&mut self, func: &FunctionLiteral) {
+        self.token(&func.token);
+        if let Some(name) = &func.name {
+            self.identifier(name);
+        }
+        self.punct(TokenType::Lparen, "(");
+        self.list(&func.parameters, |w, param| w.identifier(param));
+        if let Some(rest) = &func.rest {
+            if !func.parameters.is_empty() {
+                self.punct(TokenType::Comma, ",");
+            }
+            self.punct(TokenType::Ellipsis, "...");
+            self.identifier(rest);
+        }
+        self.punct(TokenType::Rparen, ")");
+        self.visit_block_statement(&func.body);
     }
-}
 
-impl Expression for Identifier {
-    fn expression_node(&self) {}
+    fn visit_call_expression(&mut self, call: &CallExpression) {
+        let grouped = binding_precedence(call.function.as_ref()) < Precedence::Index;
+        self.operand(call.function.as_ref(), grouped);
+        self.arguments(&call.arguments);
+    }
 
-    fn as_any(&self) -> &dyn Any {
-        self
+    fn visit_spread_expression(&mut self, expr: &SpreadExpression) {
+        self.token(&expr.token);
+        self.visit_expression(expr.value.as_ref());
     }
 
-    fn clone_box(&self) -> Box<dyn Expression> {
-        Box::new(self.clone())
+    fn visit_array_literal(&mut self, array: &ArrayLiteral) {
+        self.token(&array.token);
+        self.list(&array.elements, |w, element| {
+            w.visit_expression(element.as_ref())
+        });
+        self.punct(TokenType::Rbracket, "]");
     }
-}
 
-impl Expression for Boolean {
-    fn expression_node(&self) {}
+    fn visit_hash_literal(&mut self, hash: &HashLiteral) {
+        self.token(&hash.token);
+        self.list(&hash.pairs, |w, (key, value)| {
+            w.visit_expression(key.as_ref());
+            w.punct(TokenType::Colon, ":");
+            w.visit_expression(value.as_ref());
+        });
+        self.punct(TokenType::Rbrace, "}");
+    }
 
-    fn as_any(&self) -> &dyn Any {
-        self
+    fn visit_match_expression(&mut self, expr: &MatchExpression) {
+        self.token(&expr.token);
+        self.operand(expr.subject.as_ref(), true);
+        self.punct(TokenType::Lbrace, "{");
+        self.list(&expr.arms, |w, arm| {
+            w.visit_expression(arm.pattern.as_ref());
+            w.punct(TokenType::Arrow, "=>");
+            w.visit_expression(arm.body.as_ref());
+        });
+        self.punct(TokenType::Rbrace, "}");
     }
 
-    fn clone_box(&self) -> Box<dyn Expression> {
-        Box::new(Boolean {
-            token: self.token.clone(),
-            value: self.value,
-        })
+    fn visit_try_catch_expression(&mut self, expr: &TryCatchExpression) {
+        self.token(&expr.token);
+        self.visit_block_statement(&expr.try_block);
+        self.punct(TokenType::Catch, "catch");
+        self.punct(TokenType::Lparen, "(");
+        self.identifier(&expr.error_name);
+        self.punct(TokenType::Rparen, ")");
+        self.visit_block_statement(&expr.catch_block);
     }
-}
 
-impl Expression for IfExpression {
-    fn expression_node(&self) {}
+    fn visit_array_destructure_pattern(&mut self, pattern: &ArrayDestructurePattern) {
+        self.token(&pattern.token);
+        self.list(&pattern.elements, |w, name| match name {
+            Some(name) => w.identifier(name),
+            None => w.punct(TokenType::Ident, "_"),
+        });
+        self.punct(TokenType::Rbracket, "]");
+    }
 
-    fn as_any(&self) -> &dyn Any {
-        self
+    fn visit_hash_destructure_pattern(&mut self, pattern: &HashDestructurePattern) {
+        self.token(&pattern.token);
+        self.list(&pattern.pairs, |w, (key, name)| {
+            w.identifier(key);
+            // `{x}` is parsed with the key doubling as the name
+            let shorthand =
+                (key.token.line, key.token.column) == (name.token.line, name.token.column);
+            if !shorthand {
+                w.punct(TokenType::Colon, ":");
+                w.identifier(name);
+            }
+        });
+        self.punct(TokenType::Rbrace, "}");
     }
 
-    fn clone_box(&self) -> Box<dyn Expression> {
-        Box::new(IfExpression {
-            token: self.token.clone(),
-            condition: self.condition.clone_box(),
-            consequence: self.consequence.clone(),
-            alternative: self.alternative.clone(),
-        })
+    fn visit_wildcard_expression(&mut self, expr: &WildcardExpression) {
+        self.token(&expr.token);
     }
-}
 
-impl Expression for FunctionLiteral {
-    fn expression_node(&self) {}
+    fn visit_index_expression(&mut self, expr: &IndexExpression) {
+        let grouped = binding_precedence(expr.left.as_ref()) < Precedence::Index;
+        self.operand(expr.left.as_ref(), grouped);
+        self.token(&expr.token);
+        self.visit_expression(expr.index.as_ref());
+        self.punct(TokenType::Rbracket, "]");
+    }
 
-    fn as_any(&self) -> &dyn Any {
-        self
+    fn visit_index_assign_expression(&mut self, expr: &IndexAssignExpression) {
+        self.visit_index_expression(&expr.target);
+        self.token(&expr.token);
+        self.visit_expression(expr.value.as_ref());
     }
 
-    fn clone_box(&self) -> Box<dyn Expression> {
-        Box::new(self.clone())
+    fn visit_method_call_expression(&mut self, expr: &MethodCallExpression) {
+        let grouped = binding_precedence(expr.object.as_ref()) < Precedence::Index;
+        self.operand(expr.object.as_ref(), grouped);
+        self.token(&expr.token);
+        self.identifier(&expr.method);
+        self.arguments(&expr.arguments);
     }
 }
 
-impl Expression for CallExpression {
-    fn expression_node(&self) {}
+/// Transformer
+///
+/// Rewrites an AST by taking ownership of each node and returning its
+/// replacement. Every `transform_*` method defaults to the matching `fold_*`
+/// function, which rebuilds the node from its transformed children, so an
+/// implementation overrides only the nodes it rewrites.
+pub trait Transformer {
+    fn transform_program(&self, program: Program) -> Program {
+        fold_program(self, program)
+    }
 
-    fn as_any(&self) -> &dyn Any {
-        self
+    fn transform_statement(&self, statement: Box<dyn Statement>) -> Box<dyn Statement> {
+        fold_statement(self, statement)
     }
 
-    fn clone_box(&self) -> Box<dyn Expression> {
-        let mut arguments = Vec::new();
-        for arg in &self.arguments {
-            arguments.push(arg.clone_box());
-        }
+    fn transform_expression(&self, expression: Box<dyn Expression>) -> Box<dyn Expression> {
+        fold_expression(self, expression)
+    }
 
-        Box::new(CallExpression {
-            token: self.token.clone(),
-            function: self.function.clone_box(),
-            arguments,
-        })
+    fn transform_block_statement(&self, block: BlockStatement) -> BlockStatement {
+        fold_block_statement(self, block)
     }
 }
 
-impl fmt::Display for Program {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for s in &self.statements {
-            write!(f, "{}", s)?;
-        }
-        Ok(())
+/// Downcasts an owned statement, handing it back unchanged on a type mismatch
+fn downcast_statement<T: Statement>(
+    statement: Box<dyn Statement>,
+) -> Result<Box<T>, Box<dyn Statement>> {
+    if statement.as_any().is::<T>() {
+        let any: Box<dyn Any> = statement;
+        Ok(any.downcast().expect("type was checked"))
+    } else {
+        Err(statement)
     }
 }
 
-impl fmt::Display for dyn Statement {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let Some(stmt) = self.as_any().downcast_ref::<ExpressionStatement>() {
-            return write!(f, "{}", stmt);
-        }
-        if let Some(stmt) = self.as_any().downcast_ref::<LetStatement>() {
-            return write!(f, "{}", stmt);
-        }
-        if let Some(stmt) = self.as_any().downcast_ref::<ReturnStatement>() {
-            return write!(f, "{}", stmt);
-        }
-        if let Some(stmt) = self.as_any().downcast_ref::<BlockStatement>() {
-            return write!(f, "{}", stmt);
-        }
-        if let Some(expr) = self.as_any().downcast_ref::<IfExpression>() {
-            return write!(f, "{}", expr);
-        }
-        write!(f, "{}", self.token_literal())
+/// Downcasts an owned expression, handing it back unchanged on a type mismatch
+fn downcast_expression<T: Expression>(
+    expression: Box<dyn Expression>,
+) -> Result<Box<T>, Box<dyn Expression>> {
+    if expression.as_any().is::<T>() {
+        let any: Box<dyn Any> = expression;
+        Ok(any.downcast().expect("type was checked"))
+    } else {
+        Err(expression)
     }
 }
 
-impl fmt::Display for LetStatement {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{} {} = ",
-            self.token_literal(),
-            self.name.token_literal()
-        )?;
-        if let Some(value) = &self.value {
-            write!(f, "{}", value)?;
-        }
-        write!(f, ";")
+pub fn fold_program<T: Transformer + ?Sized>(transformer: &T, program: Program) -> Program {
+    Program {
+        statements: program
+            .statements
+            .into_iter()
+            .map(|statement| transformer.transform_statement(statement))
+            .collect(),
     }
 }
 
-impl fmt::Display for ReturnStatement {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} ", self.token_literal())?;
-        if let Some(value) = &self.return_value {
-            write!(f, "{}", value)?;
-        }
-        write!(f, ";")
-    }
+pub fn fold_block_statement<T: Transformer + ?Sized>(
+    transformer: &T,
+    mut block: BlockStatement,
+) -> BlockStatement {
+    block.statements = block
+        .statements
+        .into_iter()
+        .map(|statement| transformer.transform_statement(statement))
+        .collect();
+    block
 }
 
-impl fmt::Display for DummyExpression {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "")
-    }
+fn fold_index_expression<T: Transformer + ?Sized>(
+    transformer: &T,
+    mut expr: IndexExpression,
+) -> IndexExpression {
+    expr.left = transformer.transform_expression(expr.left);
+    expr.index = transformer.transform_expression(expr.index);
+    expr
 }
 
-impl fmt::Display for ExpressionStatement {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.expression)
-    }
+fn fold_expressions<T: Transformer + ?Sized>(
+    transformer: &T,
+    expressions: Vec<Box<dyn Expression>>,
+) -> Vec<Box<dyn Expression>> {
+    expressions
+        .into_iter()
+        .map(|expr| transformer.transform_expression(expr))
+        .collect()
 }
 
-impl fmt::Display for dyn Expression {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let Some(expr) = self.as_any().downcast_ref::<PrefixExpression>() {
-            return write!(f, "{}", expr);
+/// Rebuilds a statement from its transformed children
+pub fn fold_statement<T: Transformer + ?Sized>(
+    transformer: &T,
+    statement: Box<dyn Statement>,
+) -> Box<dyn Statement> {
+    let statement = match downcast_statement::<ExpressionStatement>(statement) {
+        Ok(mut stmt) => {
+            stmt.expression = transformer.transform_expression(stmt.expression);
+            return stmt;
         }
-        if let Some(expr) = self.as_any().downcast_ref::<InfixExpression>() {
-            return write!(f, "{}", expr);
+        Err(statement) => statement,
+    };
+    let statement = match downcast_statement::<LetStatement>(statement) {
+        Ok(mut stmt) => {
+            stmt.value = stmt
+                .value
+                .map(|value| transformer.transform_expression(value));
+            return stmt;
         }
-        if let Some(expr) = self.as_any().downcast_ref::<IntegerLiteral>() {
-            return write!(f, "{}", expr);
+        Err(statement) => statement,
+    };
+    let statement = match downcast_statement::<DestructureStatement>(statement) {
+        Ok(mut stmt) => {
+            stmt.value = transformer.transform_expression(stmt.value);
+            return stmt;
         }
-        if let Some(expr) = self.as_any().downcast_ref::<Identifier>() {
-            return write!(f, "{}", expr);
+        Err(statement) => statement,
+    };
+    let statement = match downcast_statement::<ConstStatement>(statement) {
+        Ok(mut stmt) => {
+            stmt.value = transformer.transform_expression(stmt.value);
+            return stmt;
         }
-        if let Some(expr) = self.as_any().downcast_ref::<Boolean>() {
-            return write!(f, "{}", expr);
+        Err(statement) => statement,
+    };
+    let statement = match downcast_statement::<AssignStatement>(statement) {
+        Ok(mut stmt) => {
+            stmt.value = transformer.transform_expression(stmt.value);
+            return stmt;
         }
-        if let Some(expr) = self.as_any().downcast_ref::<IfExpression>() {
-            return write!(f, "{}", expr);
+        Err(statement) => statement,
+    };
+    let statement = match downcast_statement::<ReturnStatement>(statement) {
+        Ok(mut stmt) => {
+            stmt.return_value = stmt
+                .return_value
+                .map(|value| transformer.transform_expression(value));
+            return stmt;
         }
-        if let Some(expr) = self.as_any().downcast_ref::<FunctionLiteral>() {
-            return write!(f, "{}", expr);
+        Err(statement) => statement,
+    };
+    let statement = match downcast_statement::<BlockStatement>(statement) {
+        Ok(block) => return Box::new(transformer.transform_block_statement(*block)),
+        Err(statement) => statement,
+    };
+    let statement = match downcast_statement::<WhileStatement>(statement) {
+        Ok(mut stmt) => {
+            stmt.condition = transformer.transform_expression(stmt.condition);
+            stmt.body = transformer.transform_block_statement(stmt.body);
+            return stmt;
         }
-        if let Some(expr) = self.as_any().downcast_ref::<CallExpression>() {
-            return write!(f, "{}", expr);
+        Err(statement) => statement,
+    };
+    let statement = match downcast_statement::<DoWhileStatement>(statement) {
+        Ok(mut stmt) => {
+            stmt.body = transformer.transform_block_statement(stmt.body);
+            stmt.condition = transformer.transform_expression(stmt.condition);
+            return stmt;
         }
-        write!(f, "{}", self.token_literal())
+        Err(statement) => statement,
+    };
+    let statement = match downcast_statement::<LoopStatement>(statement) {
+        Ok(mut stmt) => {
+            stmt.body = transformer.transform_block_statement(stmt.body);
+            return stmt;
+        }
+        Err(statement) => statement,
+    };
+    let statement = match downcast_statement::<WhileLetStatement>(statement) {
+        Ok(mut stmt) => {
+            stmt.expression = transformer.transform_expression(stmt.expression);
+            stmt.body = transformer.transform_block_statement(stmt.body);
+            return stmt;
+        }
+        Err(statement) => statement,
+    };
+    let statement = match downcast_statement::<LabeledStatement>(statement) {
+        Ok(mut stmt) => {
+            stmt.body = transformer.transform_statement(stmt.body);
+            return stmt;
+        }
+        Err(statement) => statement,
+    };
+    match downcast_statement::<ForStatement>(statement) {
+        Ok(mut stmt) => {
+            stmt.iterable = transformer.transform_expression(stmt.iterable);
+            stmt.body = transformer.transform_block_statement(stmt.body);
+            stmt
+        }
+        // break and continue have no children
+        Err(statement) => statement,
     }
 }
 
-impl fmt::Display for PrefixExpression {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "({}{})", self.operator, self.right)
+/// Rebuilds an expression from its transformed children
+pub fn fold_expression<T: Transformer + ?Sized>(
+    transformer: &T,
+    expression: Box<dyn Expression>,
+) -> Box<dyn Expression> {
+    let expression = match downcast_expression::<PrefixExpression>(expression) {
+        Ok(mut expr) => {
+            expr.right = transformer.transform_expression(expr.right);
+            return expr;
+        }
+        Err(expression) => expression,
+    };
+    let expression = match downcast_expression::<InfixExpression>(expression) {
+        Ok(mut expr) => {
+            expr.left = transformer.transform_expression(expr.left);
+            expr.right = transformer.transform_expression(expr.right);
+            return expr;
+        }
+        Err(expression) => expression,
+    };
+    let expression = match downcast_expression::<TypeofExpression>(expression) {
+        Ok(mut expr) => {
+            expr.expr = transformer.transform_expression(expr.expr);
+            return expr;
+        }
+        Err(expression) => expression,
+    };
+    let expression = match downcast_expression::<IfExpression>(expression) {
+        Ok(mut expr) => {
+            expr.condition = transformer.transform_expression(expr.condition);
+            expr.consequence = transformer.transform_block_statement(expr.consequence);
+            expr.alternative = expr
+                .alternative
+                .map(|alternative| transformer.transform_block_statement(alternative));
+            return expr;
+        }
+        Err(expression) => expression,
+    };
+    let expression = match downcast_expression::<FunctionLiteral>(expression) {
+        Ok(mut func) => {
+            func.body = transformer.transform_block_statement(func.body);
+            return func;
+        }
+        Err(expression) => expression,
+    };
+    let expression = match downcast_expression::<CallExpression>(expression) {
+        Ok(mut call) => {
+            call.function = transformer.transform_expression(call.function);
+            call.arguments = fold_expressions(transformer, call.arguments);
+            return call;
+        }
+        Err(expression) => expression,
+    };
+    let expression = match downcast_expression::<SpreadExpression>(expression) {
+        Ok(mut expr) => {
+            expr.value = transformer.transform_expression(expr.value);
+            return expr;
+        }
+        Err(expression) => expression,
+    };
+    let expression = match downcast_expression::<ArrayLiteral>(expression) {
+        Ok(mut array) => {
+            array.elements = fold_expressions(transformer, array.elements);
+            return array;
+        }
+        Err(expression) => expression,
+    };
+    let expression = match downcast_expression::<HashLiteral>(expression) {
+        Ok(mut hash) => {
+            hash.pairs = hash
+                .pairs
+                .into_iter()
+                .map(|(key, value)| {
+                    (
+                        transformer.transform_expression(key),
+                        transformer.transform_expression(value),
+                    )
+                })
+                .collect();
+            return hash;
+        }
+        Err(expression) => expression,
+    };
+    let expression = match downcast_expression::<MatchExpression>(expression) {
+        Ok(mut expr) => {
+            expr.subject = transformer.transform_expression(expr.subject);
+            expr.arms = expr
+                .arms
+                .into_iter()
+                .map(|arm| MatchArm {
+                    pattern: transformer.transform_expression(arm.pattern),
+                    body: transformer.transform_expression(arm.body),
+                })
+                .collect();
+            return expr;
+        }
+        Err(expression) => expression,
+    };
+    let expression = match downcast_expression::<TryCatchExpression>(expression) {
+        Ok(mut expr) => {
+            expr.try_block = transformer.transform_block_statement(expr.try_block);
+            expr.catch_block = transformer.transform_block_statement(expr.catch_block);
+            return expr;
+        }
+        Err(expression) => expression,
+    };
+    let expression = match downcast_expression::<IndexExpression>(expression) {
+        Ok(expr) => return Box::new(fold_index_expression(transformer, *expr)),
+        Err(expression) => expression,
+    };
+    let expression = match downcast_expression::<IndexAssignExpression>(expression) {
+        Ok(mut expr) => {
+            expr.target = fold_index_expression(transformer, expr.target);
+            expr.value = transformer.transform_expression(expr.value);
+            return expr;
+        }
+        Err(expression) => expression,
+    };
+    match downcast_expression::<MethodCallExpression>(expression) {
+        Ok(mut expr) => {
+            expr.object = transformer.transform_expression(expr.object);
+            expr.arguments = fold_expressions(transformer, expr.arguments);
+            expr
+        }
+        // identifiers, literals and wildcards have no children
+        Err(expression) => expression,
     }
 }
 
-impl fmt::Display for InfixExpression {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "({} {} {})", self.left, self.operator, self.right)
+/// Leaves every node as it is
+#[derive(Debug, Default, Clone, Copy)]
+pub struct IdentityTransformer;
+
+impl Transformer for IdentityTransformer {}
+
+/// Replaces `IntegerLiteral + IntegerLiteral` with a single literal holding
+/// the sum, innermost first, so `1 + 2 + x` becomes `3 + x`
+///
+/// Sums that would overflow are left for the evaluator to report.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ConstantFoldingTransformer;
+
+impl ConstantFoldingTransformer {
+    fn fold_integers(expr: &InfixExpression) -> Option<IntegerLiteral> {
+        let left = expr.left.as_any().downcast_ref::<IntegerLiteral>()?;
+        let right = expr.right.as_any().downcast_ref::<IntegerLiteral>()?;
+
+        if expr.operator != "+" {
+            return None;
+        }
+        let value = left.value.checked_add(right.value)?;
+
+        Some(IntegerLiteral {
+            token: Token {
+                token_type: TokenType::Int,
+                literal: value.to_string(),
+                line: left.token.line,
+                column: left.token.column,
+            },
+            value,
+        })
     }
 }
 
-impl fmt::Display for IntegerLiteral {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.value)
+impl Transformer for ConstantFoldingTransformer {
+    fn transform_expression(&self, expression: Box<dyn Expression>) -> Box<dyn Expression> {
+        let expression = fold_expression(self, expression);
+
+        match expression
+            .as_any()
+            .downcast_ref::<InfixExpression>()
+            .and_then(Self::fold_integers)
+        {
+            Some(literal) => Box::new(literal),
+            None => expression,
+        }
     }
 }
 
-impl fmt::Display for Identifier {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.value)
-    }
+/// Tagged Representation
+///
+/// An owned, type-tagged mirror of the core AST. The `Statement` and
+/// `Expression` trait objects can't be matched on or serialized directly, so
+/// a program is converted to an `AstNode` tree, inspected or stored, and
+/// converted back. The conversion keeps every token, so a round trip is
+/// lossless.
+///
+/// With the `serde` feature, AstNode and the core node types implement
+/// `Serialize` and `Deserialize`; the node types go through this form.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AstNode {
+    Program {
+        statements: Vec<AstNode>,
+    },
+    LetStatement {
+        token: Token,
+        name: Identifier,
+        value: Option<Box<AstNode>>,
+    },
+    ReturnStatement {
+        token: Token,
+        return_value: Option<Box<AstNode>>,
+    },
+    ExpressionStatement {
+        token: Token,
+        expression: Box<AstNode>,
+    },
+    BlockStatement {
+        token: Token,
+        statements: Vec<AstNode>,
+    },
+    Identifier(Identifier),
+    IntegerLiteral {
+        token: Token,
+        value: i64,
+    },
+    Boolean {
+        token: Token,
+        value: bool,
+    },
+    PrefixExpression {
+        token: Token,
+        operator: String,
+        right: Box<AstNode>,
+    },
+    InfixExpression {
+        token: Token,
+        left: Box<AstNode>,
+        operator: String,
+        right: Box<AstNode>,
+    },
+    IfExpression {
+        token: Token,
+        condition: Box<AstNode>,
+        consequence: Box<AstNode>,
+        alternative: Option<Box<AstNode>>,
+    },
+    FunctionLiteral {
+        token: Token,
+        name: Option<Identifier>,
+        parameters: Vec<Identifier>,
+        rest: Option<Identifier>,
+        body: Box<AstNode>,
+    },
+    CallExpression {
+        token: Token,
+        function: Box<AstNode>,
+        arguments: Vec<AstNode>,
+    },
 }
 
-impl fmt::Display for Boolean {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.value)
+impl AstNode {
+    /// The name of the AST type this node stands for
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AstNode::Program { .. } => "Program",
+            AstNode::LetStatement { .. } => "LetStatement",
+            AstNode::ReturnStatement { .. } => "ReturnStatement",
+            AstNode::ExpressionStatement { .. } => "ExpressionStatement",
+            AstNode::BlockStatement { .. } => "BlockStatement",
+            AstNode::Identifier(_) => "Identifier",
+            AstNode::IntegerLiteral { .. } => "IntegerLiteral",
+            AstNode::Boolean { .. } => "Boolean",
+            AstNode::PrefixExpression { .. } => "PrefixExpression",
+            AstNode::InfixExpression { .. } => "InfixExpression",
+            AstNode::IfExpression { .. } => "IfExpression",
+            AstNode::FunctionLiteral { .. } => "FunctionLiteral",
+            AstNode::CallExpression { .. } => "CallExpression",
+        }
     }
-}
 
-impl fmt::Display for BlockStatement {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for stmt in &self.statements {
-            write!(f, "{}", stmt)?;
+    /// Converts a statement, failing on node types without a tagged form
+    pub fn from_statement(statement: &dyn Statement) -> Result<AstNode, String> {
+        let any = statement.as_any();
+
+        if let Some(stmt) = any.downcast_ref::<LetStatement>() {
+            Ok(AstNode::LetStatement {
+                token: stmt.token.clone(),
+                name: stmt.name.clone(),
+                value: Self::from_optional(stmt.value.as_deref())?,
+            })
+        } else if let Some(stmt) = any.downcast_ref::<ReturnStatement>() {
+            Ok(AstNode::ReturnStatement {
+                token: stmt.token.clone(),
+                return_value: Self::from_optional(stmt.return_value.as_deref())?,
+            })
+        } else if let Some(stmt) = any.downcast_ref::<ExpressionStatement>() {
+            Ok(AstNode::ExpressionStatement {
+                token: stmt.token.clone(),
+                expression: Box::new(Self::from_expression(stmt.expression.as_ref())?),
+            })
+        } else if let Some(block) = any.downcast_ref::<BlockStatement>() {
+            Self::from_block(block)
+        } else {
+            Err(format!(
+                "cannot convert statement to AstNode: {}",
+                statement
+            ))
         }
-        Ok(())
     }
-}
 
-impl fmt::Display for IfExpression {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "if{} {}", self.condition, self.consequence)?;
+    /// Converts an expression, failing on node types without a tagged form
+    pub fn from_expression(expression: &dyn Expression) -> Result<AstNode, String> {
+        let any = expression.as_any();
 
-        if let Some(alt) = &self.alternative {
-            write!(f, "else {}", alt)?;
+        if let Some(ident) = any.downcast_ref::<Identifier>() {
+            Ok(AstNode::Identifier(ident.clone()))
+        } else if let Some(lit) = any.downcast_ref::<IntegerLiteral>() {
+            Ok(AstNode::IntegerLiteral {
+                token: lit.token.clone(),
+                value: lit.value,
+            })
+        } else if let Some(boolean) = any.downcast_ref::<Boolean>() {
+            Ok(AstNode::Boolean {
+                token: boolean.token.clone(),
+                value: boolean.value,
+            })
+        } else if let Some(expr) = any.downcast_ref::<PrefixExpression>() {
+            Ok(AstNode::PrefixExpression {
+                token: expr.token.clone(),
+                operator: expr.operator.clone(),
+                right: Box::new(Self::from_expression(expr.right.as_ref())?),
+            })
+        } else if let Some(expr) = any.downcast_ref::<InfixExpression>() {
+            Ok(AstNode::InfixExpression {
+                token: expr.token.clone(),
+                left: Box::new(Self::from_expression(expr.left.as_ref())?),
+                operator: expr.operator.clone(),
+                right: Box::new(Self::from_expression(expr.right.as_ref())?),
+            })
+        } else if let Some(expr) = any.downcast_ref::<IfExpression>() {
+            Ok(AstNode::IfExpression {
+                token: expr.token.clone(),
+                condition: Box::new(Self::from_expression(expr.condition.as_ref())?),
+                consequence: Box::new(Self::from_block(&expr.consequence)?),
+                alternative: match &expr.alternative {
+                    Some(alternative) => Some(Box::new(Self::from_block(alternative)?)),
+                    None => None,
+                },
+            })
+        } else if let Some(func) = any.downcast_ref::<FunctionLiteral>() {
+            Ok(AstNode::FunctionLiteral {
+                token: func.token.clone(),
+                name: func.name.clone(),
+                parameters: func.parameters.clone(),
+                rest: func.rest.clone(),
+                body: Box::new(Self::from_block(&func.body)?),
+            })
+        } else if let Some(call) = any.downcast_ref::<CallExpression>() {
+            Ok(AstNode::CallExpression {
+                token: call.token.clone(),
+                function: Box::new(Self::from_expression(call.function.as_ref())?),
+                arguments: call
+                    .arguments
+                    .iter()
+                    .map(|arg| Self::from_expression(arg.as_ref()))
+                    .collect::<Result<_, _>>()?,
+            })
+        } else {
+            Err(format!(
+                "cannot convert expression to AstNode: {}",
+                expression
+            ))
         }
+    }
 
-        Ok(())
+    fn from_optional(expression: Option<&dyn Expression>) -> Result<Option<Box<AstNode>>, String> {
+        expression
+            .map(|expr| Self::from_expression(expr).map(Box::new))
+            .transpose()
+    }
+
+    fn from_block(block: &BlockStatement) -> Result<AstNode, String> {
+        Ok(AstNode::BlockStatement {
+            token: block.token.clone(),
+            statements: Self::from_statements(&block.statements)?,
+        })
+    }
+
+    fn from_statements(statements: &[Box<dyn Statement>]) -> Result<Vec<AstNode>, String> {
+        statements
+            .iter()
+            .map(|statement| Self::from_statement(statement.as_ref()))
+            .collect()
+    }
+
+    /// Rebuilds the statement this node stands for
+    pub fn into_statement(self) -> Result<Box<dyn Statement>, String> {
+        match self {
+            AstNode::LetStatement { token, name, value } => Ok(Box::new(LetStatement {
+                token,
+                name,
+                value: value.map(|value| value.into_expression()).transpose()?,
+            })),
+            AstNode::ReturnStatement {
+                token,
+                return_value,
+            } => Ok(Box::new(ReturnStatement {
+                token,
+                return_value: return_value
+                    .map(|value| value.into_expression())
+                    .transpose()?,
+            })),
+            AstNode::ExpressionStatement { token, expression } => {
+                Ok(Box::new(ExpressionStatement {
+                    token,
+                    expression: expression.into_expression()?,
+                }))
+            }
+            AstNode::BlockStatement { .. } => Ok(Box::new(self.into_block()?)),
+            node => Err(format!("expected a statement, got {}", node.kind())),
+        }
+    }
+
+    /// Rebuilds the expression this node stands for
+    pub fn into_expression(self) -> Result<Box<dyn Expression>, String> {
+        match self {
+            AstNode::Identifier(ident) => Ok(Box::new(ident)),
+            AstNode::IntegerLiteral { token, value } => {
+                Ok(Box::new(IntegerLiteral { token, value }))
+            }
+            AstNode::Boolean { token, value } => Ok(Box::new(Boolean { token, value })),
+            AstNode::PrefixExpression {
+                token,
+                operator,
+                right,
+            } => Ok(Box::new(PrefixExpression {
+                token,
+                operator,
+                right: right.into_expression()?,
+            })),
+            AstNode::InfixExpression {
+                token,
+                left,
+                operator,
+                right,
+            } => Ok(Box::new(InfixExpression {
+                token,
+                left: left.into_expression()?,
+                operator,
+                right: right.into_expression()?,
+            })),
+            AstNode::IfExpression {
+                token,
+                condition,
+                consequence,
+                alternative,
+            } => Ok(Box::new(IfExpression {
+                token,
+                condition: condition.into_expression()?,
+                consequence: consequence.into_block()?,
+                alternative: alternative
+                    .map(|alternative| alternative.into_block())
+                    .transpose()?,
+            })),
+            AstNode::FunctionLiteral {
+                token,
+                name,
+                parameters,
+                rest,
+                body,
+            } => Ok(Box::new(FunctionLiteral {
+                token,
+                name,
+                parameters,
+                rest,
+                body: body.into_block()?,
+            })),
+            AstNode::CallExpression {
+                token,
+                function,
+                arguments,
+            } => Ok(Box::new(CallExpression {
+                token,
+                function: function.into_expression()?,
+                arguments: arguments
+                    .into_iter()
+                    .map(AstNode::into_expression)
+                    .collect::<Result<_, _>>()?,
+            })),
+            node => Err(format!("expected an expression, got {}", node.kind())),
+        }
+    }
+
+    fn into_block(self) -> Result<BlockStatement, String> {
+        match self {
+            AstNode::BlockStatement { token, statements } => Ok(BlockStatement {
+                token,
+                statements: Self::into_statements(statements)?,
+            }),
+            node => Err(format!("expected a BlockStatement, got {}", node.kind())),
+        }
+    }
+
+    fn into_statements(statements: Vec<AstNode>) -> Result<Vec<Box<dyn Statement>>, String> {
+        statements
+            .into_iter()
+            .map(AstNode::into_statement)
+            .collect()
     }
 }
 
-impl fmt::Display for FunctionLiteral {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let params: Vec<String> = self.parameters.iter().map(|p| p.to_string()).collect();
+impl TryFrom<&Program> for AstNode {
+    type Error = String;
 
-        write!(
-            f,
-            "{}({}) {}",
-            self.token_literal(),
-            params.join(", "),
-            self.body
-        )
+    fn try_from(program: &Program) -> Result<Self, Self::Error> {
+        Ok(AstNode::Program {
+            statements: AstNode::from_statements(&program.statements)?,
+        })
     }
 }
 
-impl fmt::Display for CallExpression {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let args: Vec<String> = self.arguments.iter().map(|a| a.to_string()).collect();
+impl TryFrom<AstNode> for Program {
+    type Error = String;
 
-        write!(f, "{}({})", self.function, args.join(", "))
+    fn try_from(node: AstNode) -> Result<Self, Self::Error> {
+        match node {
+            AstNode::Program { statements } => Ok(Program {
+                statements: AstNode::into_statements(statements)?,
+            }),
+            node => Err(format!("expected a Program, got {}", node.kind())),
+        }
     }
 }
 
-impl fmt::Display for StringLiteral {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.value)
+#[cfg(feature = "serde")]
+impl serde::Serialize for Program {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        AstNode::try_from(self)
+            .map_err(serde::ser::Error::custom)?
+            .serialize(serializer)
     }
 }
 
-/// --------------------
-/// Clone Implementation
-/// --------------------
-impl Clone for BlockStatement {
-    fn clone(&self) -> Self {
-        let mut statements = Vec::new();
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Program {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let node = AstNode::deserialize(deserializer)?;
+        Program::try_from(node).map_err(serde::de::Error::custom)
+    }
+}
 
-        for stmt in &self.statements {
-            if let Some(expr_stmt) = stmt.as_any().downcast_ref::<ExpressionStatement>() {
-                let expr = expr_stmt.expression.clone_box();
-                statements.push(Box::new(ExpressionStatement {
-                    token: expr_stmt.token.clone(),
-                    expression: expr,
-                }) as Box<dyn Statement>);
-            } else if let Some(let_stmt) = stmt.as_any().downcast_ref::<LetStatement>() {
-                let value = let_stmt.value.as_ref().map(|expr| expr.clone_box());
-                statements.push(Box::new(LetStatement {
-                    token: let_stmt.token.clone(),
-                    name: let_stmt.name.clone(),
-                    value,
-                }) as Box<dyn Statement>);
-            } else if let Some(return_stmt) = stmt.as_any().downcast_ref::<ReturnStatement>() {
-                let return_value = return_stmt
-                    .return_value
-                    .as_ref()
-                    .map(|expr| expr.clone_box());
-                statements.push(Box::new(ReturnStatement {
-                    token: return_stmt.token.clone(),
-                    return_value,
-                }) as Box<dyn Statement>);
+/// Implements Serialize and Deserialize for node types by converting them to
+/// and from AstNode
+#[cfg(feature = "serde")]
+macro_rules! serde_via_ast_node {
+    ($from:ident, $into:ident, $downcast:ident: $($node:ident),*) => {$(
+        impl serde::Serialize for $node {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                AstNode::$from(self)
+                    .map_err(serde::ser::Error::custom)?
+                    .serialize(serializer)
             }
         }
 
-        BlockStatement {
-            token: self.token.clone(),
-            statements,
+        impl<'de> serde::Deserialize<'de> for $node {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let node = AstNode::deserialize(deserializer)?;
+                let kind = node.kind();
+                let rebuilt = node.$into().map_err(serde::de::Error::custom)?;
+                $downcast::<$node>(rebuilt).map(|node| *node).map_err(|_| {
+                    serde::de::Error::custom(format!(
+                        "expected a {}, got {}",
+                        stringify!($node),
+                        kind
+                    ))
+                })
+            }
         }
-    }
+    )*};
 }
 
-impl Clone for FunctionLiteral {
-    fn clone(&self) -> Self {
-        FunctionLiteral {
-            token: self.token.clone(),
-            parameters: self.parameters.clone(),
-            body: self.body.clone(),
-        }
-    }
-}
+#[cfg(feature = "serde")]
+serde_via_ast_node!(
+    from_statement, into_statement, downcast_statement:
+    LetStatement, ReturnStatement, ExpressionStatement, BlockStatement
+);
+
+#[cfg(feature = "serde")]
+serde_via_ast_node!(
+    from_expression, into_expression, downcast_expression:
+    IntegerLiteral, Boolean, PrefixExpression, InfixExpression, IfExpression,
+    FunctionLiteral, CallExpression
+);
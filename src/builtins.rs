@@ -1,5 +1,13 @@
-use crate::object::{Builtin, Error, Integer, Object, ObjectType, StringObj};
+use crate::evaluator::{is_truthy, objects_equal, Evaluator};
+use crate::object::{
+    object_to_hash_key, Array, Boolean, Builtin, BuiltinFunction, CaughtError, ComposedFunction,
+    Error, ExitValue, FloatObj, Hash, HashKey, Integer, MemoizedFunction, Null, Object, ObjectType,
+    PartialFunction, Range, StringObj,
+};
+use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::io::{self, Write};
 
 /// Create a new error
 fn new_error(message: &str) -> Box<dyn Object> {
@@ -7,7 +15,7 @@ fn new_error(message: &str) -> Box<dyn Object> {
 }
 
 /// Define the len() function
-fn len_function(args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+fn len_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
     if args.len() != 1 {
         return new_error(&format!(
             "wrong number of arguments. got={}, want=1",
@@ -20,6 +28,17 @@ fn len_function(args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
             let string_obj = args[0].as_any().downcast_ref::<StringObj>().unwrap();
             Box::new(Integer::new(string_obj.value.len() as i64))
         }
+        ObjectType::Array => {
+            let array = args[0].as_any().downcast_ref::<Array>().unwrap();
+            Box::new(Integer::new(array.elements.len() as i64))
+        }
+        ObjectType::Range => {
+            let range = args[0].as_any().downcast_ref::<Range>().unwrap();
+            match i64::try_from(range.len()) {
+                Ok(len) => Box::new(Integer::new(len)),
+                Err(_) => new_error("integer overflow"),
+            }
+        }
         _ => new_error(&format!(
             "argument to `len` not supported, got {}",
             args[0].type_()
@@ -27,6 +46,1610 @@ fn len_function(args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
     }
 }
 
+/// Checks the argument count and that the first argument is an array
+///
+/// A range is accepted too and materialized into an array
+fn array_argument<'a>(
+    name: &str,
+    args: &'a [Box<dyn Object>],
+    want: usize,
+) -> Result<Cow<'a, Array>, Box<dyn Object>> {
+    check_arity(args, want)?;
+    array_at(name, args, 0)
+}
+
+/// Checks that the argument at `index` is an array or a range
+fn array_at<'a>(
+    name: &str,
+    args: &'a [Box<dyn Object>],
+    index: usize,
+) -> Result<Cow<'a, Array>, Box<dyn Object>> {
+    if let Some(range) = args[index].as_any().downcast_ref::<Range>() {
+        return Ok(Cow::Owned(range_to_array(range)));
+    }
+
+    match args[index].as_any().downcast_ref::<Array>() {
+        Some(array) => Ok(Cow::Borrowed(array)),
+        None => Err(new_error(&format!(
+            "argument to `{}` must be ARRAY, got {}",
+            name,
+            args[index].type_()
+        ))),
+    }
+}
+
+/// Null for an index past the end of a range, which has no element there
+fn range_element(range: &Range, index: u64) -> Box<dyn Object> {
+    match range.get(index) {
+        Some(value) => Box::new(Integer::new(value)),
+        None => Box::new(Null::new()),
+    }
+}
+
+/// Define the first() function
+fn first_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if let (1, Some(range)) = (args.len(), args[0].as_any().downcast_ref::<Range>()) {
+        return range_element(range, 0);
+    }
+
+    match array_argument("first", &args, 1) {
+        Ok(array) => match array.elements.first() {
+            Some(element) => element.clone(),
+            None => Box::new(Null::new()),
+        },
+        Err(err) => err,
+    }
+}
+
+/// Define the last() function
+fn last_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if let (1, Some(range)) = (args.len(), args[0].as_any().downcast_ref::<Range>()) {
+        return match range.len() {
+            0 => Box::new(Null::new()),
+            len => range_element(range, len - 1),
+        };
+    }
+
+    match array_argument("last", &args, 1) {
+        Ok(array) => match array.elements.last() {
+            Some(element) => element.clone(),
+            None => Box::new(Null::new()),
+        },
+        Err(err) => err,
+    }
+}
+
+/// Define the rest() function, returning a new array without the first element
+///
+/// The rest of a range is the range that starts one step later.
+fn rest_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if let (1, Some(range)) = (args.len(), args[0].as_any().downcast_ref::<Range>()) {
+        return match range.get(1) {
+            Some(second) => Box::new(Range::new(second, range.end, range.step)),
+            None if range.is_empty() => Box::new(Null::new()),
+            None => Box::new(Range::new(range.end, range.end, range.step)),
+        };
+    }
+
+    match array_argument("rest", &args, 1) {
+        Ok(array) if array.elements.is_empty() => Box::new(Null::new()),
+        Ok(array) => Box::new(Array::new(array.elements[1..].to_vec())),
+        Err(err) => err,
+    }
+}
+
+/// Define the push() function, returning a new array with the value appended
+fn push_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    match array_argument("push", &args, 2) {
+        Ok(array) => {
+            let mut elements = array.elements.clone();
+            elements.push(args[1].clone());
+            Box::new(Array::new(elements))
+        }
+        Err(err) => err,
+    }
+}
+
+/// Define the pop() function, returning a new array without the last element
+fn pop_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    match array_argument("pop", &args, 1) {
+        Ok(array) if array.elements.is_empty() => Box::new(Null::new()),
+        Ok(array) => {
+            let end = array.elements.len() - 1;
+            Box::new(Array::new(array.elements[..end].to_vec()))
+        }
+        Err(err) => err,
+    }
+}
+
+/// Checks that an argument can be called, returning it for `apply_function`
+fn callable_argument(
+    name: &str,
+    args: &[Box<dyn Object>],
+    index: usize,
+) -> Result<Box<dyn Object>, Box<dyn Object>> {
+    match args[index].type_() {
+        ObjectType::Function
+        | ObjectType::Builtin
+        | ObjectType::PartialFunction
+        | ObjectType::MemoizedFunction
+        | ObjectType::ComposedFunction => Ok(args[index].clone()),
+        other => Err(new_error(&format!(
+            "argument to `{}` must be FUNCTION, got {}",
+            name, other
+        ))),
+    }
+}
+
+/// Define the map() function
+fn map_function(evaluator: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    let array = match array_argument("map", &args, 2) {
+        Ok(array) => array,
+        Err(err) => return err,
+    };
+    let func = match callable_argument("map", &args, 1) {
+        Ok(func) => func,
+        Err(err) => return err,
+    };
+
+    let mut elements = Vec::with_capacity(array.elements.len());
+    for element in &array.elements {
+        let result = evaluator.apply_function(func.clone(), vec![element.clone()]);
+        if matches!(result.type_(), ObjectType::Error | ObjectType::Exit) {
+            return result;
+        }
+        elements.push(result);
+    }
+
+    Box::new(Array::new(elements))
+}
+
+/// Define the filter() function
+fn filter_function(evaluator: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    let array = match array_argument("filter", &args, 2) {
+        Ok(array) => array,
+        Err(err) => return err,
+    };
+    let func = match callable_argument("filter", &args, 1) {
+        Ok(func) => func,
+        Err(err) => return err,
+    };
+
+    let mut elements = Vec::new();
+    for element in &array.elements {
+        let result = evaluator.apply_function(func.clone(), vec![element.clone()]);
+        if matches!(result.type_(), ObjectType::Error | ObjectType::Exit) {
+            return result;
+        }
+        if is_truthy(result) {
+            elements.push(element.clone());
+        }
+    }
+
+    Box::new(Array::new(elements))
+}
+
+/// Define the any() function, true if the predicate holds for some element
+fn any_function(evaluator: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    match first_match("any", evaluator, &args, true) {
+        Ok(found) => Box::new(Boolean::new(found.is_some())),
+        Err(err) => err,
+    }
+}
+
+/// Define the all() function, true if the predicate holds for every element
+fn all_function(evaluator: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    match first_match("all", evaluator, &args, false) {
+        Ok(found) => Box::new(Boolean::new(found.is_none())),
+        Err(err) => err,
+    }
+}
+
+/// Define the none() function, true if the predicate holds for no element
+fn none_function(evaluator: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    match first_match("none", evaluator, &args, true) {
+        Ok(found) => Box::new(Boolean::new(found.is_none())),
+        Err(err) => err,
+    }
+}
+
+/// Define the find() function, returning the first element the predicate
+/// holds for, or null
+fn find_function(evaluator: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    match first_match("find", evaluator, &args, true) {
+        Ok(Some(index)) => match array_at("find", &args, 0) {
+            Ok(array) => array.elements[index].clone(),
+            Err(err) => err,
+        },
+        Ok(None) => Box::new(Null::new()),
+        Err(err) => err,
+    }
+}
+
+/// Define the find_index() function, returning the index of the first element
+/// the predicate holds for, or -1
+fn find_index_function(evaluator: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    match first_match("find_index", evaluator, &args, true) {
+        Ok(Some(index)) => Box::new(Integer::new(index as i64)),
+        Ok(None) => Box::new(Integer::new(-1)),
+        Err(err) => err,
+    }
+}
+
+/// Applies the predicate to each element in turn, stopping at the first
+/// result whose truthiness is `target`. Returns the index of that element.
+fn first_match(
+    name: &str,
+    evaluator: &mut Evaluator,
+    args: &[Box<dyn Object>],
+    target: bool,
+) -> Result<Option<usize>, Box<dyn Object>> {
+    let array = array_argument(name, args, 2)?;
+    let func = callable_argument(name, args, 1)?;
+
+    for (index, element) in array.elements.iter().enumerate() {
+        let result = evaluator.apply_function(func.clone(), vec![element.clone()]);
+        if matches!(result.type_(), ObjectType::Error | ObjectType::Exit) {
+            return Err(result);
+        }
+        if is_truthy(result) == target {
+            return Ok(Some(index));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Define the enumerate() function, pairing each element with its index
+///
+/// Indices count from 0, or from the optional second argument
+fn enumerate_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if args.is_empty() || args.len() > 2 {
+        return new_error(&format!(
+            "wrong number of arguments. got={}, want=1 to 2",
+            args.len()
+        ));
+    }
+
+    let array = match array_at("enumerate", &args, 0) {
+        Ok(array) => array,
+        Err(err) => return err,
+    };
+
+    let start = match args.get(1) {
+        None => 0,
+        Some(start) => match start.as_any().downcast_ref::<Integer>() {
+            Some(start) => start.value,
+            None => {
+                return new_error(&format!(
+                    "argument to `enumerate` must be INTEGER, got {}",
+                    start.type_()
+                ))
+            }
+        },
+    };
+
+    let mut pairs = Vec::with_capacity(array.elements.len());
+    for (offset, element) in array.elements.iter().enumerate() {
+        let index = match i64::try_from(offset)
+            .ok()
+            .and_then(|offset| start.checked_add(offset))
+        {
+            Some(index) => index,
+            None => return new_error("integer overflow"),
+        };
+        pairs.push(Box::new(Array::new(vec![
+            Box::new(Integer::new(index)),
+            element.clone(),
+        ])) as Box<dyn Object>);
+    }
+
+    Box::new(Array::new(pairs))
+}
+
+/// Define the flatten() function, removing one level of nesting
+///
+/// Elements that are not arrays are kept as they are
+fn flatten_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    let array = match array_argument("flatten", &args, 1) {
+        Ok(array) => array,
+        Err(err) => return err,
+    };
+
+    let mut elements = Vec::new();
+    for element in &array.elements {
+        push_flattened(&mut elements, element.clone());
+    }
+
+    Box::new(Array::new(elements))
+}
+
+/// Define the flat_map() function, mapping each element and flattening the
+/// results one level, as flatten() does
+fn flat_map_function(evaluator: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    let array = match array_argument("flat_map", &args, 2) {
+        Ok(array) => array,
+        Err(err) => return err,
+    };
+    let func = match callable_argument("flat_map", &args, 1) {
+        Ok(func) => func,
+        Err(err) => return err,
+    };
+
+    let mut elements = Vec::new();
+    for element in &array.elements {
+        let result = evaluator.apply_function(func.clone(), vec![element.clone()]);
+        if matches!(result.type_(), ObjectType::Error | ObjectType::Exit) {
+            return result;
+        }
+        push_flattened(&mut elements, result);
+    }
+
+    Box::new(Array::new(elements))
+}
+
+/// Appends the elements of `value` if it is an array, or `value` itself if not
+fn push_flattened(elements: &mut Vec<Box<dyn Object>>, value: Box<dyn Object>) {
+    match value.as_any().downcast_ref::<Array>() {
+        Some(array) => elements.extend(array.elements.iter().cloned()),
+        None => elements.push(value),
+    }
+}
+
+/// Define the unique() function, keeping the first occurrence of each value
+///
+/// Values compare as they do with `==`, so `1` and `1.0` are duplicates
+fn unique_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    let array = match array_argument("unique", &args, 1) {
+        Ok(array) => array,
+        Err(err) => return err,
+    };
+
+    let mut elements: Vec<Box<dyn Object>> = Vec::new();
+    for element in &array.elements {
+        if !elements
+            .iter()
+            .any(|seen| objects_equal(seen.as_ref(), element.as_ref()))
+        {
+            elements.push(element.clone());
+        }
+    }
+
+    Box::new(Array::new(elements))
+}
+
+/// Define the count_by() function, counting elements by the key the function
+/// gives for them
+fn count_by_function(evaluator: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    let array = match array_argument("count_by", &args, 2) {
+        Ok(array) => array,
+        Err(err) => return err,
+    };
+    let func = match callable_argument("count_by", &args, 1) {
+        Ok(func) => func,
+        Err(err) => return err,
+    };
+
+    let mut counts: HashMap<HashKey, i64> = HashMap::new();
+    for element in &array.elements {
+        let result = evaluator.apply_function(func.clone(), vec![element.clone()]);
+        if matches!(result.type_(), ObjectType::Error | ObjectType::Exit) {
+            return result;
+        }
+        match object_to_hash_key(result.as_ref()) {
+            Some(key) => *counts.entry(key).or_insert(0) += 1,
+            None => return new_error(&format!("unusable as hash key: {}", result.type_())),
+        }
+    }
+
+    Box::new(Hash::new(
+        counts
+            .into_iter()
+            .map(|(key, count)| (key, Box::new(Integer::new(count)) as Box<dyn Object>))
+            .collect(),
+    ))
+}
+
+/// Define the slice() function for arrays and strings
+///
+/// Takes `(value, start)` or `(value, start, end)` with `end` exclusive.
+/// Negative indices count from the end, indices past either end are clamped,
+/// and strings are sliced by character.
+fn slice_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if args.len() != 2 && args.len() != 3 {
+        return new_error(&format!(
+            "wrong number of arguments. got={}, want=2 or 3",
+            args.len()
+        ));
+    }
+
+    let mut bounds = Vec::with_capacity(2);
+    for arg in &args[1..] {
+        match arg.as_any().downcast_ref::<Integer>() {
+            Some(int) => bounds.push(int.value),
+            None => {
+                return new_error(&format!(
+                    "argument to `slice` must be INTEGER, got {}",
+                    arg.type_()
+                ))
+            }
+        }
+    }
+
+    if let Some(string) = args[0].as_any().downcast_ref::<StringObj>() {
+        let chars: Vec<char> = string.value.chars().collect();
+        let (start, end) = slice_bounds(&bounds, chars.len() as u64);
+        return Box::new(StringObj::new(
+            chars[start as usize..end as usize].iter().collect(),
+        ));
+    }
+
+    if let Some(range) = args[0].as_any().downcast_ref::<Range>() {
+        // only the values in the slice are made, however long the range
+        let (start, end) = slice_bounds(&bounds, range.len());
+        let elements = (start..end)
+            .map(|index| range_element(range, index))
+            .collect();
+        return Box::new(Array::new(elements));
+    }
+
+    match args[0].type_() {
+        ObjectType::Array => {
+            let array = array_at("slice", &args, 0).unwrap();
+            let (start, end) = slice_bounds(&bounds, array.elements.len() as u64);
+            Box::new(Array::new(
+                array.elements[start as usize..end as usize].to_vec(),
+            ))
+        }
+        other => new_error(&format!(
+            "argument to `slice` must be ARRAY or STRING, got {}",
+            other
+        )),
+    }
+}
+
+/// Resolves `[start]` or `[start, end]` against a length, counting negative
+/// indices from the end and clamping to `0..=len`
+fn slice_bounds(bounds: &[i64], len: u64) -> (u64, u64) {
+    let resolve = |index: i64| {
+        let index = index as i128;
+        let index = if index < 0 {
+            index + len as i128
+        } else {
+            index
+        };
+        index.clamp(0, len as i128) as u64
+    };
+
+    let start = resolve(bounds[0]);
+    let end = bounds.get(1).map_or(len, |&end| resolve(end));
+    (start, end.max(start))
+}
+
+/// Define the zip() function, pairing up elements until the shorter array ends
+fn zip_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    let (left, right) = match array_argument("zip", &args, 2)
+        .and_then(|left| Ok((left, array_at("zip", &args, 1)?)))
+    {
+        Ok(arrays) => arrays,
+        Err(err) => return err,
+    };
+
+    let pairs = left
+        .elements
+        .iter()
+        .zip(&right.elements)
+        .map(|(a, b)| Box::new(Array::new(vec![a.clone(), b.clone()])) as Box<dyn Object>)
+        .collect();
+
+    Box::new(Array::new(pairs))
+}
+
+/// Define the zip_with() function, combining elements with a binary function
+/// until the shorter array ends
+fn zip_with_function(evaluator: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    let (left, right, func) = match array_argument("zip_with", &args, 3).and_then(|left| {
+        let right = array_at("zip_with", &args, 1)?;
+        let func = callable_argument("zip_with", &args, 2)?;
+        Ok((left, right, func))
+    }) {
+        Ok(arguments) => arguments,
+        Err(err) => return err,
+    };
+
+    let mut elements = Vec::new();
+    for (a, b) in left.elements.iter().zip(&right.elements) {
+        let result = evaluator.apply_function(func.clone(), vec![a.clone(), b.clone()]);
+        if matches!(result.type_(), ObjectType::Error | ObjectType::Exit) {
+            return result;
+        }
+        elements.push(result);
+    }
+
+    Box::new(Array::new(elements))
+}
+
+/// Define the reduce() function, folding the array from the left
+fn reduce_function(evaluator: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    let array = match array_argument("reduce", &args, 3) {
+        Ok(array) => array,
+        Err(err) => return err,
+    };
+    let func = match callable_argument("reduce", &args, 2) {
+        Ok(func) => func,
+        Err(err) => return err,
+    };
+
+    let mut accumulator = args[1].clone();
+    for element in &array.elements {
+        accumulator = evaluator.apply_function(func.clone(), vec![accumulator, element.clone()]);
+        if matches!(accumulator.type_(), ObjectType::Error | ObjectType::Exit) {
+            return accumulator;
+        }
+    }
+
+    accumulator
+}
+
+/// Natural ordering used by `sort`: numbers numerically, strings lexicographically
+fn natural_ordering(a: &dyn Object, b: &dyn Object) -> Result<Ordering, String> {
+    if let (Some(a), Some(b)) = (
+        a.as_any().downcast_ref::<StringObj>(),
+        b.as_any().downcast_ref::<StringObj>(),
+    ) {
+        return Ok(a.value.cmp(&b.value));
+    }
+
+    if let (Some(a), Some(b)) = (
+        a.as_any().downcast_ref::<Integer>(),
+        b.as_any().downcast_ref::<Integer>(),
+    ) {
+        return Ok(a.value.cmp(&b.value));
+    }
+
+    match (numeric_value(a), numeric_value(b)) {
+        (Some(x), Some(y)) => Ok(x.total_cmp(&y)),
+        _ if a.type_() == b.type_() => Err(format!("cannot sort values of type {}", a.type_())),
+        _ => Err(format!(
+            "cannot sort mixed types: {} and {}",
+            a.type_(),
+            b.type_()
+        )),
+    }
+}
+
+fn numeric_value(obj: &dyn Object) -> Option<f64> {
+    if let Some(int) = obj.as_any().downcast_ref::<Integer>() {
+        return Some(int.value as f64);
+    }
+    obj.as_any()
+        .downcast_ref::<FloatObj>()
+        .map(|float| float.value)
+}
+
+/// Define the partial() function, fixing the leading arguments of a function
+/// so `partial(f, a)(b)` calls `f(a, b)`
+fn partial_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if args.len() < 2 {
+        return new_error(&format!(
+            "wrong number of arguments. got={}, want=at least 2",
+            args.len()
+        ));
+    }
+    let func = match callable_argument("partial", &args, 0) {
+        Ok(func) => func,
+        Err(err) => return err,
+    };
+
+    Box::new(PartialFunction::new(func, args[1..].to_vec()))
+}
+
+/// Define the memoize() function, wrapping a function so repeated calls with
+/// the same arguments return the first result instead of running it again
+fn memoize_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if args.len() != 1 {
+        return new_error(&format!(
+            "wrong number of arguments. got={}, want=1",
+            args.len()
+        ));
+    }
+    match callable_argument("memoize", &args, 0) {
+        Ok(func) => Box::new(MemoizedFunction::new(func)),
+        Err(err) => err,
+    }
+}
+
+/// Define the compose() function, chaining functions right to left so
+/// `compose(f, g)(x)` calls `f(g(x))`
+fn compose_function(_: &mut Evaluator, mut args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    args.reverse();
+    composed("compose", args)
+}
+
+/// Define the pipe() function, chaining functions left to right so
+/// `pipe(f, g)(x)` calls `g(f(x))`
+fn pipe_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    composed("pipe", args)
+}
+
+/// Builds a ComposedFunction applying `functions` in the order given
+fn composed(name: &str, functions: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    for index in 0..functions.len() {
+        if let Err(err) = callable_argument(name, &functions, index) {
+            return err;
+        }
+    }
+
+    Box::new(ComposedFunction::new(functions))
+}
+
+/// Define the sort() function, returning a new sorted array
+///
+/// An optional comparator `fn(a, b)` returns a negative, zero or positive integer
+fn sort_function(evaluator: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if args.len() != 1 && args.len() != 2 {
+        return new_error(&format!(
+            "wrong number of arguments. got={}, want=1 or 2",
+            args.len()
+        ));
+    }
+
+    let array = match array_argument("sort", &args[..1], 1) {
+        Ok(array) => array,
+        Err(err) => return err,
+    };
+    let comparator = if args.len() == 2 {
+        match callable_argument("sort", &args, 1) {
+            Ok(func) => Some(func),
+            Err(err) => return err,
+        }
+    } else {
+        None
+    };
+
+    let mut compare = |a: &dyn Object, b: &dyn Object| match &comparator {
+        Some(func) => {
+            let result = evaluator.apply_function(func.clone(), vec![a.clone_box(), b.clone_box()]);
+            match result.as_any().downcast_ref::<Integer>() {
+                Some(int) => Ok(int.value.cmp(&0)),
+                None if matches!(result.type_(), ObjectType::Error | ObjectType::Exit) => {
+                    Err(result)
+                }
+                None => Err(new_error(&format!(
+                    "comparator for `sort` must return INTEGER, got {}",
+                    result.type_()
+                ))),
+            }
+        }
+        None => natural_ordering(a, b).map_err(|msg| new_error(&msg)),
+    };
+
+    match merge_sort(array.elements.clone(), &mut compare) {
+        Ok(elements) => Box::new(Array::new(elements)),
+        Err(err) => err,
+    }
+}
+
+/// A stable merge sort that stops at the first comparison to fail
+///
+/// Unlike `slice::sort_by` it never panics, whatever the comparator returns,
+/// so a user comparator that is not a consistent order gives some
+/// arrangement of the elements rather than crashing the interpreter.
+fn merge_sort<F>(
+    mut elements: Vec<Box<dyn Object>>,
+    compare: &mut F,
+) -> Result<Vec<Box<dyn Object>>, Box<dyn Object>>
+where
+    F: FnMut(&dyn Object, &dyn Object) -> Result<Ordering, Box<dyn Object>>,
+{
+    if elements.len() <= 1 {
+        return Ok(elements);
+    }
+
+    let right = elements.split_off(elements.len() / 2);
+    let left = merge_sort(elements, compare)?;
+    let right = merge_sort(right, compare)?;
+
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+    while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+        if compare(a.as_ref(), b.as_ref())? == Ordering::Greater {
+            merged.extend(right.next());
+        } else {
+            merged.extend(left.next());
+        }
+    }
+    merged.extend(left);
+    merged.extend(right);
+    Ok(merged)
+}
+
+/// Checks the argument count
+fn check_arity(args: &[Box<dyn Object>], want: usize) -> Result<(), Box<dyn Object>> {
+    if args.len() != want {
+        return Err(new_error(&format!(
+            "wrong number of arguments. got={}, want={}",
+            args.len(),
+            want
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that the argument at `index` is a string
+fn string_argument<'a>(
+    name: &str,
+    args: &'a [Box<dyn Object>],
+    index: usize,
+) -> Result<&'a str, Box<dyn Object>> {
+    match args[index].as_any().downcast_ref::<StringObj>() {
+        Some(string) => Ok(&string.value),
+        None => Err(new_error(&format!(
+            "argument to `{}` must be STRING, got {}",
+            name,
+            args[index].type_()
+        ))),
+    }
+}
+
+/// Define the split() function
+///
+/// An empty delimiter splits the string into its characters
+fn split_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    let parts = check_arity(&args, 2).and_then(|_| {
+        let string = string_argument("split", &args, 0)?;
+        let delimiter = string_argument("split", &args, 1)?;
+        Ok(if delimiter.is_empty() {
+            string.chars().map(|c| c.to_string()).collect::<Vec<_>>()
+        } else {
+            string.split(delimiter).map(str::to_string).collect()
+        })
+    });
+
+    match parts {
+        Ok(parts) => Box::new(Array::new(
+            parts
+                .into_iter()
+                .map(|part| Box::new(StringObj::new(part)) as Box<dyn Object>)
+                .collect(),
+        )),
+        Err(err) => err,
+    }
+}
+
+/// Define the join() function
+fn join_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    let array = match array_argument("join", &args, 2) {
+        Ok(array) => array,
+        Err(err) => return err,
+    };
+    let separator = match string_argument("join", &args, 1) {
+        Ok(separator) => separator,
+        Err(err) => return err,
+    };
+
+    let parts: Vec<String> = array.elements.iter().map(|e| e.inspect()).collect();
+    Box::new(StringObj::new(parts.join(separator)))
+}
+
+/// Applies a string to string transformation to a single string argument
+fn map_string_argument(
+    name: &str,
+    args: &[Box<dyn Object>],
+    f: impl Fn(&str) -> String,
+) -> Box<dyn Object> {
+    match check_arity(args, 1).and_then(|_| string_argument(name, args, 0)) {
+        Ok(string) => Box::new(StringObj::new(f(string))),
+        Err(err) => err,
+    }
+}
+
+/// Define the trim() function
+fn trim_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    map_string_argument("trim", &args, |s| s.trim().to_string())
+}
+
+/// Define the upper() function
+fn upper_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    map_string_argument("upper", &args, str::to_uppercase)
+}
+
+/// Define the lower() function
+fn lower_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    map_string_argument("lower", &args, str::to_lowercase)
+}
+
+/// Applies a string predicate to two string arguments
+fn string_predicate(
+    name: &str,
+    args: &[Box<dyn Object>],
+    f: impl Fn(&str, &str) -> bool,
+) -> Box<dyn Object> {
+    let result = check_arity(args, 2).and_then(|_| {
+        let string = string_argument(name, args, 0)?;
+        let pattern = string_argument(name, args, 1)?;
+        Ok(f(string, pattern))
+    });
+
+    match result {
+        Ok(value) => Box::new(Boolean::new(value)),
+        Err(err) => err,
+    }
+}
+
+/// Define the contains() function
+///
+/// Checks for a substring in a string, or for an element equal to the value,
+/// as `==` compares, in an array
+fn contains_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if let Err(err) = check_arity(&args, 2) {
+        return err;
+    }
+
+    if let Some(array) = args[0].as_any().downcast_ref::<Array>() {
+        let needle = &args[1];
+        let found = array
+            .elements
+            .iter()
+            .any(|e| objects_equal(e.as_ref(), needle.as_ref()));
+        return Box::new(Boolean::new(found));
+    }
+
+    string_predicate("contains", &args, |s, p| s.contains(p))
+}
+
+/// Define the starts_with() function
+fn starts_with_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    string_predicate("starts_with", &args, |s, p| s.starts_with(p))
+}
+
+/// Define the ends_with() function
+fn ends_with_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    string_predicate("ends_with", &args, |s, p| s.ends_with(p))
+}
+
+/// Define the replace() function, replacing every occurrence of the pattern
+fn replace_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    let result = check_arity(&args, 3).and_then(|_| {
+        let string = string_argument("replace", &args, 0)?;
+        let from = string_argument("replace", &args, 1)?;
+        let to = string_argument("replace", &args, 2)?;
+        Ok(string.replace(from, to))
+    });
+
+    match result {
+        Ok(value) => Box::new(StringObj::new(value)),
+        Err(err) => err,
+    }
+}
+
+/// Builds the error for a non-numeric argument
+fn not_numeric_error(name: &str, arg: &dyn Object) -> Box<dyn Object> {
+    new_error(&format!(
+        "argument to `{}` must be INTEGER or FLOAT, got {}",
+        name,
+        arg.type_()
+    ))
+}
+
+/// Define the abs() function
+fn abs_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if let Err(err) = check_arity(&args, 1) {
+        return err;
+    }
+
+    if let Some(int) = args[0].as_any().downcast_ref::<Integer>() {
+        return match int.value.checked_abs() {
+            Some(value) => Box::new(Integer::new(value)),
+            None => new_error("integer overflow"),
+        };
+    }
+    if let Some(float) = args[0].as_any().downcast_ref::<FloatObj>() {
+        return Box::new(FloatObj::new(float.value.abs()));
+    }
+
+    not_numeric_error("abs", args[0].as_ref())
+}
+
+/// Define the sqrt() function, which always returns a FLOAT
+fn sqrt_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if let Err(err) = check_arity(&args, 1) {
+        return err;
+    }
+
+    match numeric_value(args[0].as_ref()) {
+        Some(value) if value < 0.0 => new_error("argument to `sqrt` must not be negative"),
+        Some(value) => Box::new(FloatObj::new(value.sqrt())),
+        None => not_numeric_error("sqrt", args[0].as_ref()),
+    }
+}
+
+/// Rounds a number to an INTEGER using the given rounding function
+fn round_to_integer(name: &str, args: &[Box<dyn Object>], f: fn(f64) -> f64) -> Box<dyn Object> {
+    if let Err(err) = check_arity(args, 1) {
+        return err;
+    }
+
+    if args[0].type_() == ObjectType::Integer {
+        return args[0].clone();
+    }
+    match args[0].as_any().downcast_ref::<FloatObj>() {
+        Some(float) => float_to_integer(f(float.value)),
+        None => not_numeric_error(name, args[0].as_ref()),
+    }
+}
+
+/// Converts a whole-number FLOAT to an INTEGER, or errors if it is NaN,
+/// infinite or outside the range of an INTEGER
+fn float_to_integer(value: f64) -> Box<dyn Object> {
+    // i64::MIN is exactly -2^63, and 2^63 is the first float past i64::MAX
+    if value.is_finite() && value >= i64::MIN as f64 && value < -(i64::MIN as f64) {
+        Box::new(Integer::new(value as i64))
+    } else {
+        new_error(&format!(
+            "could not convert {} to INTEGER",
+            FloatObj::new(value).inspect()
+        ))
+    }
+}
+
+/// Define the floor() function
+fn floor_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    round_to_integer("floor", &args, f64::floor)
+}
+
+/// Define the ceil() function
+fn ceil_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    round_to_integer("ceil", &args, f64::ceil)
+}
+
+/// Define the pow() function
+///
+/// Two integers give an INTEGER, anything involving a float gives a FLOAT
+fn pow_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if let Err(err) = check_arity(&args, 2) {
+        return err;
+    }
+
+    if let (Some(base), Some(exp)) = (
+        args[0].as_any().downcast_ref::<Integer>(),
+        args[1].as_any().downcast_ref::<Integer>(),
+    ) {
+        if exp.value < 0 {
+            return new_error("negative exponent: integer power requires a non-negative exponent");
+        }
+        return match u32::try_from(exp.value)
+            .ok()
+            .and_then(|exp| base.value.checked_pow(exp))
+        {
+            Some(value) => Box::new(Integer::new(value)),
+            None => new_error("integer overflow"),
+        };
+    }
+
+    match (
+        numeric_value(args[0].as_ref()),
+        numeric_value(args[1].as_ref()),
+    ) {
+        (Some(base), Some(exp)) => Box::new(FloatObj::new(base.powf(exp))),
+        (None, _) => not_numeric_error("pow", args[0].as_ref()),
+        (_, None) => not_numeric_error("pow", args[1].as_ref()),
+    }
+}
+
+/// Picks one of two numbers of the same type, keeping the first when equal
+fn pick_number(
+    name: &str,
+    args: &[Box<dyn Object>],
+    take_second: fn(Ordering) -> bool,
+) -> Box<dyn Object> {
+    if let Err(err) = check_arity(args, 2) {
+        return err;
+    }
+
+    for arg in args {
+        if numeric_value(arg.as_ref()).is_none() {
+            return not_numeric_error(name, arg.as_ref());
+        }
+    }
+    if args[0].type_() != args[1].type_() {
+        return new_error(&format!(
+            "arguments to `{}` must be the same type, got {} and {}",
+            name,
+            args[0].type_(),
+            args[1].type_()
+        ));
+    }
+
+    let ordering = match natural_ordering(args[1].as_ref(), args[0].as_ref()) {
+        Ok(ordering) => ordering,
+        Err(msg) => return new_error(&msg),
+    };
+    if take_second(ordering) {
+        args[1].clone()
+    } else {
+        args[0].clone()
+    }
+}
+
+/// Define the min() function
+fn min_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    pick_number("min", &args, Ordering::is_lt)
+}
+
+/// Define the max() function
+fn max_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    pick_number("max", &args, Ordering::is_gt)
+}
+
+/// Define the sum() function, which gives 0 for an empty array
+fn sum_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    fold_numbers("sum", &args, 0, i64::checked_add, |a, b| a + b)
+}
+
+/// Define the product() function, which gives 1 for an empty array
+fn product_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    fold_numbers("product", &args, 1, i64::checked_mul, |a, b| a * b)
+}
+
+/// Define the avg() function, returning the mean as a float
+fn avg_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    let array = match array_argument("avg", &args, 1) {
+        Ok(array) => array,
+        Err(err) => return err,
+    };
+
+    if array.elements.is_empty() {
+        return new_error("cannot take `avg` of an empty array");
+    }
+
+    let mut total = 0.0;
+    for element in &array.elements {
+        match numeric_value(element.as_ref()) {
+            Some(value) => total += value,
+            None => return not_numeric_element_error("avg", element.as_ref()),
+        }
+    }
+
+    Box::new(FloatObj::new(total / array.elements.len() as f64))
+}
+
+/// Combines the elements of a numeric array, starting from `identity`
+///
+/// The result stays an integer until the first float, after which the rest
+/// is combined as floats
+fn fold_numbers(
+    name: &str,
+    args: &[Box<dyn Object>],
+    identity: i64,
+    int_op: fn(i64, i64) -> Option<i64>,
+    float_op: fn(f64, f64) -> f64,
+) -> Box<dyn Object> {
+    let array = match array_argument(name, args, 1) {
+        Ok(array) => array,
+        Err(err) => return err,
+    };
+
+    let mut int_total = identity;
+    let mut float_total: Option<f64> = None;
+    for element in &array.elements {
+        if let (None, Some(int)) = (float_total, element.as_any().downcast_ref::<Integer>()) {
+            match int_op(int_total, int.value) {
+                Some(total) => int_total = total,
+                None => return new_error("integer overflow"),
+            }
+            continue;
+        }
+
+        match numeric_value(element.as_ref()) {
+            Some(value) => {
+                let total = float_total.unwrap_or(int_total as f64);
+                float_total = Some(float_op(total, value));
+            }
+            None => return not_numeric_element_error(name, element.as_ref()),
+        }
+    }
+
+    match float_total {
+        Some(total) => Box::new(FloatObj::new(total)),
+        None => Box::new(Integer::new(int_total)),
+    }
+}
+
+fn not_numeric_element_error(name: &str, element: &dyn Object) -> Box<dyn Object> {
+    new_error(&format!(
+        "elements of `{}` must be INTEGER or FLOAT, got {}",
+        name,
+        element.type_()
+    ))
+}
+
+/// Materializes every value of a range into an array
+pub fn range_to_array(range: &Range) -> Array {
+    Array::new(
+        range
+            .values()
+            .map(|value| Box::new(Integer::new(value)) as Box<dyn Object>)
+            .collect(),
+    )
+}
+
+/// Define the range() function
+///
+/// Takes `(end)`, `(start, end)` or `(start, end, step)`, like Python's range
+fn range_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if args.is_empty() || args.len() > 3 {
+        return new_error(&format!(
+            "wrong number of arguments. got={}, want=1 to 3",
+            args.len()
+        ));
+    }
+
+    let mut values = Vec::with_capacity(args.len());
+    for arg in &args {
+        match arg.as_any().downcast_ref::<Integer>() {
+            Some(int) => values.push(int.value),
+            None => {
+                return new_error(&format!(
+                    "argument to `range` must be INTEGER, got {}",
+                    arg.type_()
+                ))
+            }
+        }
+    }
+
+    let range = match values[..] {
+        [end] => Range::new(0, end, 1),
+        [start, end] => Range::new(start, end, 1),
+        [start, end, step] => Range::new(start, end, step),
+        _ => unreachable!(),
+    };
+
+    if range.step == 0 {
+        return new_error("range step must not be zero");
+    }
+
+    Box::new(range)
+}
+
+/// Define the type() function
+fn type_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if args.len() != 1 {
+        return new_error(&format!(
+            "wrong number of arguments. got={}, want=1",
+            args.len()
+        ));
+    }
+
+    Box::new(StringObj::new(args[0].type_().to_string()))
+}
+
+/// Define the int() function
+fn int_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if args.len() != 1 {
+        return new_error(&format!(
+            "wrong number of arguments. got={}, want=1",
+            args.len()
+        ));
+    }
+
+    match args[0].type_() {
+        ObjectType::Integer => args[0].clone(),
+        ObjectType::Float => {
+            let float = args[0].as_any().downcast_ref::<FloatObj>().unwrap();
+            float_to_integer(float.value.trunc())
+        }
+        ObjectType::Boolean => {
+            let boolean = args[0].as_any().downcast_ref::<Boolean>().unwrap();
+            Box::new(Integer::new(boolean.value as i64))
+        }
+        ObjectType::String => {
+            let string_obj = args[0].as_any().downcast_ref::<StringObj>().unwrap();
+            match string_obj.value.parse::<i64>() {
+                Ok(value) => Box::new(Integer::new(value)),
+                Err(_) => new_error(&format!(
+                    "could not convert \"{}\" to INTEGER",
+                    string_obj.value
+                )),
+            }
+        }
+        _ => new_error(&format!(
+            "argument to `int` not supported, got {}",
+            args[0].type_()
+        )),
+    }
+}
+
+/// Define the str() function
+fn str_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if args.len() != 1 {
+        return new_error(&format!(
+            "wrong number of arguments. got={}, want=1",
+            args.len()
+        ));
+    }
+
+    Box::new(StringObj::new(args[0].inspect()))
+}
+
+/// Define the bool() function
+///
+/// Converts with the truthiness used by `if`, so zero and empty values are
+/// false
+fn bool_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if args.len() != 1 {
+        return new_error(&format!(
+            "wrong number of arguments. got={}, want=1",
+            args.len()
+        ));
+    }
+
+    Box::new(Boolean::new(is_truthy(args[0].clone())))
+}
+
+/// Writes each argument on its own line, as `puts` does
+pub fn write_puts(out: &mut dyn Write, args: &[Box<dyn Object>]) -> io::Result<()> {
+    for arg in args {
+        writeln!(out, "{}", arg.inspect())?;
+    }
+    Ok(())
+}
+
+/// Writes the arguments separated by spaces with no trailing newline, as `print` does
+pub fn write_print(out: &mut dyn Write, args: &[Box<dyn Object>]) -> io::Result<()> {
+    let parts: Vec<String> = args.iter().map(|arg| arg.inspect()).collect();
+    write!(out, "{}", parts.join(" "))?;
+    out.flush()
+}
+
+/// Define the puts() function
+fn puts_function(evaluator: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if let Err(err) = write_puts(evaluator.output(), &args) {
+        return new_error(&format!("could not write output: {}", err));
+    }
+    Box::new(Null::new())
+}
+
+/// Define the print() function
+fn print_function(evaluator: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if let Err(err) = write_print(evaluator.output(), &args) {
+        return new_error(&format!("could not write output: {}", err));
+    }
+    Box::new(Null::new())
+}
+
+/// Define the input() function
+///
+/// Writes the optional prompt, then reads a line without its line ending.
+/// Returns null once the input is exhausted.
+fn input_function(evaluator: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if args.len() > 1 {
+        return new_error(&format!(
+            "wrong number of arguments. got={}, want=0 to 1",
+            args.len()
+        ));
+    }
+
+    if !args.is_empty() {
+        let prompt = match string_argument("input", &args, 0) {
+            Ok(prompt) => prompt,
+            Err(err) => return err,
+        };
+        let output = evaluator.output();
+        if let Err(err) = write!(output, "{}", prompt).and_then(|_| output.flush()) {
+            return new_error(&format!("could not write output: {}", err));
+        }
+    }
+
+    read_line(evaluator)
+}
+
+/// Define the readline() function, which reads a line with no prompt
+fn readline_function(evaluator: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if let Err(err) = check_arity(&args, 0) {
+        return err;
+    }
+
+    read_line(evaluator)
+}
+
+/// Reads a line from the evaluator's input, as `input` and `readline` do
+fn read_line(evaluator: &mut Evaluator) -> Box<dyn Object> {
+    let mut line = String::new();
+    match evaluator.input().read_line(&mut line) {
+        Ok(0) => Box::new(Null::new()),
+        Ok(_) => {
+            let line = line.strip_suffix('\n').unwrap_or(&line);
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            Box::new(StringObj::new(line.to_string()))
+        }
+        Err(err) => new_error(&format!("could not read input: {}", err)),
+    }
+}
+
+/// Define the format() function
+///
+/// Each `{}` in the format string is replaced by the next argument, which
+/// must use every argument exactly once
+fn format_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if args.is_empty() {
+        return new_error("wrong number of arguments. got=0, want=at least 1");
+    }
+
+    let template = match string_argument("format", &args, 0) {
+        Ok(template) => template,
+        Err(err) => return err,
+    };
+
+    let values = &args[1..];
+    let placeholders = template.matches("{}").count();
+    if placeholders != values.len() {
+        return new_error(&format!(
+            "format string expects {} arguments, got {}",
+            placeholders,
+            values.len()
+        ));
+    }
+
+    let mut result = String::new();
+    let mut parts = template.split("{}");
+    result.push_str(parts.next().unwrap_or_default());
+    for (part, value) in parts.zip(values) {
+        result.push_str(&value.inspect());
+        result.push_str(part);
+    }
+
+    Box::new(StringObj::new(result))
+}
+
+/// Define the assert() function
+///
+/// Takes `(condition)` or `(condition, message)` and fails with an error
+/// unless the condition is truthy
+fn assert_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if args.is_empty() || args.len() > 2 {
+        return new_error(&format!(
+            "wrong number of arguments. got={}, want=1 to 2",
+            args.len()
+        ));
+    }
+
+    if is_truthy(args[0].clone()) {
+        return Box::new(Null::new());
+    }
+
+    match args.get(1) {
+        Some(message) => new_error(&format!("assertion failed: {}", message.inspect())),
+        None => new_error("assertion failed"),
+    }
+}
+
+/// Define the error() function, which raises an error with the given message,
+/// or raises again an error bound by `catch`
+fn error_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if let [caught] = args.as_slice() {
+        if let Some(caught) = caught.as_any().downcast_ref::<CaughtError>() {
+            return Box::new(caught.error.clone());
+        }
+    }
+    match check_arity(&args, 1).and_then(|_| string_argument("error", &args, 0)) {
+        Ok(message) => new_error(message),
+        Err(err) => err,
+    }
+}
+
+/// Define the is_error() function
+///
+/// Errors normally propagate past any call they are passed to, so this
+/// builtin is registered as one that catches them
+fn is_error_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if let Err(err) = check_arity(&args, 1) {
+        return err;
+    }
+
+    Box::new(Boolean::new(matches!(
+        args[0].type_(),
+        ObjectType::Error | ObjectType::CaughtError
+    )))
+}
+
+/// Define the error_message() function, returning the message of an error
+/// such as the one bound by a `catch`
+///
+/// Like is_error(), this is registered as a builtin that catches errors
+fn error_message_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if let Err(err) = check_arity(&args, 1) {
+        return err;
+    }
+
+    let any = args[0].as_any();
+    let error = any.downcast_ref::<Error>().or_else(|| {
+        any.downcast_ref::<CaughtError>()
+            .map(|caught| &caught.error)
+    });
+    match error {
+        Some(error) => Box::new(StringObj::new(error.message.clone())),
+        None => new_error(&format!(
+            "argument to `error_message` must be ERROR, got {}",
+            args[0].type_()
+        )),
+    }
+}
+
+/// Define the exit() function, taking an optional exit code that defaults to 0
+fn exit_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    match args.as_slice() {
+        [] => Box::new(ExitValue::new(0)),
+        [code] => match code.as_any().downcast_ref::<Integer>() {
+            Some(code) => Box::new(ExitValue::new(code.value)),
+            None => new_error(&format!(
+                "argument to `exit` must be INTEGER, got {}",
+                code.type_()
+            )),
+        },
+        _ => new_error(&format!(
+            "wrong number of arguments. got={}, want=0 to 1",
+            args.len()
+        )),
+    }
+}
+
+/// Define the keys() function, returning a hash's keys in sorted order
+fn keys_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    match hash_argument("keys", &args) {
+        Ok(hash) => {
+            let mut keys: Vec<&HashKey> = hash.pairs.keys().collect();
+            keys.sort();
+            Box::new(Array::new(
+                keys.into_iter().map(HashKey::to_object).collect(),
+            ))
+        }
+        Err(err) => err,
+    }
+}
+
+/// Define the values() function, in the same order as keys()
+fn values_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    match hash_argument("values", &args) {
+        Ok(hash) => {
+            let mut pairs: Vec<(&HashKey, &Box<dyn Object>)> = hash.pairs.iter().collect();
+            pairs.sort_by(|a, b| a.0.cmp(b.0));
+            Box::new(Array::new(
+                pairs.into_iter().map(|(_, value)| value.clone()).collect(),
+            ))
+        }
+        Err(err) => err,
+    }
+}
+
+/// Define the entries() function, returning `[key, value]` pairs in the same
+/// order as keys()
+fn entries_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    match hash_argument("entries", &args) {
+        Ok(hash) => {
+            let mut pairs: Vec<(&HashKey, &Box<dyn Object>)> = hash.pairs.iter().collect();
+            pairs.sort_by(|a, b| a.0.cmp(b.0));
+            Box::new(Array::new(
+                pairs
+                    .into_iter()
+                    .map(|(key, value)| {
+                        Box::new(Array::new(vec![key.to_object(), value.clone()]))
+                            as Box<dyn Object>
+                    })
+                    .collect(),
+            ))
+        }
+        Err(err) => err,
+    }
+}
+
+/// Define the delete() function, returning a copy of the hash without the key
+fn delete_function(_: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    if let Err(err) = check_arity(&args, 2) {
+        return err;
+    }
+
+    let hash = match args[0].as_any().downcast_ref::<Hash>() {
+        Some(hash) => hash,
+        None => {
+            return new_error(&format!(
+                "argument to `delete` must be HASH, got {}",
+                args[0].type_()
+            ))
+        }
+    };
+
+    let key = match object_to_hash_key(&*args[1]) {
+        Some(key) => key,
+        None => return new_error(&format!("unusable as hash key: {}", args[1].type_())),
+    };
+
+    let mut pairs = hash.pairs.clone();
+    pairs.remove(&key);
+    Box::new(Hash::new(pairs))
+}
+
+/// Define the unbind() function, which removes a name from the caller's scope
+fn unbind_function(evaluator: &mut Evaluator, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
+    let name = match check_arity(&args, 1).and_then(|_| string_argument("unbind", &args, 0)) {
+        Ok(name) => name,
+        Err(err) => return err,
+    };
+
+    let env = evaluator.caller_environment();
+    if env.is_const(name) {
+        return new_error(&format!("cannot unbind const binding '{}'", name));
+    }
+
+    match env.delete(name) {
+        Some(_) => Box::new(Null::new()),
+        None => new_error(&format!("identifier not found: {}", name)),
+    }
+}
+
+/// Checks for a single hash argument
+fn hash_argument<'a>(name: &str, args: &'a [Box<dyn Object>]) -> Result<&'a Hash, Box<dyn Object>> {
+    check_arity(args, 1)?;
+
+    args[0].as_any().downcast_ref::<Hash>().ok_or_else(|| {
+        new_error(&format!(
+            "argument to `{}` must be HASH, got {}",
+            name,
+            args[0].type_()
+        ))
+    })
+}
+
+/// Looks up a method callable as `value.name(args)` for the given type
+///
+/// Methods are builtins that take the receiver as their first argument, so
+/// `"hi".upper()` runs the same code as `upper("hi")`.
+pub fn get_method(receiver: ObjectType, name: &str) -> Option<BuiltinFunction> {
+    let func: BuiltinFunction = match (receiver, name) {
+        (ObjectType::String, "len") => len_function,
+        (ObjectType::String, "upper") => upper_function,
+        (ObjectType::String, "lower") => lower_function,
+        (ObjectType::String, "trim") => trim_function,
+        (ObjectType::String, "split") => split_function,
+        (ObjectType::String, "contains") => contains_function,
+        (ObjectType::String, "starts_with") => starts_with_function,
+        (ObjectType::String, "ends_with") => ends_with_function,
+        (ObjectType::String, "replace") => replace_function,
+        (ObjectType::String, "slice") => slice_function,
+        (ObjectType::Array | ObjectType::Range, "len") => len_function,
+        (ObjectType::Array | ObjectType::Range, "first") => first_function,
+        (ObjectType::Array | ObjectType::Range, "last") => last_function,
+        (ObjectType::Array | ObjectType::Range, "rest") => rest_function,
+        (ObjectType::Array | ObjectType::Range, "push") => push_function,
+        (ObjectType::Array | ObjectType::Range, "pop") => pop_function,
+        (ObjectType::Array | ObjectType::Range, "map") => map_function,
+        (ObjectType::Array | ObjectType::Range, "filter") => filter_function,
+        (ObjectType::Array | ObjectType::Range, "reduce") => reduce_function,
+        (ObjectType::Array | ObjectType::Range, "flat_map") => flat_map_function,
+        (ObjectType::Array | ObjectType::Range, "unique") => unique_function,
+        (ObjectType::Array | ObjectType::Range, "count_by") => count_by_function,
+        (ObjectType::Array | ObjectType::Range, "sum") => sum_function,
+        (ObjectType::Array | ObjectType::Range, "product") => product_function,
+        (ObjectType::Array | ObjectType::Range, "avg") => avg_function,
+        (ObjectType::Array | ObjectType::Range, "slice") => slice_function,
+        (ObjectType::Array | ObjectType::Range, "find") => find_function,
+        (ObjectType::Array | ObjectType::Range, "find_index") => find_index_function,
+        (ObjectType::Array | ObjectType::Range, "any") => any_function,
+        (ObjectType::Array | ObjectType::Range, "all") => all_function,
+        (ObjectType::Array | ObjectType::Range, "none") => none_function,
+        (ObjectType::Array | ObjectType::Range, "sort") => sort_function,
+        (ObjectType::Array | ObjectType::Range, "join") => join_function,
+        (ObjectType::Array | ObjectType::Range, "contains") => contains_function,
+        (ObjectType::Hash, "keys") => keys_function,
+        (ObjectType::Hash, "values") => values_function,
+        (ObjectType::Hash, "entries") => entries_function,
+        _ => return None,
+    };
+
+    Some(func)
+}
+
 // Map for builtin function
 pub fn get_builtins() -> HashMap<String, Box<dyn Object>> {
     let mut builtins = HashMap::new();
@@ -36,5 +1659,330 @@ pub fn get_builtins() -> HashMap<String, Box<dyn Object>> {
         Box::new(Builtin::new(len_function)) as Box<dyn Object>,
     );
 
+    builtins.insert(
+        "puts".to_string(),
+        Box::new(Builtin::new(puts_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "print".to_string(),
+        Box::new(Builtin::new(print_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "input".to_string(),
+        Box::new(Builtin::new(input_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "readline".to_string(),
+        Box::new(Builtin::new(readline_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "type".to_string(),
+        Box::new(Builtin::new(type_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "int".to_string(),
+        Box::new(Builtin::new(int_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "str".to_string(),
+        Box::new(Builtin::new(str_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "bool".to_string(),
+        Box::new(Builtin::new(bool_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "format".to_string(),
+        Box::new(Builtin::new(format_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "assert".to_string(),
+        Box::new(Builtin::new(assert_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "error".to_string(),
+        Box::new(Builtin::new(error_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "is_error".to_string(),
+        Box::new(Builtin::new(is_error_function).catching_errors()) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "error_message".to_string(),
+        Box::new(Builtin::new(error_message_function).catching_errors()) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "exit".to_string(),
+        Box::new(Builtin::new(exit_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "first".to_string(),
+        Box::new(Builtin::new(first_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "last".to_string(),
+        Box::new(Builtin::new(last_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "rest".to_string(),
+        Box::new(Builtin::new(rest_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "push".to_string(),
+        Box::new(Builtin::new(push_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "pop".to_string(),
+        Box::new(Builtin::new(pop_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "map".to_string(),
+        Box::new(Builtin::new(map_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "filter".to_string(),
+        Box::new(Builtin::new(filter_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "reduce".to_string(),
+        Box::new(Builtin::new(reduce_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "find".to_string(),
+        Box::new(Builtin::new(find_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "find_index".to_string(),
+        Box::new(Builtin::new(find_index_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "any".to_string(),
+        Box::new(Builtin::new(any_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "all".to_string(),
+        Box::new(Builtin::new(all_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "none".to_string(),
+        Box::new(Builtin::new(none_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "enumerate".to_string(),
+        Box::new(Builtin::new(enumerate_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "flatten".to_string(),
+        Box::new(Builtin::new(flatten_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "flat_map".to_string(),
+        Box::new(Builtin::new(flat_map_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "unique".to_string(),
+        Box::new(Builtin::new(unique_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "count_by".to_string(),
+        Box::new(Builtin::new(count_by_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "slice".to_string(),
+        Box::new(Builtin::new(slice_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "zip".to_string(),
+        Box::new(Builtin::new(zip_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "zip_with".to_string(),
+        Box::new(Builtin::new(zip_with_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "partial".to_string(),
+        Box::new(Builtin::new(partial_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "memoize".to_string(),
+        Box::new(Builtin::new(memoize_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "compose".to_string(),
+        Box::new(Builtin::new(compose_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "pipe".to_string(),
+        Box::new(Builtin::new(pipe_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "sort".to_string(),
+        Box::new(Builtin::new(sort_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "split".to_string(),
+        Box::new(Builtin::new(split_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "join".to_string(),
+        Box::new(Builtin::new(join_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "trim".to_string(),
+        Box::new(Builtin::new(trim_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "upper".to_string(),
+        Box::new(Builtin::new(upper_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "lower".to_string(),
+        Box::new(Builtin::new(lower_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "contains".to_string(),
+        Box::new(Builtin::new(contains_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "starts_with".to_string(),
+        Box::new(Builtin::new(starts_with_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "ends_with".to_string(),
+        Box::new(Builtin::new(ends_with_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "replace".to_string(),
+        Box::new(Builtin::new(replace_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "keys".to_string(),
+        Box::new(Builtin::new(keys_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "values".to_string(),
+        Box::new(Builtin::new(values_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "entries".to_string(),
+        Box::new(Builtin::new(entries_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "delete".to_string(),
+        Box::new(Builtin::new(delete_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "unbind".to_string(),
+        Box::new(Builtin::new(unbind_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "abs".to_string(),
+        Box::new(Builtin::new(abs_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "sqrt".to_string(),
+        Box::new(Builtin::new(sqrt_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "floor".to_string(),
+        Box::new(Builtin::new(floor_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "ceil".to_string(),
+        Box::new(Builtin::new(ceil_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "pow".to_string(),
+        Box::new(Builtin::new(pow_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "min".to_string(),
+        Box::new(Builtin::new(min_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "max".to_string(),
+        Box::new(Builtin::new(max_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "sum".to_string(),
+        Box::new(Builtin::new(sum_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "product".to_string(),
+        Box::new(Builtin::new(product_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "avg".to_string(),
+        Box::new(Builtin::new(avg_function)) as Box<dyn Object>,
+    );
+
+    builtins.insert(
+        "range".to_string(),
+        Box::new(Builtin::new(range_function)) as Box<dyn Object>,
+    );
+
     builtins
 }
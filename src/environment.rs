@@ -1,100 +1,234 @@
-use crate::object::{Boolean, Builtin, Function, Integer, Null, Object, ObjectType, StringObj};
-use std::rc::Rc;
-use std::{cell::RefCell, collections::HashMap};
+use crate::object::{Function, Object};
+use std::rc::{Rc, Weak};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+};
+
+type Bindings = HashMap<String, Box<dyn Object>>;
+
+/// The bindings of a scope. Only functions stored in the scope they close
+/// over hold it weakly; see `Environment::set`.
+#[derive(Debug, Clone)]
+enum Store {
+    Strong(Rc<RefCell<Bindings>>),
+    Weak(Weak<RefCell<Bindings>>),
+}
 
 /// Stores variable bindings
-#[derive(Debug)]
+///
+/// The bindings of a scope are shared between clones of its `Environment`,
+/// so a closure that captures a clone sees later updates to the scope it was
+/// created in, and can update that scope itself.
+#[derive(Debug, Clone)]
 pub struct Environment {
-    store: HashMap<String, Box<dyn Object>>,
+    store: Store,
+    /// Names bound with `const` in this scope
+    consts: Rc<RefCell<HashSet<String>>>,
     outer: Option<Rc<RefCell<Environment>>>,
 }
 
 impl Environment {
     pub fn new() -> Self {
         Environment {
-            store: HashMap::new(),
+            store: Store::Strong(Rc::new(RefCell::new(HashMap::new()))),
+            consts: Rc::new(RefCell::new(HashSet::new())),
             outer: None,
         }
     }
 
     pub fn new_enclosed(outer: Rc<RefCell<Environment>>) -> Self {
         Environment {
-            store: HashMap::new(),
+            store: Store::Strong(Rc::new(RefCell::new(HashMap::new()))),
+            consts: Rc::new(RefCell::new(HashSet::new())),
             outer: Some(outer),
         }
     }
 
+    /// The bindings of this scope
+    fn bindings(&self) -> Rc<RefCell<Bindings>> {
+        match &self.store {
+            Store::Strong(store) => Rc::clone(store),
+            // weak scopes only live inside the store that keeps them alive
+            Store::Weak(store) => store.upgrade().expect("scope dropped while in use"),
+        }
+    }
+
+    /// Whether both environments are the same scope, sharing its bindings
+    pub fn same_scope(&self, other: &Environment) -> bool {
+        let ptr = |env: &Environment| match &env.store {
+            Store::Strong(store) => Rc::as_ptr(store),
+            Store::Weak(store) => store.as_ptr(),
+        };
+        ptr(self) == ptr(other)
+    }
+
     /// Gets variable from Environment
-    pub fn get(&self, name: &String) -> Option<Box<dyn Object>> {
-        match self.store.get(name) {
-            Some(obj) => Some(obj.clone()),
-            None => {
-                if let Some(outer) = &self.outer {
-                    outer.borrow().get(name)
-                } else {
-                    None
-                }
-            }
+    pub fn get(&self, name: &str) -> Option<Box<dyn Object>> {
+        if let Some(obj) = self.bindings().borrow().get(name) {
+            return Some(strengthen(obj.clone()));
+        }
+
+        match &self.outer {
+            Some(outer) => outer.borrow().get(name),
+            None => None,
+        }
+    }
+
+    /// Names bound in this scope and every enclosing one, sorted and
+    /// without duplicates
+    ///
+    /// The names are owned because the bindings live behind a `RefCell`.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.bindings().borrow().keys().cloned().collect();
+        if let Some(outer) = &self.outer {
+            names.extend(outer.borrow().names());
         }
+
+        names.sort();
+        names.dedup();
+        names
     }
 
     /// Sets a variable in Environment
+    ///
+    /// A function that closes over this scope is stored holding it weakly,
+    /// as otherwise the scope and the function would keep each other alive
+    /// after the scope is done with, say when a call that defines an inner
+    /// function returns. `get` hands out copies that hold the scope strongly.
     pub fn set(&mut self, name: String, val: Box<dyn Object>) -> Box<dyn Object> {
-        self.store.insert(name, val.clone());
+        let stored = self.weaken(val.clone());
+        self.bindings().borrow_mut().insert(name, stored);
         val
     }
+
+    /// Makes a function that closes over this scope hold it weakly
+    fn weaken(&self, val: Box<dyn Object>) -> Box<dyn Object> {
+        let Some(function) = val.as_any().downcast_ref::<Function>() else {
+            return val;
+        };
+
+        let env = weakened(&function.env.borrow(), &self.bindings());
+        match env {
+            Some(env) => Box::new(Function {
+                env: Rc::new(RefCell::new(env)),
+                ..function.clone()
+            }),
+            None => val,
+        }
+    }
+
+    /// Removes a variable from this scope, returning its value
+    ///
+    /// Outer scopes are left alone, so a binding they hold becomes visible again
+    pub fn delete(&mut self, name: &str) -> Option<Box<dyn Object>> {
+        self.consts.borrow_mut().remove(name);
+        let removed = self.bindings().borrow_mut().remove(name);
+        removed.map(strengthen)
+    }
+
+    /// Updates a variable in the nearest scope that binds it
+    ///
+    /// Returns false, leaving every scope untouched, if `name` is not bound
+    pub fn assign(&self, name: &str, val: Box<dyn Object>) -> bool {
+        let bindings = self.bindings();
+        if let Some(slot) = bindings.borrow_mut().get_mut(name) {
+            *slot = self.weaken(val);
+            return true;
+        }
+
+        match &self.outer {
+            Some(outer) => outer.borrow().assign(name, val),
+            None => false,
+        }
+    }
+
+    /// Sets a variable and marks it const in this scope
+    pub fn set_const(&mut self, name: String, val: Box<dyn Object>) -> Box<dyn Object> {
+        self.consts.borrow_mut().insert(name.clone());
+        self.set(name, val)
+    }
+
+    /// Whether `name` was bound with `const` in this scope
+    ///
+    /// Outer scopes are not consulted, so an enclosed scope may shadow a const
+    pub fn is_const(&self, name: &str) -> bool {
+        self.consts.borrow().contains(name)
+    }
+
+    /// Whether the nearest scope that binds `name` bound it with `const`
+    pub fn resolves_to_const(&self, name: &str) -> bool {
+        if self.bindings().borrow().contains_key(name) {
+            return self.is_const(name);
+        }
+
+        match &self.outer {
+            Some(outer) => outer.borrow().resolves_to_const(name),
+            None => false,
+        }
+    }
 }
 
-/// Clone for Box dyn
-impl Clone for Box<dyn Object> {
-    fn clone(&self) -> Self {
-        match self.type_() {
-            ObjectType::Integer => {
-                let int = self.as_any().downcast_ref::<Integer>().unwrap();
-                Box::new(Integer::new(int.value))
-            }
-            ObjectType::Boolean => {
-                let boolean = self.as_any().downcast_ref::<Boolean>().unwrap();
-                Box::new(Boolean::new(boolean.value))
-            }
-            ObjectType::String => {
-                let string = self.as_any().downcast_ref::<StringObj>().unwrap();
-                Box::new(StringObj::new(string.value.clone()))
-            }
-            ObjectType::Function => {
-                if let Some(function) = self.as_any().downcast_ref::<Function>() {
-                    Box::new(Function {
-                        parameters: function.parameters.clone(),
-                        body: function.body.clone(),
-                        env: Rc::clone(&function.env),
-                    })
-                } else {
-                    Box::new(Null::new())
-                }
-            }
-            ObjectType::Builtin => {
-                let builtin = self.as_any().downcast_ref::<Builtin>().unwrap();
-                Box::new(Builtin::new(builtin.func))
-            }
-
-            _ => Box::new(Null::new()),
+/// A copy of `env` that holds `target` weakly, or None if neither `env` nor
+/// any scope enclosing it is `target`
+fn weakened(env: &Environment, target: &Rc<RefCell<Bindings>>) -> Option<Environment> {
+    if let Store::Strong(store) = &env.store {
+        if Rc::ptr_eq(store, target) {
+            return Some(Environment {
+                store: Store::Weak(Rc::downgrade(store)),
+                ..env.clone()
+            });
         }
     }
+
+    let outer = weakened(&env.outer.as_ref()?.borrow(), target)?;
+    Some(Environment {
+        outer: Some(Rc::new(RefCell::new(outer))),
+        ..env.clone()
+    })
 }
 
-/// Clone for Enviroment
-impl Clone for Environment {
-    fn clone(&self) -> Self {
-        let mut new_env = Environment {
-            store: HashMap::new(),
-            outer: self.outer.clone(),
-        };
+/// Undoes `weakened` for a function read out of the scope it closes over
+fn strengthen(val: Box<dyn Object>) -> Box<dyn Object> {
+    let Some(function) = val.as_any().downcast_ref::<Function>() else {
+        return val;
+    };
 
-        // Clone each binding
-        for (key, val) in &self.store {
-            new_env.store.insert(key.clone(), val.clone());
-        }
+    let env = strengthened(&function.env.borrow());
+    match env {
+        Some(env) => Box::new(Function {
+            env: Rc::new(RefCell::new(env)),
+            ..function.clone()
+        }),
+        None => val,
+    }
+}
+
+/// A copy of `env` with its weak scope made strong, or None if it has none
+fn strengthened(env: &Environment) -> Option<Environment> {
+    if let Store::Weak(store) = &env.store {
+        return Some(Environment {
+            store: Store::Strong(store.upgrade()?),
+            ..env.clone()
+        });
+    }
+
+    let outer = strengthened(&env.outer.as_ref()?.borrow())?;
+    Some(Environment {
+        outer: Some(Rc::new(RefCell::new(outer))),
+        ..env.clone()
+    })
+}
 
-        new_env
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Clone for Box dyn
+impl Clone for Box<dyn Object> {
+    fn clone(&self) -> Self {
+        self.clone_box()
     }
 }
@@ -1,13 +1,18 @@
 use crate::ast::{
-    self, BlockStatement, Expression, ExpressionStatement, InfixExpression, IntegerLiteral,
-    LetStatement, PrefixExpression, Program, ReturnStatement, Statement, StringLiteral,
+    self, BlockStatement, BreakStatement, ContinueStatement, Expression, ExpressionStatement,
+    ForStatement, InfixExpression, IntegerLiteral, LetStatement, Node, PrefixExpression, Program,
+    ReturnStatement, Span, Statement, StringLiteral, WhileStatement,
 };
 use crate::builtins;
 use crate::environment::Environment;
 use crate::object::{
-    Boolean, Builtin, Error, Function, Integer, Null, Object, ObjectType, ReturnValue, StringObj,
+    object_to_hash_key, Array, Boolean, Break, Builtin, CaughtError, ComposedFunction, Continue,
+    Error, FloatObj, Function, Hash, HashKey, Integer, MemoizedFunction, Null, Object, ObjectType,
+    PartialFunction, Range, ReturnValue, StringObj, TailCall,
 };
 use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
 use std::rc::Rc;
 use std::sync::OnceLock;
 
@@ -37,8 +42,12 @@ fn native_bool_to_boolean_object(input: bool) -> Box<dyn Object> {
     }
 }
 
+/// Evaluates a program in `env`
+///
+/// A convenience wrapper around `Evaluator` for callers that manage their
+/// own environment. Bindings made by the program are visible in `env`.
 pub fn eval(program: &Program, env: &mut Environment) -> Box<dyn Object> {
-    eval_program(program, env)
+    Evaluator::with_environment(env.clone()).eval_program(program)
 }
 
 /// Create new error object
@@ -46,196 +55,1494 @@ fn new_error(message: &str) -> Box<dyn Object> {
     Box::new(Error::new(message.to_string()))
 }
 
+/// Errors and exits both stop evaluation and are passed out unchanged
 fn is_error(obj: &dyn Object) -> bool {
-    obj.type_() == ObjectType::Error
+    matches!(obj.type_(), ObjectType::Error | ObjectType::Exit)
 }
 
-fn eval_program(program: &Program, env: &mut Environment) -> Box<dyn Object> {
-    let mut result: Box<dyn Object> = Box::new(null_obj().clone());
+/// Default limit on nested function calls
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 1000;
+
+/// Default limit on nested expressions being evaluated, counted across calls.
+/// A call nests a few expressions deep, so this allows about as much as
+/// `DEFAULT_MAX_CALL_DEPTH`.
+pub const DEFAULT_MAX_EVAL_DEPTH: usize = 5000;
+
+/// Stack size for a thread that evaluates with the default depth limits,
+/// which can use over 10 MiB of stack in an unoptimized build
+pub const STACK_SIZE: usize = 64 * 1024 * 1024;
+
+/// Evaluates programs against a global environment that persists between
+/// calls, so bindings made by one program are visible to the next
+///
+/// Builtins such as `puts` and `input` go through the Evaluator's input and
+/// output, which are stdin and stdout unless replaced with `with_io`.
+///
+/// Evaluation recurses on the Rust stack, and the default depth limits need
+/// more of it than the 2 MiB Rust gives spawned threads. Evaluate on a
+/// thread with `STACK_SIZE` of stack, as `Repl` does, or lower the limits
+/// with `set_max_call_depth` and `set_max_eval_depth`.
+pub struct Evaluator<'io> {
+    env: Environment,
+    /// A frame for each function call in progress, innermost last
+    call_stack: Vec<String>,
+    /// Calls deeper than this fail instead of overflowing the Rust stack
+    max_call_depth: usize,
+    /// Number of expressions currently being evaluated, outermost included
+    eval_depth: usize,
+    /// Expressions nested deeper than this fail instead of overflowing the
+    /// Rust stack
+    max_eval_depth: usize,
+    /// Scope of the call expression that invoked the running builtin
+    caller_env: Option<Environment>,
+    /// Name the callee was called by, for the stack frame of the next call
+    callee_name: Option<String>,
+    input: Box<dyn BufRead + 'io>,
+    output: Box<dyn Write + 'io>,
+}
+
+impl Default for Evaluator<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'io> Evaluator<'io> {
+    pub fn new() -> Self {
+        Self::with_environment(Environment::new())
+    }
+
+    /// Creates an Evaluator whose global scope shares the bindings of `env`
+    pub fn with_environment(env: Environment) -> Self {
+        Evaluator {
+            env,
+            call_stack: Vec::new(),
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            eval_depth: 0,
+            max_eval_depth: DEFAULT_MAX_EVAL_DEPTH,
+            caller_env: None,
+            callee_name: None,
+            input: Box::new(BufReader::new(io::stdin())),
+            output: Box::new(io::stdout()),
+        }
+    }
+
+    /// Replaces the streams that builtins read from and write to
+    pub fn with_io(mut self, input: impl BufRead + 'io, output: impl Write + 'io) -> Self {
+        self.input = Box::new(input);
+        self.output = Box::new(output);
+        self
+    }
+
+    /// Creates an Evaluator that allows at most `n` nested function calls
+    pub fn with_max_depth(n: usize) -> Self {
+        let mut evaluator = Self::new();
+        evaluator.set_max_call_depth(n);
+        evaluator
+    }
+
+    /// Sets the maximum number of nested function calls
+    pub fn set_max_call_depth(&mut self, n: usize) {
+        self.max_call_depth = n;
+    }
+
+    /// Sets the maximum number of nested expressions being evaluated
+    pub fn set_max_eval_depth(&mut self, n: usize) {
+        self.max_eval_depth = n;
+    }
+
+    /// The global environment
+    pub fn environment(&self) -> &Environment {
+        &self.env
+    }
+
+    /// Replaces the global environment, so bindings made so far are gone
+    pub fn set_environment(&mut self, env: Environment) {
+        self.env = env;
+    }
+
+    /// The scope a builtin was called from, for builtins such as `unbind`
+    /// that act on it. Outside of any call this is the global scope.
+    pub(crate) fn caller_environment(&mut self) -> &mut Environment {
+        self.caller_env.get_or_insert_with(|| self.env.clone())
+    }
+
+    /// The stream builtins read from
+    pub fn input(&mut self) -> &mut dyn BufRead {
+        &mut self.input
+    }
+
+    /// The stream builtins write to
+    pub fn output(&mut self) -> &mut dyn Write {
+        &mut self.output
+    }
+
+    /// Evaluates each statement of `program` in the global environment
+    pub fn eval_program(&mut self, program: &Program) -> Box<dyn Object> {
+        let mut env = self.env.clone();
+        let mut result: Box<dyn Object> = Box::new(null_obj().clone());
+
+        for statement in &program.statements {
+            result = self.eval_statement(statement.as_ref(), &mut env);
+
+            if is_error(&*result) {
+                return result;
+            }
+
+            // handle return value
+            if result.type_() == ObjectType::ReturnValue {
+                return unwrap_return_value(result);
+            }
+
+            if let Some(error) = loop_signal_error(&*result) {
+                return error;
+            }
+        }
+
+        result
+    }
+
+    fn eval_statement(
+        &mut self,
+        statement: &dyn Statement,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        let any = statement.as_any();
+
+        if let Some(expr_stmt) = any.downcast_ref::<ExpressionStatement>() {
+            return self.eval_expression(expr_stmt.expression.as_ref(), env);
+        }
+
+        if let Some(return_stmt) = any.downcast_ref::<ReturnStatement>() {
+            return self.eval_return_statement(return_stmt, env);
+        }
+
+        if let Some(let_stmt) = any.downcast_ref::<LetStatement>() {
+            let result = self.eval_let_statement(let_stmt, env);
+            return attach_span(result, let_stmt.span());
+        }
+
+        if let Some(destructure) = any.downcast_ref::<ast::DestructureStatement>() {
+            return self.eval_destructure_statement(destructure, env);
+        }
+
+        if let Some(assign) = any.downcast_ref::<ast::AssignStatement>() {
+            let result = self.eval_assign_statement(assign, env);
+            return attach_span(result, assign.span());
+        }
+
+        if let Some(const_stmt) = any.downcast_ref::<ast::ConstStatement>() {
+            let result = self.eval_const_statement(const_stmt, env);
+            return attach_span(result, const_stmt.span());
+        }
+
+        if let Some(result) = self.eval_loop(statement, None, env) {
+            return result;
+        }
+
+        if let Some(labeled) = any.downcast_ref::<ast::LabeledStatement>() {
+            let label = Some(labeled.label.as_str());
+            if let Some(result) = self.eval_loop(labeled.body.as_ref(), label, env) {
+                return result;
+            }
+            return self.eval_statement(labeled.body.as_ref(), env);
+        }
+
+        if let Some(break_stmt) = any.downcast_ref::<BreakStatement>() {
+            return Box::new(Break::new(break_stmt.label.clone()));
+        }
+
+        if let Some(continue_stmt) = any.downcast_ref::<ContinueStatement>() {
+            return Box::new(Continue::new(continue_stmt.label.clone()));
+        }
+
+        Box::new(null_obj().clone())
+    }
+
+    fn eval_return_statement(
+        &mut self,
+        return_stmt: &ReturnStatement,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        if let Some(return_val) = &return_stmt.return_value {
+            // a call returned from a function body is left to apply_function
+            if !self.call_stack.is_empty() {
+                if let Some(call) = return_val.as_any().downcast_ref::<ast::CallExpression>() {
+                    return self.eval_tail_call(call, env);
+                }
+            }
+
+            let val = self.eval_expression(return_val.as_ref(), env);
+            if is_error(&*val) {
+                return val;
+            }
+            return Box::new(ReturnValue::new(val));
+        }
+
+        Box::new(null_obj().clone())
+    }
+
+    /// Evaluates the callee and arguments of a `return f(...)` but does not
+    /// make the call. The TailCall travels out of the function body, and
+    /// apply_function makes the call in its place once the frame is gone.
+    fn eval_tail_call(
+        &mut self,
+        call: &ast::CallExpression,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        let function = self.eval_expression(call.function.as_ref(), env);
+        if is_error(&*function) {
+            return function;
+        }
+
+        let args = self.eval_arguments(&call.arguments, env);
+        if !args.is_empty() && is_error(&*args[0]) && !catches_error(&*function, &*args[0]) {
+            return args[0].clone();
+        }
+
+        if function.type_() != ObjectType::Function {
+            self.callee_name = callee_name(call);
+            let val = attach_span(self.call_from(function, args, env), call.span());
+            if is_error(&*val) {
+                return val;
+            }
+            return Box::new(ReturnValue::new(val));
+        }
+
+        Box::new(TailCall::new(function, args).with_name(callee_name(call)))
+    }
+
+    fn eval_let_statement(
+        &mut self,
+        let_stmt: &LetStatement,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        if env.is_const(&let_stmt.name.value) {
+            return const_error(&let_stmt.name.value);
+        }
+
+        if let Some(val_expr) = &let_stmt.value {
+            let val = self.eval_expression(val_expr.as_ref(), env);
+            if is_error(&*val) {
+                return val;
+            }
+            return env.set(let_stmt.name.value.clone(), val);
+        }
+
+        Box::new(null_obj().clone())
+    }
+
+    fn eval_destructure_statement(
+        &mut self,
+        stmt: &ast::DestructureStatement,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        let val = self.eval_expression(stmt.value.as_ref(), env);
+        if is_error(&*val) {
+            return val;
+        }
+
+        if let Some(pattern) = stmt
+            .pattern
+            .as_any()
+            .downcast_ref::<ast::ArrayDestructurePattern>()
+        {
+            return attach_span(bind_array_pattern(pattern, val, env), stmt.span());
+        }
+
+        if let Some(pattern) = stmt
+            .pattern
+            .as_any()
+            .downcast_ref::<ast::HashDestructurePattern>()
+        {
+            return attach_span(bind_hash_pattern(pattern, val, env), stmt.span());
+        }
+
+        new_error(&format!("invalid destructuring pattern: {}", stmt.pattern))
+    }
+
+    fn eval_assign_statement(
+        &mut self,
+        assign: &ast::AssignStatement,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        let val = self.eval_expression(assign.value.as_ref(), env);
+        if is_error(&*val) {
+            return val;
+        }
+
+        self.assign_to(&assign.target, val, env)
+    }
+
+    fn eval_const_statement(
+        &mut self,
+        const_stmt: &ast::ConstStatement,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        if env.is_const(&const_stmt.name.value) {
+            return const_error(&const_stmt.name.value);
+        }
+
+        let val = self.eval_expression(const_stmt.value.as_ref(), env);
+        if is_error(&*val) {
+            return val;
+        }
+
+        env.set_const(const_stmt.name.value.clone(), val)
+    }
+
+    fn eval_expression(
+        &mut self,
+        expression: &dyn Expression,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        if self.eval_depth >= self.max_eval_depth {
+            return new_error("maximum recursion depth exceeded");
+        }
+
+        self.eval_depth += 1;
+        let result = self.eval_expression_node(expression, env);
+        self.eval_depth -= 1;
+        attach_span(result, expression.span())
+    }
+
+    fn eval_expression_node(
+        &mut self,
+        expression: &dyn Expression,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        let any = expression.as_any();
+
+        if let Some(int_lit) = any.downcast_ref::<IntegerLiteral>() {
+            return Box::new(Integer::new(int_lit.value));
+        }
+
+        if let Some(float_lit) = any.downcast_ref::<ast::FloatLiteral>() {
+            return Box::new(FloatObj::new(float_lit.value));
+        }
+
+        if let Some(string_lit) = any.downcast_ref::<StringLiteral>() {
+            return Box::new(StringObj::new(string_lit.value.clone()));
+        }
+
+        if let Some(bool_lit) = any.downcast_ref::<ast::Boolean>() {
+            return native_bool_to_boolean_object(bool_lit.value);
+        }
+
+        if any.is::<ast::NullLiteral>() {
+            return Box::new(null_obj().clone());
+        }
+
+        if let Some(ident) = any.downcast_ref::<ast::Identifier>() {
+            return eval_identifier(ident, env);
+        }
+
+        if let Some(prefix) = any.downcast_ref::<PrefixExpression>() {
+            return self.eval_prefix_node(prefix, env);
+        }
+
+        if let Some(infix) = any.downcast_ref::<InfixExpression>() {
+            return self.eval_infix_node(infix, env);
+        }
+
+        if let Some(if_expr) = any.downcast_ref::<ast::IfExpression>() {
+            return self.eval_if_expression(if_expr, env);
+        }
+
+        if let Some(fn_lit) = any.downcast_ref::<ast::FunctionLiteral>() {
+            return eval_function_literal(fn_lit, env);
+        }
+
+        if let Some(call) = any.downcast_ref::<ast::CallExpression>() {
+            return self.eval_call_expression(call, env);
+        }
+
+        if let Some(method_call) = any.downcast_ref::<ast::MethodCallExpression>() {
+            return self.eval_method_call_expression(method_call, env);
+        }
+
+        if let Some(array_lit) = any.downcast_ref::<ast::ArrayLiteral>() {
+            return self.eval_array_literal(array_lit, env);
+        }
+
+        if let Some(index_expr) = any.downcast_ref::<ast::IndexExpression>() {
+            return self.eval_index_node(index_expr, env);
+        }
+
+        if let Some(typeof_expr) = any.downcast_ref::<ast::TypeofExpression>() {
+            return self.eval_typeof_expression(typeof_expr, env);
+        }
+
+        if let Some(assign) = any.downcast_ref::<ast::IndexAssignExpression>() {
+            return self.eval_index_assign_expression(assign, env);
+        }
+
+        if let Some(match_expr) = any.downcast_ref::<ast::MatchExpression>() {
+            return self.eval_match_expression(match_expr, env);
+        }
+
+        if let Some(try_catch) = any.downcast_ref::<ast::TryCatchExpression>() {
+            return self.eval_try_catch_expression(try_catch, env);
+        }
+
+        if let Some(hash_lit) = any.downcast_ref::<ast::HashLiteral>() {
+            return self.eval_hash_literal(hash_lit, env);
+        }
+
+        Box::new(null_obj().clone())
+    }
+
+    fn eval_prefix_node(
+        &mut self,
+        prefix: &PrefixExpression,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        let right = self.eval_expression(prefix.right.as_ref(), env);
+
+        // Check for errors in the right expression
+        if is_error(&*right) {
+            return right;
+        }
+
+        eval_prefix_expression(&prefix.operator, right)
+    }
+
+    fn eval_infix_node(
+        &mut self,
+        infix: &InfixExpression,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        // `&&`, `||` and `??` must only evaluate the right operand when needed
+        if infix.operator == "&&" || infix.operator == "||" {
+            return self.eval_logical_expression(infix, env);
+        }
+        if infix.operator == "??" {
+            return self.eval_null_coalesce_expression(infix, env);
+        }
+
+        let left = self.eval_expression(infix.left.as_ref(), env);
+
+        // Check for errors in left expression
+        if is_error(&*left) {
+            return left;
+        }
+
+        let right = self.eval_expression(infix.right.as_ref(), env);
+
+        // Check for errors in right expression
+        if is_error(&*right) {
+            return right;
+        }
+
+        eval_infix_expression(&infix.operator, left, right)
+    }
+
+    fn eval_call_expression(
+        &mut self,
+        call: &ast::CallExpression,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        let function = self.eval_expression(call.function.as_ref(), env);
+        if is_error(&*function) {
+            return function;
+        }
+
+        let args = self.eval_arguments(&call.arguments, env);
+        if !args.is_empty() && is_error(&*args[0]) && !catches_error(&*function, &*args[0]) {
+            return args[0].clone();
+        }
+
+        self.callee_name = callee_name(call);
+        self.call_from(function, args, env)
+    }
+
+    /// Applies `function`, making `env` the caller's scope if it is a builtin
+    fn call_from(
+        &mut self,
+        function: Box<dyn Object>,
+        args: Vec<Box<dyn Object>>,
+        env: &Environment,
+    ) -> Box<dyn Object> {
+        // partial, memoized and composed functions may wrap a builtin, so
+        // they get the caller's scope too
+        if !matches!(
+            function.type_(),
+            ObjectType::Builtin
+                | ObjectType::PartialFunction
+                | ObjectType::MemoizedFunction
+                | ObjectType::ComposedFunction
+        ) {
+            return self.apply_function(function, args);
+        }
+
+        let outer = self.caller_env.replace(env.clone());
+        let result = self.apply_function(function, args);
+        self.caller_env = outer;
+        result
+    }
+
+    fn eval_array_literal(
+        &mut self,
+        array_lit: &ast::ArrayLiteral,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        let mut elements = self.eval_expressions(&array_lit.elements, env);
+        if elements.len() == 1 && is_error(&*elements[0]) {
+            return elements.remove(0);
+        }
+
+        Box::new(Array::new(elements))
+    }
+
+    fn eval_index_node(
+        &mut self,
+        index_expr: &ast::IndexExpression,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        let left = self.eval_expression(index_expr.left.as_ref(), env);
+        if is_error(&*left) {
+            return left;
+        }
+
+        let index = self.eval_expression(index_expr.index.as_ref(), env);
+        if is_error(&*index) {
+            return index;
+        }
+
+        eval_index_expression(left, index)
+    }
+
+    fn eval_typeof_expression(
+        &mut self,
+        typeof_expr: &ast::TypeofExpression,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        let value = self.eval_expression(typeof_expr.expr.as_ref(), env);
+        if is_error(&*value) {
+            return value;
+        }
+
+        Box::new(StringObj::new(value.type_().to_string()))
+    }
+
+    fn eval_index_assign_expression(
+        &mut self,
+        assign: &ast::IndexAssignExpression,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        let value = self.eval_expression(assign.value.as_ref(), env);
+        if is_error(&*value) {
+            return value;
+        }
+
+        self.eval_index_assign(&assign.target, value, env)
+    }
+
+    fn eval_expressions(
+        &mut self,
+        exps: &[Box<dyn Expression>],
+        env: &mut Environment,
+    ) -> Vec<Box<dyn Object>> {
+        let mut result = Vec::new();
+
+        for exp in exps {
+            let evaluated = self.eval_expression(exp.as_ref(), env);
+            if is_error(&*evaluated) {
+                return vec![evaluated];
+            }
+            result.push(evaluated);
+        }
+
+        result
+    }
+
+    /// Evaluates the arguments of a call, expanding each spread argument into
+    /// the elements of its array
+    fn eval_arguments(
+        &mut self,
+        args: &[Box<dyn Expression>],
+        env: &mut Environment,
+    ) -> Vec<Box<dyn Object>> {
+        let mut result = Vec::new();
+
+        for arg in args {
+            let Some(spread) = arg.as_any().downcast_ref::<ast::SpreadExpression>() else {
+                let evaluated = self.eval_expression(arg.as_ref(), env);
+                if is_error(&*evaluated) {
+                    return vec![evaluated];
+                }
+                result.push(evaluated);
+                continue;
+            };
+
+            let evaluated = self.eval_expression(spread.value.as_ref(), env);
+            if is_error(&*evaluated) {
+                return vec![evaluated];
+            }
+            match evaluated.as_any().downcast_ref::<Array>() {
+                Some(array) => result.extend(array.elements.iter().cloned()),
+                None => {
+                    return vec![new_error(&format!(
+                        "spread argument must be ARRAY, got {}",
+                        evaluated.type_()
+                    ))]
+                }
+            }
+        }
+
+        result
+    }
+
+    fn eval_method_call_expression(
+        &mut self,
+        method_call: &ast::MethodCallExpression,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        let object = self.eval_expression(method_call.object.as_ref(), env);
+        if is_error(&*object) {
+            return object;
+        }
+
+        let method = match builtins::get_method(object.type_(), &method_call.method.value) {
+            Some(method) => method,
+            None => {
+                return new_error(&format!(
+                    "unknown method: {}.{}",
+                    object.type_(),
+                    method_call.method.value
+                ))
+            }
+        };
+
+        let mut args = self.eval_expressions(&method_call.arguments, env);
+        if args.len() == 1 && is_error(&*args[0]) {
+            return args.remove(0);
+        }
+        args.insert(0, object);
+
+        method(self, args)
+    }
+
+    /// Stores `value` at the location named by `target` and returns `value`
+    ///
+    /// Arrays are updated in place at an existing index, while hashes insert or
+    /// overwrite the entry for the key.
+    ///
+    /// Bindings hold values rather than references, so the container is updated
+    /// by building a modified copy and assigning that back to whatever the
+    /// container expression names: the variable in `arr[0] = v`, or the
+    /// enclosing element in `grid[0][1] = v`. Each index in the chain is
+    /// evaluated once, and the copies are written back using those values.
+    fn eval_index_assign(
+        &mut self,
+        target: &ast::IndexExpression,
+        value: Box<dyn Object>,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        // the index expressions from outermost to innermost, and the
+        // expression at the root of the chain
+        let mut index_exprs = vec![target.index.as_ref()];
+        let mut root = target.left.as_ref();
+        while let Some(inner) = root.as_any().downcast_ref::<ast::IndexExpression>() {
+            index_exprs.push(inner.index.as_ref());
+            root = inner.left.as_ref();
+        }
+        index_exprs.reverse();
+
+        let root_value = self.eval_expression(root, env);
+        if is_error(&*root_value) {
+            return root_value;
+        }
+
+        // containers[i] is what index i is applied to
+        let mut containers = vec![root_value];
+        let mut indices = Vec::new();
+        for (i, index_expr) in index_exprs.iter().enumerate() {
+            let index = self.eval_expression(*index_expr, env);
+            if is_error(&*index) {
+                return index;
+            }
+
+            if i + 1 < index_exprs.len() {
+                let container = containers.last().unwrap().clone();
+                let element = eval_index_expression(container, index.clone());
+                if is_error(&*element) {
+                    return element;
+                }
+                containers.push(element);
+            }
+            indices.push(index);
+        }
+
+        let mut updated = value.clone();
+        for (container, index) in containers.iter().zip(&indices).rev() {
+            updated = match with_index_set(&**container, &**index, updated) {
+                Ok(container) => container,
+                Err(err) => return err,
+            };
+        }
+
+        let stored = self.assign_to(root, updated, env);
+        if is_error(&*stored) {
+            return stored;
+        }
+
+        value
+    }
+
+    /// Assigns `value` to a bound variable, updating it in the scope that
+    /// binds it, which may be a scope enclosing the current one
+    fn assign_to(
+        &mut self,
+        target: &dyn Expression,
+        value: Box<dyn Object>,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        if let Some(ident) = target.as_any().downcast_ref::<ast::Identifier>() {
+            if env.resolves_to_const(&ident.value) {
+                return const_error(&ident.value);
+            }
+            if !env.assign(&ident.value, value.clone()) {
+                return new_error(&format!("identifier not found: {}", ident.value));
+            }
+            return value;
+        }
+
+        new_error(&format!("invalid assignment target: {}", target))
+    }
+
+    /// Evaluates the body of the first arm whose pattern equals the subject
+    ///
+    /// Patterns compare with the same structural equality as `==`, except that a
+    /// pattern of another type simply does not match. Returns null if no arm
+    /// matches.
+    fn eval_match_expression(
+        &mut self,
+        match_expr: &ast::MatchExpression,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        let subject = self.eval_expression(match_expr.subject.as_ref(), env);
+        if is_error(&*subject) {
+            return subject;
+        }
+
+        for arm in &match_expr.arms {
+            if !arm.pattern.as_any().is::<ast::WildcardExpression>() {
+                let pattern = self.eval_expression(arm.pattern.as_ref(), env);
+                if is_error(&*pattern) {
+                    return pattern;
+                }
+
+                if !objects_equal(&*subject, &*pattern) {
+                    continue;
+                }
+            }
+
+            return self.eval_expression(arm.body.as_ref(), env);
+        }
+
+        Box::new(null_obj().clone())
+    }
+
+    /// Evaluates the try block, and if that results in an error evaluates
+    /// the catch block instead with the error bound to its name as a
+    /// CaughtError. Exits and loop signals are not errors, so they pass
+    /// through.
+    fn eval_try_catch_expression(
+        &mut self,
+        try_catch: &ast::TryCatchExpression,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        let mut result = self.eval_block_statement(&try_catch.try_block, env);
+
+        // a `return f(...)` in the try block has to be made here, where its
+        // error can still be caught
+        if let Some(tail_call) = result.as_any().downcast_ref::<TailCall>() {
+            self.callee_name = tail_call.name.clone();
+            let value = self.apply_function(tail_call.function.clone(), tail_call.args.clone());
+            result = if is_error(&*value) {
+                value
+            } else {
+                Box::new(ReturnValue::new(value))
+            };
+        }
+
+        let Some(error) = result.as_any().downcast_ref::<Error>() else {
+            return result;
+        };
+
+        let outer = Rc::new(RefCell::new(env.clone()));
+        let mut catch_env = Environment::new_enclosed(outer);
+        catch_env.set(
+            try_catch.error_name.value.clone(),
+            Box::new(CaughtError::new(error.clone())),
+        );
+        self.eval_block_statement(&try_catch.catch_block, &mut catch_env)
+    }
+
+    fn eval_hash_literal(
+        &mut self,
+        node: &ast::HashLiteral,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        let mut pairs = HashMap::new();
+
+        for (key_node, value_node) in &node.pairs {
+            let key = self.eval_expression(key_node.as_ref(), env);
+            if is_error(&*key) {
+                return key;
+            }
+
+            let hash_key = match object_to_hash_key(&*key) {
+                Some(hash_key) => hash_key,
+                None => return new_error(&format!("unusable as hash key: {}", key.type_())),
+            };
+
+            let value = self.eval_expression(value_node.as_ref(), env);
+            if is_error(&*value) {
+                return value;
+            }
+
+            pairs.insert(hash_key, value);
+        }
+
+        Box::new(Hash::new(pairs))
+    }
+
+    /// Calls a function or builtin with already evaluated arguments
+    ///
+    /// Higher-order builtins such as `map` call back through here with the
+    /// Evaluator they were given. A function carries the environment it closed
+    /// over, so builtins never need access to the caller's environment.
+    pub(crate) fn apply_function(
+        &mut self,
+        mut func: Box<dyn Object>,
+        mut args: Vec<Box<dyn Object>>,
+    ) -> Box<dyn Object> {
+        let mut name = self.callee_name.take();
+
+        // each tail call made by the body replaces the current call, so a
+        // function that recurses through `return f(...)` runs in constant stack
+        loop {
+            match func.type_() {
+                ObjectType::Function => {
+                    if self.call_stack.len() >= self.max_call_depth {
+                        return new_error("maximum recursion depth exceeded");
+                    }
+
+                    let function = func.as_any().downcast_ref::<Function>().unwrap();
+                    let mut extended_env = Environment::new_enclosed(Rc::clone(&function.env));
+
+                    // a named function can see itself, which lets it recurse.
+                    // Binding the name in each call, rather than in a scope the
+                    // function holds, keeps it out of the defining scope
+                    // without a function that refers to its own scope.
+                    if let Some(fn_name) = &function.name {
+                        extended_env.set(fn_name.clone(), func.clone());
+                    }
 
-    for statement in &program.statements {
-        result = eval_statement(statement.as_ref(), env);
+                    for (param_idx, param) in function.parameters.iter().enumerate() {
+                        if param_idx < args.len() {
+                            extended_env.set(param.value.clone(), args[param_idx].clone());
+                        }
+                    }
 
-        if is_error(&*result) {
-            return result;
-        }
+                    if let Some(rest) = &function.rest {
+                        let extra = args.get(function.parameters.len()..).unwrap_or_default();
+                        extended_env.set(rest.value.clone(), Box::new(Array::new(extra.to_vec())));
+                    }
 
-        // handle return value
-        if let Some(return_value) = result.as_any().downcast_ref::<ReturnValue>() {
-            // ectract the acatual value
-            match return_value.value.type_() {
-                ObjectType::Integer => {
-                    if let Some(int) = return_value.value.as_any().downcast_ref::<Integer>() {
-                        return Box::new(Integer::new(int.value));
+                    self.call_stack.push(call_frame(function, name.take()));
+                    let evaluated = self.eval_block_statement(&function.body, &mut extended_env);
+                    let evaluated = self.attach_stack_trace(evaluated);
+                    self.call_stack.pop();
+
+                    if let Some(tail_call) = evaluated.as_any().downcast_ref::<TailCall>() {
+                        func = tail_call.function.clone();
+                        args = tail_call.args.clone();
+                        name = tail_call.name.clone();
+                        continue;
+                    }
+
+                    if let Some(error) = loop_signal_error(&*evaluated) {
+                        return error;
                     }
+                    return unwrap_return_value(evaluated);
+                }
+                ObjectType::Builtin => {
+                    let builtin = func.as_any().downcast_ref::<Builtin>().unwrap();
+                    return (builtin.func)(self, args);
                 }
-                ObjectType::Boolean => {
-                    if let Some(boolean) = return_value.value.as_any().downcast_ref::<Boolean>() {
-                        return native_bool_to_boolean_object(boolean.value);
+                ObjectType::PartialFunction => {
+                    let partial = func.as_any().downcast_ref::<PartialFunction>().unwrap();
+                    args = partial.partial_args.iter().cloned().chain(args).collect();
+                    func = partial.func.clone();
+                }
+                ObjectType::MemoizedFunction => {
+                    let memoized = func.as_any().downcast_ref::<MemoizedFunction>().unwrap();
+                    if let Some(cached) = memoized.cache.borrow().get(&args) {
+                        return cached;
+                    }
+
+                    self.callee_name = name.take();
+                    let result = self.apply_function(memoized.func.clone(), args.clone());
+                    if !is_error(&*result) {
+                        memoized.cache.borrow_mut().insert(args, result.clone());
                     }
+                    return result;
                 }
-                ObjectType::Null => {
-                    return Box::new(null_obj().clone());
+                ObjectType::ComposedFunction => {
+                    let composed = func.as_any().downcast_ref::<ComposedFunction>().unwrap();
+                    let mut functions = composed.functions.iter();
+                    let Some(first) = functions.next() else {
+                        return args
+                            .into_iter()
+                            .next()
+                            .unwrap_or_else(|| Box::new(null_obj().clone()));
+                    };
+
+                    let mut result = self.apply_function(first.clone(), args);
+                    for function in functions {
+                        if is_error(&*result) {
+                            break;
+                        }
+                        result = self.apply_function(function.clone(), vec![result]);
+                    }
+                    return result;
                 }
-                _ => {}
+                _ => return new_error(&format!("not a function: {}", func.type_())),
             }
-            return Box::new(null_obj().clone());
         }
     }
 
-    result
-}
-
-fn eval_statement(statement: &dyn Statement, env: &mut Environment) -> Box<dyn Object> {
-    match statement.as_any().downcast_ref::<ExpressionStatement>() {
-        Some(expr_stmt) => {
-            let result = eval_expression(expr_stmt.expression.as_ref(), env);
-            if is_error(&*result) {
-                return result;
+    /// Records the calls in progress on an error raised in the current
+    /// function. An error that already carries a trace passes through
+    /// unchanged, so the trace reflects where the error was raised.
+    fn attach_stack_trace(&self, obj: Box<dyn Object>) -> Box<dyn Object> {
+        match obj.as_any().downcast_ref::<Error>() {
+            Some(error) if error.stack_trace.is_empty() => {
+                let stack_trace = self.call_stack.iter().rev().cloned().collect();
+                Box::new(error.clone().with_stack_trace(stack_trace))
             }
-            result
+            _ => obj,
         }
-        None => {
-            if let Some(return_stmt) = statement.as_any().downcast_ref::<ReturnStatement>() {
-                if let Some(return_val) = &return_stmt.return_value {
-                    let val = eval_expression(return_val.as_ref(), env);
-                    if is_error(&*val) {
-                        return val;
-                    }
-                    return Box::new(ReturnValue::new(val));
-                }
-                return Box::new(null_obj().clone());
-            }
+    }
 
-            // Handle let statements
-            if let Some(let_stmt) = statement.as_any().downcast_ref::<LetStatement>() {
-                if let Some(val_expr) = &let_stmt.value {
-                    let val = eval_expression(val_expr.as_ref(), env);
-                    if is_error(&*val) {
-                        return val;
-                    }
-                    return env.set(let_stmt.name.value.clone(), val);
-                }
-                return Box::new(null_obj().clone());
-            }
+    fn eval_if_expression(
+        &mut self,
+        if_expression: &ast::IfExpression,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        let condition = self.eval_expression(if_expression.condition.as_ref(), env);
+
+        if is_error(&*condition) {
+            return condition;
+        }
 
+        if is_truthy(condition) {
+            self.eval_block_statement(&if_expression.consequence, env)
+        } else if let Some(alt) = &if_expression.alternative {
+            self.eval_block_statement(alt, env)
+        } else {
             Box::new(null_obj().clone())
         }
     }
-}
 
-fn eval_expression(expression: &dyn Expression, env: &mut Environment) -> Box<dyn Object> {
-    if let Some(int_lit) = expression.as_any().downcast_ref::<IntegerLiteral>() {
-        return Box::new(Integer::new(int_lit.value));
+    /// Runs `statement` if it is a loop, which `label` names if it is labeled,
+    /// or returns None if it isn't a loop
+    fn eval_loop(
+        &mut self,
+        statement: &dyn Statement,
+        label: Option<&str>,
+        env: &mut Environment,
+    ) -> Option<Box<dyn Object>> {
+        let any = statement.as_any();
+
+        let result = if let Some(while_stmt) = any.downcast_ref::<WhileStatement>() {
+            self.eval_while_statement(while_stmt, label, env)
+        } else if let Some(do_while) = any.downcast_ref::<ast::DoWhileStatement>() {
+            self.eval_do_while_statement(do_while, label, env)
+        } else if let Some(loop_stmt) = any.downcast_ref::<ast::LoopStatement>() {
+            self.eval_loop_statement(loop_stmt, label, env)
+        } else if let Some(while_let) = any.downcast_ref::<ast::WhileLetStatement>() {
+            self.eval_while_let_statement(while_let, label, env)
+        } else if let Some(for_stmt) = any.downcast_ref::<ForStatement>() {
+            self.eval_for_statement(for_stmt, label, env)
+        } else {
+            return None;
+        };
+
+        Some(result)
     }
 
-    if let Some(string_lit) = expression.as_any().downcast_ref::<StringLiteral>() {
-        return Box::new(StringObj::new(string_lit.value.clone()));
+    fn eval_while_statement(
+        &mut self,
+        while_stmt: &WhileStatement,
+        label: Option<&str>,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        loop {
+            let condition = self.eval_expression(while_stmt.condition.as_ref(), env);
+
+            if is_error(&*condition) {
+                return condition;
+            }
+
+            if !is_truthy(condition) {
+                break;
+            }
+
+            let result = self.eval_block_statement(&while_stmt.body, env);
+
+            match loop_flow(&*result, label) {
+                LoopFlow::Next => {}
+                LoopFlow::Break => break,
+                LoopFlow::Propagate => return result,
+            }
+        }
+
+        Box::new(null_obj().clone())
     }
 
-    if let Some(bool_lit) = expression.as_any().downcast_ref::<ast::Boolean>() {
-        return native_bool_to_boolean_object(bool_lit.value);
+    /// Runs the body, then repeats it for as long as the condition holds, so
+    /// the body always runs at least once
+    fn eval_do_while_statement(
+        &mut self,
+        do_while: &ast::DoWhileStatement,
+        label: Option<&str>,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        loop {
+            let result = self.eval_block_statement(&do_while.body, env);
+
+            match loop_flow(&*result, label) {
+                LoopFlow::Next => {}
+                LoopFlow::Break => break,
+                LoopFlow::Propagate => return result,
+            }
+
+            let condition = self.eval_expression(do_while.condition.as_ref(), env);
+
+            if is_error(&*condition) {
+                return condition;
+            }
+
+            if !is_truthy(condition) {
+                break;
+            }
+        }
+
+        Box::new(null_obj().clone())
     }
 
-    // Handle identifiers
-    if let Some(ident) = expression.as_any().downcast_ref::<ast::Identifier>() {
-        return eval_identifier(ident, env);
+    /// Runs the body until it breaks, returns or errors. A body that never does
+    /// any of those loops forever.
+    fn eval_loop_statement(
+        &mut self,
+        loop_stmt: &ast::LoopStatement,
+        label: Option<&str>,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        loop {
+            let result = self.eval_block_statement(&loop_stmt.body, env);
+
+            match loop_flow(&*result, label) {
+                LoopFlow::Next => {}
+                LoopFlow::Break => break,
+                LoopFlow::Propagate => return result,
+            }
+        }
+
+        Box::new(null_obj().clone())
     }
 
-    if let Some(prefix) = expression.as_any().downcast_ref::<PrefixExpression>() {
-        let right = eval_expression(prefix.right.as_ref(), env);
+    /// Re-evaluates the expression before each iteration and stops once it is
+    /// null. Each iteration gets a scope of its own with the value bound to
+    /// the pattern name; the expression itself is evaluated in `env`.
+    fn eval_while_let_statement(
+        &mut self,
+        while_let: &ast::WhileLetStatement,
+        label: Option<&str>,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        let outer = Rc::new(RefCell::new(env.clone()));
+        loop {
+            let value = self.eval_expression(while_let.expression.as_ref(), env);
+
+            if is_error(&*value) {
+                return value;
+            }
 
-        // Check for errors in the right expression
-        if is_error(&*right) {
-            return right;
+            if value.type_() == ObjectType::Null {
+                break;
+            }
+
+            let mut scope = Environment::new_enclosed(Rc::clone(&outer));
+            scope.set(while_let.pattern.value.clone(), value);
+
+            let result = self.eval_block_statement(&while_let.body, &mut scope);
+
+            match loop_flow(&*result, label) {
+                LoopFlow::Next => {}
+                LoopFlow::Break => break,
+                LoopFlow::Propagate => return result,
+            }
         }
 
-        return eval_prefix_expression(&prefix.operator, right);
+        Box::new(null_obj().clone())
     }
 
-    if let Some(infix) = expression.as_any().downcast_ref::<InfixExpression>() {
-        let left = eval_expression(infix.left.as_ref(), env);
+    /// Runs the body once per element. Each iteration gets a scope of its own
+    /// with the element bound to the iterator name.
+    fn eval_for_statement(
+        &mut self,
+        for_stmt: &ForStatement,
+        label: Option<&str>,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        let iterable = self.eval_expression(for_stmt.iterable.as_ref(), env);
+
+        if is_error(&*iterable) {
+            return iterable;
+        }
 
-        // Check for errors in left expression
+        // ranges yield their values on demand rather than being materialized
+        let elements: Box<dyn Iterator<Item = Box<dyn Object>>> =
+            if let Some(array) = iterable.as_any().downcast_ref::<Array>() {
+                Box::new(array.elements.clone().into_iter())
+            } else if let Some(range) = iterable.as_any().downcast_ref::<Range>() {
+                Box::new(
+                    range
+                        .values()
+                        .map(|value| Box::new(Integer::new(value)) as Box<dyn Object>),
+                )
+            } else {
+                return new_error(&format!("for loop over non-iterable: {}", iterable.type_()));
+            };
+
+        let outer = Rc::new(RefCell::new(env.clone()));
+        for element in elements {
+            let mut scope = Environment::new_enclosed(Rc::clone(&outer));
+            scope.set(for_stmt.iterator_name.value.clone(), element);
+
+            let result = self.eval_block_statement(&for_stmt.body, &mut scope);
+
+            match loop_flow(&*result, label) {
+                LoopFlow::Next => {}
+                LoopFlow::Break => break,
+                LoopFlow::Propagate => return result,
+            }
+        }
+
+        Box::new(null_obj().clone())
+    }
+
+    fn eval_block_statement(
+        &mut self,
+        block: &BlockStatement,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        let mut result: Box<dyn Object> = Box::new(Null::new());
+
+        for statement in &block.statements {
+            result = self.eval_statement(statement.as_ref(), env);
+
+            // break and continue are left for the enclosing loop to consume
+            match result.type_() {
+                ObjectType::ReturnValue
+                | ObjectType::TailCall
+                | ObjectType::Error
+                | ObjectType::Exit
+                | ObjectType::Break
+                | ObjectType::Continue => return result,
+                _ => {}
+            }
+        }
+
+        result
+    }
+
+    fn eval_logical_expression(
+        &mut self,
+        infix: &InfixExpression,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        let left = self.eval_expression(infix.left.as_ref(), env);
         if is_error(&*left) {
             return left;
         }
 
-        let right = eval_expression(infix.right.as_ref(), env);
+        let left_truthy = is_truthy(left);
+        if (infix.operator == "&&" && !left_truthy) || (infix.operator == "||" && left_truthy) {
+            return native_bool_to_boolean_object(left_truthy);
+        }
 
-        // Check for errors in right expression
+        let right = self.eval_expression(infix.right.as_ref(), env);
         if is_error(&*right) {
             return right;
         }
 
-        return eval_infix_expression(&infix.operator, left, right);
+        native_bool_to_boolean_object(is_truthy(right))
     }
 
-    if let Some(if_expr) = expression.as_any().downcast_ref::<ast::IfExpression>() {
-        return eval_if_expression(if_expr, env);
-    }
+    /// Evaluates `left ?? right` to the left operand unless it is Null, in
+    /// which case the right operand is evaluated instead
+    fn eval_null_coalesce_expression(
+        &mut self,
+        infix: &InfixExpression,
+        env: &mut Environment,
+    ) -> Box<dyn Object> {
+        let left = self.eval_expression(infix.left.as_ref(), env);
+        if left.type_() != ObjectType::Null {
+            return left;
+        }
 
-    if let Some(fn_lit) = expression.as_any().downcast_ref::<ast::FunctionLiteral>() {
-        let parameters = fn_lit.parameters.clone();
-        let body = fn_lit.body.clone();
-        let env_rc = Rc::new(RefCell::new(env.clone()));
-        return Box::new(Function::new(parameters, body, env_rc));
+        self.eval_expression(infix.right.as_ref(), env)
     }
+}
 
-    if let Some(call) = expression.as_any().downcast_ref::<ast::CallExpression>() {
-        let function = eval_expression(call.function.as_ref(), env);
-        if is_error(&*function) {
-            return function;
+/// Records where an error was raised. The innermost expression wins, so an
+/// error that already carries a span passes through unchanged.
+fn attach_span(obj: Box<dyn Object>, span: Span) -> Box<dyn Object> {
+    match obj.as_any().downcast_ref::<Error>() {
+        Some(error) if error.span.is_none() && span != Span::default() => {
+            let mut error = error.clone();
+            error.span = Some(span);
+            Box::new(error)
         }
+        _ => obj,
+    }
+}
 
-        let args = eval_expressions(&call.arguments, env);
-        if !args.is_empty() && is_error(&*args[0]) {
-            return args[0].clone();
+fn eval_index_expression(left: Box<dyn Object>, index: Box<dyn Object>) -> Box<dyn Object> {
+    match (left.type_(), index.type_()) {
+        (ObjectType::Array, ObjectType::Integer) => eval_array_index_expression(left, index),
+        (ObjectType::Hash, _) => hash_index(left, index),
+        _ => new_error(&format!("index operator not supported: {}", left.type_())),
+    }
+}
+
+/// Returns a copy of `container` with `value` stored at `index`
+fn with_index_set(
+    container: &dyn Object,
+    index: &dyn Object,
+    value: Box<dyn Object>,
+) -> Result<Box<dyn Object>, Box<dyn Object>> {
+    if let Some(array) = container.as_any().downcast_ref::<Array>() {
+        let idx = match index.as_any().downcast_ref::<Integer>() {
+            Some(idx) => idx.value,
+            None => {
+                return Err(new_error(&format!(
+                    "array index must be INTEGER, got {}",
+                    index.type_()
+                )))
+            }
+        };
+        if idx < 0 || idx as usize >= array.elements.len() {
+            return Err(new_error(&format!("index out of range: {}", idx)));
         }
 
-        return apply_function(function, args);
+        let mut elements = array.elements.clone();
+        elements[idx as usize] = value;
+        return Ok(Box::new(Array::new(elements)));
     }
 
-    Box::new(null_obj().clone())
+    if let Some(hash) = container.as_any().downcast_ref::<Hash>() {
+        let key = match object_to_hash_key(index) {
+            Some(key) => key,
+            None => {
+                return Err(new_error(&format!(
+                    "unusable as hash key: {}",
+                    index.type_()
+                )))
+            }
+        };
+
+        let mut pairs = hash.pairs.clone();
+        pairs.insert(key, value);
+        return Ok(Box::new(Hash::new(pairs)));
+    }
+
+    Err(new_error(&format!(
+        "index assignment not supported: {}",
+        container.type_()
+    )))
 }
 
-fn eval_expressions(exps: &[Box<dyn Expression>], env: &mut Environment) -> Vec<Box<dyn Object>> {
-    let mut result = Vec::new();
+/// Binds each name in `pattern` to the array element at its position, or to
+/// null once the array runs out. Nothing is bound if the value isn't an
+/// array or a name is const.
+fn bind_array_pattern(
+    pattern: &ast::ArrayDestructurePattern,
+    value: Box<dyn Object>,
+    env: &mut Environment,
+) -> Box<dyn Object> {
+    let Some(array) = value.as_any().downcast_ref::<Array>() else {
+        return new_error(&format!("cannot destructure {} as an array", value.type_()));
+    };
+
+    let mut names = pattern.elements.iter().flatten();
+    if let Some(name) = names.find(|name| env.is_const(&name.value)) {
+        return const_error(&name.value);
+    }
 
-    for exp in exps {
-        let evaluated = eval_expression(exp.as_ref(), env);
-        if is_error(&*evaluated) {
-            return vec![evaluated];
+    for (i, name) in pattern.elements.iter().enumerate() {
+        if let Some(name) = name {
+            let element = match array.elements.get(i) {
+                Some(element) => element.clone(),
+                None => Box::new(null_obj().clone()),
+            };
+            env.set(name.value.clone(), element);
         }
-        result.push(evaluated);
     }
 
-    result
+    value
 }
 
-fn apply_function(func: Box<dyn Object>, args: Vec<Box<dyn Object>>) -> Box<dyn Object> {
-    match func.type_() {
-        ObjectType::Function => {
-            let function = func.as_any().downcast_ref::<Function>().unwrap();
-            let mut extended_env = Environment::new_enclosed(Rc::clone(&function.env));
+/// Binds each name in `pattern` to the value stored under its key, or to
+/// null if the hash has no such key. Nothing is bound if the value isn't a
+/// hash or a name is const.
+fn bind_hash_pattern(
+    pattern: &ast::HashDestructurePattern,
+    value: Box<dyn Object>,
+    env: &mut Environment,
+) -> Box<dyn Object> {
+    let Some(hash) = value.as_any().downcast_ref::<Hash>() else {
+        return new_error(&format!("cannot destructure {} as a hash", value.type_()));
+    };
 
-            for (param_idx, param) in function.parameters.iter().enumerate() {
-                if param_idx < args.len() {
-                    extended_env.set(param.value.clone(), args[param_idx].clone());
-                }
-            }
+    let mut names = pattern.pairs.iter().map(|(_, name)| name);
+    if let Some(name) = names.find(|name| env.is_const(&name.value)) {
+        return const_error(&name.value);
+    }
 
-            let evaluated = eval_block_statement(&function.body, &mut extended_env);
-            unwrap_return_value(evaluated)
-        }
-        ObjectType::Builtin => {
-            let builtin = func.as_any().downcast_ref::<Builtin>().unwrap();
-            (builtin.func)(args)
+    for (key, name) in &pattern.pairs {
+        let element = match hash.pairs.get(&HashKey::String(key.value.clone())) {
+            Some(element) => element.clone(),
+            None => Box::new(null_obj().clone()),
+        };
+        env.set(name.value.clone(), element);
+    }
+
+    value
+}
+
+fn const_error(name: &str) -> Box<dyn Object> {
+    new_error(&format!("cannot reassign const binding '{}'", name))
+}
+
+fn eval_array_index_expression(array: Box<dyn Object>, index: Box<dyn Object>) -> Box<dyn Object> {
+    let elements = &array.as_any().downcast_ref::<Array>().unwrap().elements;
+    let idx = index.as_any().downcast_ref::<Integer>().unwrap().value;
+
+    if idx < 0 || idx as usize >= elements.len() {
+        return Box::new(null_obj().clone());
+    }
+
+    elements[idx as usize].clone()
+}
+
+fn hash_index(hash: Box<dyn Object>, key: Box<dyn Object>) -> Box<dyn Object> {
+    let hash = hash.as_any().downcast_ref::<Hash>().unwrap();
+
+    let key = match object_to_hash_key(&*key) {
+        Some(key) => key,
+        None => return new_error(&format!("unusable as hash key: {}", key.type_())),
+    };
+
+    match hash.pairs.get(&key) {
+        Some(value) => value.clone(),
+        None => Box::new(null_obj().clone()),
+    }
+}
+
+/// Whether `function` is a builtin that takes `arg` even though it is an error
+fn catches_error(function: &dyn Object, arg: &dyn Object) -> bool {
+    arg.type_() == ObjectType::Error
+        && function
+            .as_any()
+            .downcast_ref::<Builtin>()
+            .is_some_and(|builtin| builtin.catches_errors)
+}
+
+/// What a loop does after one run of its body
+enum LoopFlow {
+    /// run the next iteration
+    Next,
+    /// leave the loop
+    Break,
+    /// leave the loop, handing the body's result to whatever encloses it
+    Propagate,
+}
+
+/// Decides how the loop named `label` carries on after its body produced
+/// `result`. A labeled break or continue applies only to the loop with that
+/// label, and passes through any loops inside it.
+fn loop_flow(result: &dyn Object, label: Option<&str>) -> LoopFlow {
+    let targets_this_loop =
+        |target: &Option<String>| target.is_none() || target.as_deref() == label;
+
+    let any = result.as_any();
+    if let Some(signal) = any.downcast_ref::<Break>() {
+        return if targets_this_loop(&signal.label) {
+            LoopFlow::Break
+        } else {
+            LoopFlow::Propagate
+        };
+    }
+    if let Some(signal) = any.downcast_ref::<Continue>() {
+        return if targets_this_loop(&signal.label) {
+            LoopFlow::Next
+        } else {
+            LoopFlow::Propagate
+        };
+    }
+
+    match result.type_() {
+        ObjectType::ReturnValue | ObjectType::TailCall | ObjectType::Error | ObjectType::Exit => {
+            LoopFlow::Propagate
         }
-        _ => new_error(&format!("not a function: {}", func.type_())),
+        _ => LoopFlow::Next,
+    }
+}
+
+/// A break or continue that escaped every loop it could have applied to
+fn loop_signal_error(obj: &dyn Object) -> Option<Box<dyn Object>> {
+    let any = obj.as_any();
+    if let Some(signal) = any.downcast_ref::<Break>() {
+        return Some(match &signal.label {
+            Some(label) => new_error(&format!("break to unknown label '{}", label)),
+            None => new_error("break outside loop"),
+        });
+    }
+    if let Some(signal) = any.downcast_ref::<Continue>() {
+        return Some(match &signal.label {
+            Some(label) => new_error(&format!("continue to unknown label '{}", label)),
+            None => new_error("continue outside loop"),
+        });
+    }
+    None
+}
+
+/// A stack frame naming the function by its declared name, or else by the
+/// name it was called through
+fn call_frame(function: &Function, callee_name: Option<String>) -> String {
+    let line = function.body.token.line;
+    match function.name.clone().or(callee_name) {
+        Some(name) => format!("in function {} at line {}", name, line),
+        None => format!("in function at line {}", line),
     }
 }
 
+/// The name a call expression calls its function through, if it is a plain
+/// identifier
+fn callee_name(call: &ast::CallExpression) -> Option<String> {
+    call.function
+        .as_any()
+        .downcast_ref::<ast::Identifier>()
+        .map(|ident| ident.value.clone())
+}
+
 fn unwrap_return_value(obj: Box<dyn Object>) -> Box<dyn Object> {
     if let Some(return_value) = obj.as_any().downcast_ref::<ReturnValue>() {
         return return_value.value.clone();
@@ -243,6 +1550,18 @@ fn unwrap_return_value(obj: Box<dyn Object>) -> Box<dyn Object> {
     obj
 }
 
+fn eval_function_literal(fn_lit: &ast::FunctionLiteral, env: &Environment) -> Box<dyn Object> {
+    let parameters = fn_lit.parameters.clone();
+    let body = fn_lit.body.clone();
+    let env_rc = Rc::new(RefCell::new(env.clone()));
+    let function = Function::new(parameters, body, env_rc).with_rest(fn_lit.rest.clone());
+
+    match &fn_lit.name {
+        Some(name) => Box::new(function.with_name(name.value.clone())),
+        None => Box::new(function),
+    }
+}
+
 fn eval_identifier(node: &ast::Identifier, env: &Environment) -> Box<dyn Object> {
     if let Some(val) = env.get(&node.value) {
         return val;
@@ -257,47 +1576,20 @@ fn eval_identifier(node: &ast::Identifier, env: &Environment) -> Box<dyn Object>
     new_error(&format!("identifier not found: {}", node.value))
 }
 
-fn eval_if_expression(if_expression: &ast::IfExpression, env: &mut Environment) -> Box<dyn Object> {
-    let condition = eval_expression(if_expression.condition.as_ref(), env);
+/// Whether a condition holds. `null`, `false`, zero and empty strings,
+/// arrays and hashes are falsy and everything else is truthy; `if`, loops,
+/// `!`, `&&`, `||`, `bool` and predicate builtins all go through this.
+pub(crate) fn is_truthy(obj: Box<dyn Object>) -> bool {
+    let any = obj.as_any();
 
-    if is_error(&*condition) {
-        return condition;
-    }
-
-    if is_truthy(condition) {
-        eval_block_statement(&if_expression.consequence, env)
-    } else if let Some(alt) = &if_expression.alternative {
-        eval_block_statement(alt, env)
-    } else {
-        Box::new(null_obj().clone())
-    }
-}
-
-fn eval_block_statement(block: &BlockStatement, env: &mut Environment) -> Box<dyn Object> {
-    let mut result: Box<dyn Object> = Box::new(Null::new());
-
-    for statement in &block.statements {
-        result = eval_statement(statement.as_ref(), env);
-
-        match result.type_() {
-            ObjectType::ReturnValue | ObjectType::Error => return result,
-            _ => {}
-        }
-    }
-
-    result
-}
-
-fn is_truthy(obj: Box<dyn Object>) -> bool {
     match obj.type_() {
         ObjectType::Null => false,
-        ObjectType::Boolean => {
-            if let Some(boolean) = obj.as_any().downcast_ref::<Boolean>() {
-                boolean.value
-            } else {
-                false
-            }
-        }
+        ObjectType::Boolean => any.downcast_ref::<Boolean>().unwrap().value,
+        ObjectType::Integer => any.downcast_ref::<Integer>().unwrap().value != 0,
+        ObjectType::Float => any.downcast_ref::<FloatObj>().unwrap().value != 0.0,
+        ObjectType::String => !any.downcast_ref::<StringObj>().unwrap().value.is_empty(),
+        ObjectType::Array => !any.downcast_ref::<Array>().unwrap().elements.is_empty(),
+        ObjectType::Hash => !any.downcast_ref::<Hash>().unwrap().pairs.is_empty(),
         _ => true,
     }
 }
@@ -307,7 +1599,7 @@ fn eval_infix_expression(
     left: Box<dyn Object>,
     right: Box<dyn Object>,
 ) -> Box<dyn Object> {
-    if left.type_() == ObjectType::Integer && right.type_() == ObjectType::Integer {
+    if is_numeric(&*left) && is_numeric(&*right) {
         return eval_integer_infix_expression(operator, left, right);
     }
 
@@ -315,6 +1607,14 @@ fn eval_infix_expression(
         return eval_string_infix_expression(operator, left, right);
     }
 
+    if operator == "*" {
+        match (left.type_(), right.type_()) {
+            (ObjectType::String, ObjectType::Integer) => return eval_string_repeat(left, right),
+            (ObjectType::Integer, ObjectType::String) => return eval_string_repeat(right, left),
+            _ => {}
+        }
+    }
+
     if left.type_() != right.type_() {
         return new_error(&format!(
             "type mismatch: {} {} {}",
@@ -324,15 +1624,23 @@ fn eval_infix_expression(
         ));
     }
 
+    if left.type_() == ObjectType::Array {
+        return eval_array_infix_expression(operator, left, right);
+    }
+
+    if left.type_() == ObjectType::Hash {
+        return eval_hash_infix_expression(operator, left, right);
+    }
+
     if operator == "==" {
         let left_is_true = left
             .as_any()
             .downcast_ref::<Boolean>()
-            .map_or(false, |b| b.value);
+            .is_some_and(|b| b.value);
         let right_is_true = right
             .as_any()
             .downcast_ref::<Boolean>()
-            .map_or(false, |b| b.value);
+            .is_some_and(|b| b.value);
         return native_bool_to_boolean_object(left_is_true == right_is_true);
     }
 
@@ -340,11 +1648,11 @@ fn eval_infix_expression(
         let left_is_true = left
             .as_any()
             .downcast_ref::<Boolean>()
-            .map_or(false, |b| b.value);
+            .is_some_and(|b| b.value);
         let right_is_true = right
             .as_any()
             .downcast_ref::<Boolean>()
-            .map_or(false, |b| b.value);
+            .is_some_and(|b| b.value);
         return native_bool_to_boolean_object(left_is_true != right_is_true);
     }
 
@@ -356,55 +1664,242 @@ fn eval_infix_expression(
     ))
 }
 
+/// Compares two arrays element by element for `==` and `!=`
+fn eval_array_infix_expression(
+    operator: &str,
+    left: Box<dyn Object>,
+    right: Box<dyn Object>,
+) -> Box<dyn Object> {
+    match operator {
+        "==" => native_bool_to_boolean_object(objects_equal(&*left, &*right)),
+        "!=" => native_bool_to_boolean_object(!objects_equal(&*left, &*right)),
+        _ => new_error(&format!(
+            "unknown operator: {} {} {}",
+            left.type_(),
+            operator,
+            right.type_()
+        )),
+    }
+}
+
+/// Compares two hashes by their keys and then their values for `==` and `!=`
+fn eval_hash_infix_expression(
+    operator: &str,
+    left: Box<dyn Object>,
+    right: Box<dyn Object>,
+) -> Box<dyn Object> {
+    match operator {
+        "==" => native_bool_to_boolean_object(objects_equal(&*left, &*right)),
+        "!=" => native_bool_to_boolean_object(!objects_equal(&*left, &*right)),
+        _ => new_error(&format!(
+            "unknown operator: {} {} {}",
+            left.type_(),
+            operator,
+            right.type_()
+        )),
+    }
+}
+
+/// Structural equality between two values
+///
+/// Numbers compare by value across Integer and Float, arrays and hashes
+/// compare their contents recursively, and values of different types are
+/// never equal. Functions and builtins are not comparable and are never equal.
+pub(crate) fn objects_equal(left: &dyn Object, right: &dyn Object) -> bool {
+    if is_numeric(left) && is_numeric(right) {
+        if let (Some(l), Some(r)) = (
+            left.as_any().downcast_ref::<Integer>(),
+            right.as_any().downcast_ref::<Integer>(),
+        ) {
+            return l.value == r.value;
+        }
+        return to_float(left) == to_float(right);
+    }
+
+    if left.type_() != right.type_() {
+        return false;
+    }
+
+    let (left, right) = (left.as_any(), right.as_any());
+    if let (Some(l), Some(r)) = (
+        left.downcast_ref::<StringObj>(),
+        right.downcast_ref::<StringObj>(),
+    ) {
+        return l.value == r.value;
+    }
+    if let (Some(l), Some(r)) = (
+        left.downcast_ref::<Boolean>(),
+        right.downcast_ref::<Boolean>(),
+    ) {
+        return l.value == r.value;
+    }
+    if left.is::<Null>() {
+        return true;
+    }
+    if let (Some(l), Some(r)) = (left.downcast_ref::<Array>(), right.downcast_ref::<Array>()) {
+        return l.elements.len() == r.elements.len()
+            && l.elements
+                .iter()
+                .zip(&r.elements)
+                .all(|(a, b)| objects_equal(&**a, &**b));
+    }
+    if let (Some(l), Some(r)) = (left.downcast_ref::<Hash>(), right.downcast_ref::<Hash>()) {
+        return l.pairs.len() == r.pairs.len()
+            && l.pairs.iter().all(|(key, value)| {
+                r.pairs
+                    .get(key)
+                    .is_some_and(|other| objects_equal(&**value, &**other))
+            });
+    }
+    if let (Some(l), Some(r)) = (left.downcast_ref::<Range>(), right.downcast_ref::<Range>()) {
+        return l.start == r.start && l.end == r.end && l.step == r.step;
+    }
+
+    false
+}
+
 fn eval_integer_infix_expression(
     operator: &str,
     left: Box<dyn Object>,
     right: Box<dyn Object>,
 ) -> Box<dyn Object> {
+    // Mixed integer/float arithmetic promotes the integer side
+    if left.type_() == ObjectType::Float || right.type_() == ObjectType::Float {
+        return eval_float_infix_expression(operator, to_float(&*left), to_float(&*right));
+    }
+
     let left_val = left.as_any().downcast_ref::<Integer>().unwrap().value;
     let right_val = right.as_any().downcast_ref::<Integer>().unwrap().value;
 
     match operator {
-        "+" => Box::new(Integer::new(left_val + right_val)),
-        "-" => Box::new(Integer::new(left_val - right_val)),
-        "*" => Box::new(Integer::new(left_val * right_val)),
-        "/" => Box::new(Integer::new(left_val / right_val)),
+        "+" => checked_integer(left_val.checked_add(right_val)),
+        "-" => checked_integer(left_val.checked_sub(right_val)),
+        "*" => checked_integer(left_val.checked_mul(right_val)),
+        "/" | "%" if right_val == 0 => new_error("division by zero"),
+        "/" => checked_integer(left_val.checked_div(right_val)),
+        "%" => eval_modulo(left_val, right_val),
+        "**" => eval_power(left_val, right_val),
         "<" => native_bool_to_boolean_object(left_val < right_val),
         ">" => native_bool_to_boolean_object(left_val > right_val),
+        "<=" => native_bool_to_boolean_object(left_val <= right_val),
+        ">=" => native_bool_to_boolean_object(left_val >= right_val),
         "==" => native_bool_to_boolean_object(left_val == right_val),
         "!=" => native_bool_to_boolean_object(left_val != right_val),
         _ => Box::new(null_obj().clone()),
     }
 }
 
+/// Integer modulo. The result is Euclidean, so it is never negative:
+/// `-7 % 3` is `2` rather than Rust's `-1`.
+fn eval_modulo(left_val: i64, right_val: i64) -> Box<dyn Object> {
+    checked_integer(left_val.checked_rem_euclid(right_val))
+}
+
+/// Wraps the result of checked integer arithmetic, where None means overflow
+fn checked_integer(value: Option<i64>) -> Box<dyn Object> {
+    match value {
+        Some(value) => Box::new(Integer::new(value)),
+        None => new_error("integer overflow"),
+    }
+}
+
+/// Integer exponentiation. Negative exponents would produce a fraction, so
+/// they are rejected rather than truncated.
+fn eval_power(base: i64, exponent: i64) -> Box<dyn Object> {
+    if exponent < 0 {
+        return new_error("negative exponent: integer power requires a non-negative exponent");
+    }
+
+    checked_integer(
+        u32::try_from(exponent)
+            .ok()
+            .and_then(|exp| base.checked_pow(exp)),
+    )
+}
+
+fn eval_float_infix_expression(operator: &str, left_val: f64, right_val: f64) -> Box<dyn Object> {
+    match operator {
+        "+" => Box::new(FloatObj::new(left_val + right_val)),
+        "-" => Box::new(FloatObj::new(left_val - right_val)),
+        "*" => Box::new(FloatObj::new(left_val * right_val)),
+        "/" | "%" if right_val == 0.0 => new_error("division by zero"),
+        "/" => Box::new(FloatObj::new(left_val / right_val)),
+        "**" => Box::new(FloatObj::new(left_val.powf(right_val))),
+        "%" => Box::new(FloatObj::new(left_val.rem_euclid(right_val))),
+        "<" => native_bool_to_boolean_object(left_val < right_val),
+        ">" => native_bool_to_boolean_object(left_val > right_val),
+        "<=" => native_bool_to_boolean_object(left_val <= right_val),
+        ">=" => native_bool_to_boolean_object(left_val >= right_val),
+        "==" => native_bool_to_boolean_object(left_val == right_val),
+        "!=" => native_bool_to_boolean_object(left_val != right_val),
+        _ => new_error(&format!("unknown operator: FLOAT {} FLOAT", operator)),
+    }
+}
+
+fn is_numeric(obj: &dyn Object) -> bool {
+    matches!(obj.type_(), ObjectType::Integer | ObjectType::Float)
+}
+
+/// Promotes an Integer or Float object to f64
+fn to_float(obj: &dyn Object) -> f64 {
+    if let Some(float) = obj.as_any().downcast_ref::<FloatObj>() {
+        return float.value;
+    }
+
+    obj.as_any().downcast_ref::<Integer>().unwrap().value as f64
+}
+
 fn eval_string_infix_expression(
     operator: &str,
     left: Box<dyn Object>,
     right: Box<dyn Object>,
 ) -> Box<dyn Object> {
-    if operator != "+" {
-        return new_error(&format!(
+    let left_val = &left.as_any().downcast_ref::<StringObj>().unwrap().value;
+    let right_val = &right.as_any().downcast_ref::<StringObj>().unwrap().value;
+
+    match operator {
+        "+" => Box::new(StringObj::new(format!("{}{}", left_val, right_val))),
+        "==" => native_bool_to_boolean_object(left_val == right_val),
+        "!=" => native_bool_to_boolean_object(left_val != right_val),
+        "<" => native_bool_to_boolean_object(left_val < right_val),
+        ">" => native_bool_to_boolean_object(left_val > right_val),
+        "<=" => native_bool_to_boolean_object(left_val <= right_val),
+        ">=" => native_bool_to_boolean_object(left_val >= right_val),
+        _ => new_error(&format!(
             "unknown operator: {} {} {}",
             left.type_(),
             operator,
             right.type_()
-        ));
+        )),
     }
+}
 
-    let left_val = left
-        .as_any()
-        .downcast_ref::<StringObj>()
-        .unwrap()
-        .value
-        .clone();
-    let right_val = right
-        .as_any()
-        .downcast_ref::<StringObj>()
-        .unwrap()
-        .value
-        .clone();
+/// Longest string, in bytes, that repetition may build
+const MAX_REPEAT_LEN: usize = 1 << 28;
+
+/// Repeats a string `count` times for `"ab" * 3` and `3 * "ab"`
+fn eval_string_repeat(string: Box<dyn Object>, count: Box<dyn Object>) -> Box<dyn Object> {
+    let string = &string.as_any().downcast_ref::<StringObj>().unwrap().value;
+    let count = count.as_any().downcast_ref::<Integer>().unwrap().value;
+
+    if count < 0 {
+        return new_error(&format!("negative repeat count: {}", count));
+    }
 
-    Box::new(StringObj::new(left_val + &right_val))
+    let len = usize::try_from(count)
+        .ok()
+        .and_then(|count| string.len().checked_mul(count));
+    match len {
+        Some(len) if len <= MAX_REPEAT_LEN => {
+            Box::new(StringObj::new(string.repeat(count as usize)))
+        }
+        _ => new_error(&format!(
+            "repeated string too long: {} bytes * {}, limit is {} bytes",
+            string.len(),
+            count,
+            MAX_REPEAT_LEN
+        )),
+    }
 }
 
 fn eval_prefix_expression(operator: &str, right: Box<dyn Object>) -> Box<dyn Object> {
@@ -415,25 +1910,22 @@ fn eval_prefix_expression(operator: &str, right: Box<dyn Object>) -> Box<dyn Obj
     }
 }
 
+/// Evaluates `!value`, which is true exactly when the value is falsy
 fn eval_bang_operator_expression(right: Box<dyn Object>) -> Box<dyn Object> {
-    if let Some(boolean) = right.as_any().downcast_ref::<Boolean>() {
-        return native_bool_to_boolean_object(!boolean.value);
-    }
-
-    if right.as_any().downcast_ref::<Null>().is_some() {
-        return native_bool_to_boolean_object(true);
-    }
-
-    native_bool_to_boolean_object(false)
+    native_bool_to_boolean_object(!is_truthy(right))
 }
 
 fn eval_minus_prefix_operator_expression(right: Box<dyn Object>) -> Box<dyn Object> {
+    if let Some(float) = right.as_any().downcast_ref::<FloatObj>() {
+        return Box::new(FloatObj::new(-float.value));
+    }
+
     if right.type_() != ObjectType::Integer {
         return new_error(&format!("unknown operator: -{}", right.type_()));
     }
 
     if let Some(integer) = right.as_any().downcast_ref::<Integer>() {
-        return Box::new(Integer::new(-integer.value));
+        return checked_integer(integer.value.checked_neg());
     }
 
     Box::new(null_obj().clone())
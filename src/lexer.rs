@@ -1,10 +1,16 @@
 use crate::token::{Token, TokenType};
 
 pub struct Lexer {
-    input: String,
+    /// The source, split into chars so identifiers can be non-ASCII
+    chars: Vec<char>,
     position: usize,
     read_position: usize,
-    ch: u8,
+    ch: char,
+    /// Line of the current character, starting at 1
+    line: usize,
+    /// Column of the current character, starting at 1
+    column: usize,
+    errors: Vec<String>,
 }
 
 impl Lexer {
@@ -14,85 +20,196 @@ impl Lexer {
     /// * `input` - Source code string to tokenize
     pub fn new(input: String) -> Self {
         let mut lexer = Lexer {
-            input,
+            chars: input.chars().collect(),
             position: 0,
             read_position: 0,
-            ch: 0,
+            ch: '\0',
+            line: 1,
+            column: 0,
+            errors: Vec::new(),
         };
         lexer.read_char();
         lexer
     }
 
+    /// Returns any errors encountered while lexing
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    /// Lexes the rest of the input, returning every token up to and
+    /// including the Eof token
+    pub fn tokenize_all(mut self) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        loop {
+            let tok = self.next_token();
+            let done = tok.token_type == TokenType::Eof;
+            tokens.push(tok);
+            if done {
+                return tokens;
+            }
+        }
+    }
+
     /// Reads the next character in the input and advances the position
     fn read_char(&mut self) {
-        if self.read_position >= self.input.len() {
-            self.ch = 0;
+        if self.ch == '\n' {
+            self.line += 1;
+            self.column = 1;
         } else {
-            self.ch = self.input.as_bytes()[self.read_position];
+            self.column += 1;
         }
+
+        self.ch = self.peek_char();
         self.position = self.read_position;
         self.read_position += 1;
     }
 
     /// Peek next char without advancing the position
-    fn peek_char(&mut self) -> u8 {
-        if self.read_position >= self.input.len() {
-            0
-        } else {
-            self.input.as_bytes()[self.read_position]
-        }
+    fn peek_char(&self) -> char {
+        self.chars.get(self.read_position).copied().unwrap_or('\0')
     }
 
-    /// Returns the next token in the input
+    /// Returns the next token in the input, stamped with its line and column
     pub fn next_token(&mut self) -> Token {
-        self.skip_whitespace();
+        loop {
+            self.skip_whitespace();
+            let (line, column) = (self.line, self.column);
 
-        let tok = match self.ch {
-            b'"' => {
-                let literal = self.read_string();
-                Token::new(TokenType::String, literal)
+            if self.ch == '/' && self.peek_char() == '/' {
+                self.skip_line_comment();
+            } else if self.ch == '/' && self.peek_char() == '*' {
+                if !self.skip_block_comment() {
+                    let mut tok = Token::new(TokenType::Illegal, String::from("/*"));
+                    tok.line = line;
+                    tok.column = column;
+                    return tok;
+                }
+            } else {
+                break;
             }
-            b'=' => {
-                if self.peek_char() == b'=' {
+        }
+
+        let (line, column) = (self.line, self.column);
+        let mut tok = self.read_token();
+        tok.line = line;
+        tok.column = column;
+        tok
+    }
+
+    /// Reads the token starting at the current character
+    fn read_token(&mut self) -> Token {
+        let tok = match self.ch {
+            '"' => match self.read_string() {
+                Some(literal) => Token::new(TokenType::String, literal),
+                None => Token::new(TokenType::Illegal, String::from("\"")),
+            },
+            '=' => {
+                if self.peek_char() == '=' {
                     let ch = self.ch;
                     self.read_char();
-                    let literal = format!("{}{}", ch as char, self.ch as char);
+                    let literal = format!("{}{}", ch, self.ch);
                     Token::new(TokenType::Eq, literal)
+                } else if self.peek_char() == '>' {
+                    self.read_char();
+                    Token::new(TokenType::Arrow, String::from("=>"))
                 } else {
                     Token::new(TokenType::Assign, String::from("="))
                 }
             }
-            b'!' => {
-                if self.peek_char() == b'=' {
+            '!' => {
+                if self.peek_char() == '=' {
                     let ch = self.ch;
                     self.read_char();
-                    let literal = format!("{}{}", ch as char, self.ch as char);
+                    let literal = format!("{}{}", ch, self.ch);
                     Token::new(TokenType::NotEq, literal)
                 } else {
                     Token::new(TokenType::Bang, String::from("!"))
                 }
             }
-            b'+' => Token::new(TokenType::Plus, String::from("+")),
-            b'-' => Token::new(TokenType::Minus, String::from("-")),
-            b'/' => Token::new(TokenType::Slash, String::from("/")),
-            b'*' => Token::new(TokenType::Asterisk, String::from("*")),
-            b'<' => Token::new(TokenType::Lt, String::from("<")),
-            b'>' => Token::new(TokenType::Gt, String::from(">")),
-            b'(' => Token::new(TokenType::Lparen, String::from("(")),
-            b')' => Token::new(TokenType::Rparen, String::from(")")),
-            b'{' => Token::new(TokenType::Lbrace, String::from("{")),
-            b'}' => Token::new(TokenType::Rbrace, String::from("}")),
-            b',' => Token::new(TokenType::Comma, String::from(",")),
-            b';' => Token::new(TokenType::Semicolon, String::from(";")),
-            0 => Token::new(TokenType::Eof, String::from("")),
+            '&' => {
+                if self.peek_char() == '&' {
+                    self.read_char();
+                    Token::new(TokenType::And, String::from("&&"))
+                } else {
+                    Token::new(TokenType::Illegal, String::from("&"))
+                }
+            }
+            '|' => {
+                if self.peek_char() == '|' {
+                    self.read_char();
+                    Token::new(TokenType::Or, String::from("||"))
+                } else {
+                    Token::new(TokenType::Illegal, String::from("|"))
+                }
+            }
+            '?' => {
+                if self.peek_char() == '?' {
+                    self.read_char();
+                    Token::new(TokenType::NullCoalesce, String::from("??"))
+                } else {
+                    Token::new(TokenType::Illegal, String::from("?"))
+                }
+            }
+            '+' => Token::new(TokenType::Plus, String::from("+")),
+            '-' => Token::new(TokenType::Minus, String::from("-")),
+            '/' => Token::new(TokenType::Slash, String::from("/")),
+            '*' => {
+                if self.peek_char() == '*' {
+                    self.read_char();
+                    Token::new(TokenType::Power, String::from("**"))
+                } else {
+                    Token::new(TokenType::Asterisk, String::from("*"))
+                }
+            }
+            '%' => Token::new(TokenType::Percent, String::from("%")),
+            '<' => {
+                if self.peek_char() == '=' {
+                    self.read_char();
+                    Token::new(TokenType::Lte, String::from("<="))
+                } else {
+                    Token::new(TokenType::Lt, String::from("<"))
+                }
+            }
+            '>' => {
+                if self.peek_char() == '=' {
+                    self.read_char();
+                    Token::new(TokenType::Gte, String::from(">="))
+                } else {
+                    Token::new(TokenType::Gt, String::from(">"))
+                }
+            }
+            '(' => Token::new(TokenType::Lparen, String::from("(")),
+            ')' => Token::new(TokenType::Rparen, String::from(")")),
+            '{' => Token::new(TokenType::Lbrace, String::from("{")),
+            '}' => Token::new(TokenType::Rbrace, String::from("}")),
+            '[' => Token::new(TokenType::Lbracket, String::from("[")),
+            ']' => Token::new(TokenType::Rbracket, String::from("]")),
+            ',' => Token::new(TokenType::Comma, String::from(",")),
+            ';' => Token::new(TokenType::Semicolon, String::from(";")),
+            ':' => Token::new(TokenType::Colon, String::from(":")),
+            '.' => {
+                if self.peek_char() == '.' && self.chars.get(self.read_position + 1) == Some(&'.') {
+                    self.read_char();
+                    self.read_char();
+                    Token::new(TokenType::Ellipsis, String::from("..."))
+                } else {
+                    Token::new(TokenType::Dot, String::from("."))
+                }
+            }
+            '\'' if is_letter(self.peek_char()) => {
+                self.read_char();
+                return Token::new(TokenType::Label, self.read_identifier());
+            }
+            '\'' => Token::new(TokenType::Illegal, String::from("'")),
+            '\0' => Token::new(TokenType::Eof, String::from("")),
             _ => {
                 if is_letter(self.ch) {
                     let literal = self.read_identifier();
                     let token_type = Token::lookup_ident(&literal);
                     return Token::new(token_type, literal);
                 } else if is_digit(self.ch) {
-                    let literal = self.read_numbers();
-                    return Token::new(TokenType::Int, literal);
+                    return self.read_number();
                 } else {
                     Token::new(TokenType::Illegal, String::from(""))
                 }
@@ -108,21 +225,97 @@ impl Lexer {
     /// Continues reading until it encounters a non-letter character
     fn read_identifier(&mut self) -> String {
         let position = self.position;
-        while self.position < self.input.len() && is_letter(self.ch) {
+        while self.position < self.chars.len() && is_letter(self.ch) {
             self.read_char()
         }
-        self.input[position..self.position].to_string()
+        self.chars[position..self.position].iter().collect()
+    }
+
+    /// Reads an integer or float literal from the input
+    ///
+    /// A `.` must be followed by at least one digit, so `3.` is Illegal.
+    /// A `.` followed by a letter is left for a method call, as in `3.abs()`
+    fn read_number(&mut self) -> Token {
+        if self.ch == '0' {
+            match self.peek_char() {
+                'x' | 'X' => return self.read_prefixed_number(16),
+                'b' | 'B' => return self.read_prefixed_number(2),
+                _ => {}
+            }
+        }
+
+        let position = self.position;
+        self.read_numbers();
+
+        if self.ch != '.' || is_letter(self.peek_char()) {
+            return Token::new(
+                TokenType::Int,
+                self.chars[position..self.position].iter().collect(),
+            );
+        }
+
+        let is_fraction = self.peek_char().is_ascii_digit();
+        self.read_char();
+        if !is_fraction {
+            return Token::new(
+                TokenType::Illegal,
+                self.chars[position..self.position].iter().collect(),
+            );
+        }
+
+        self.read_numbers();
+        Token::new(
+            TokenType::Float,
+            self.chars[position..self.position].iter().collect(),
+        )
+    }
+
+    /// Reads an integer literal with a radix prefix, `0xFF` for hexadecimal
+    /// or `0b1010` for binary, keeping the prefix in the literal for the
+    /// parser
+    ///
+    /// Digits may be separated by `_`. A prefix with no digits after it is
+    /// Illegal, as is a literal running into letters or digits outside its
+    /// radix, such as `0b12`.
+    fn read_prefixed_number(&mut self, radix: u32) -> Token {
+        let kind = if radix == 16 { "hexadecimal" } else { "binary" };
+        let (position, line, column) = (self.position, self.line, self.column);
+        self.read_char();
+        self.read_char();
+
+        let digits = self.position;
+        while self.ch.is_digit(radix) || self.ch == '_' {
+            self.read_char();
+        }
+
+        let invalid = self.ch.is_alphanumeric().then_some(self.ch);
+        while self.ch.is_alphanumeric() {
+            self.read_char();
+        }
+
+        let literal: String = self.chars[position..self.position].iter().collect();
+        let error = match invalid {
+            Some(ch) => format!("invalid digit '{}' in {} literal {}", ch, kind, literal),
+            None if self.position == digits => {
+                format!("{} literal {} has no digits", kind, literal)
+            }
+            None => return Token::new(TokenType::Int, literal),
+        };
+
+        self.errors
+            .push(format!("{} at line {}, column {}", error, line, column));
+        Token::new(TokenType::Illegal, literal)
     }
 
-    /// Reads a number from the input
+    /// Reads a run of digits from the input
     ///
     /// Continues reading until it encounters a non-digit character
     fn read_numbers(&mut self) -> String {
         let position = self.position;
-        while self.position < self.input.len() && is_digit(self.ch) {
+        while self.position < self.chars.len() && is_digit(self.ch) {
             self.read_char()
         }
-        self.input[position..self.position].to_string()
+        self.chars[position..self.position].iter().collect()
     }
 
     /// Skips whitespace characters in the input
@@ -132,25 +325,106 @@ impl Lexer {
         }
     }
 
-    /// Reads String from the input
-    fn read_string(&mut self) -> String {
-        let position = self.position + 1;
+    /// Skips a `//` comment up to the end of the line
+    fn skip_line_comment(&mut self) {
+        while self.ch != '\n' && self.ch != '\0' {
+            self.read_char();
+        }
+    }
+
+    /// Skips a `/* ... */` comment, which may contain nested block comments
+    ///
+    /// Returns false if the input ends before the comment is closed
+    fn skip_block_comment(&mut self) -> bool {
+        let mut depth = 0;
+
+        loop {
+            match (self.ch, self.peek_char()) {
+                ('\0', _) => return false,
+                ('/', '*') => {
+                    depth += 1;
+                    self.read_char();
+                    self.read_char();
+                }
+                ('*', '/') => {
+                    depth -= 1;
+                    self.read_char();
+                    self.read_char();
+                    if depth == 0 {
+                        return true;
+                    }
+                }
+                _ => self.read_char(),
+            }
+        }
+    }
+
+    /// Reads String from the input, replacing escape sequences as it goes
+    ///
+    /// Returns None if the string contains an unknown escape sequence
+    fn read_string(&mut self) -> Option<String> {
+        let mut value = String::new();
+        let mut valid = true;
 
         loop {
             self.read_char();
-            if self.ch == b'"' || self.ch == 0 {
-                break;
+            match self.ch {
+                '"' | '\0' => break,
+                '\\' => {
+                    let (line, column) = (self.line, self.column);
+                    self.read_char();
+                    match self.ch {
+                        'n' => value.push('\n'),
+                        't' => value.push('\t'),
+                        'r' => value.push('\r'),
+                        '0' => value.push('\0'),
+                        '\\' => value.push('\\'),
+                        '"' => value.push('"'),
+                        '\0' => break,
+                        ch => {
+                            self.errors.push(format!(
+                                "unknown escape sequence \\{} at line {}, column {}",
+                                ch, line, column
+                            ));
+                            valid = false;
+                        }
+                    }
+                }
+                ch => value.push(ch),
             }
         }
 
-        self.input[position..self.position].to_string()
+        if !valid {
+            return None;
+        }
+
+        Some(value)
+    }
+}
+
+/// Lexes `input` into its tokens, ending with Eof
+pub fn tokenize(input: &str) -> Vec<Token> {
+    Lexer::new(input.to_string()).tokenize_all()
+}
+
+/// Yields tokens up to, but not including, the final Eof token
+impl Iterator for Lexer {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        let tok = self.next_token();
+        if tok.token_type == TokenType::Eof {
+            None
+        } else {
+            Some(tok)
+        }
     }
 }
 
-fn is_digit(ch: u8) -> bool {
-    ch.is_ascii_digit() || ch == b'_'
+fn is_digit(ch: char) -> bool {
+    ch.is_ascii_digit() || ch == '_'
 }
 
-fn is_letter(ch: u8) -> bool {
-    ch.is_ascii_alphabetic() || ch == b'_'
+fn is_letter(ch: char) -> bool {
+    ch.is_alphabetic() || ch == '_'
 }
@@ -12,3 +12,8 @@ pub mod object;
 pub mod parser;
 pub mod repl;
 pub mod token;
+
+/// Parses source code into a Program, or returns the parser errors
+pub fn parse(input: &str) -> Result<ast::Program, Vec<String>> {
+    ast::Program::try_from(input)
+}
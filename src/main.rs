@@ -1,16 +1,41 @@
 use ruskey::repl::Repl;
-use std::io::{self};
+use std::env;
+use std::io::{self, BufReader};
+use std::process;
 
 fn main() -> io::Result<()> {
     //println!("Ruskey Console - AST Parser Mode");
     //println!("Type in commands to see their AST representation");
 
+    // `ruskey script.mk` runs the script, `ruskey` starts the REPL
+    let mut path = None;
+    let mut prompt = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--prompt" => match args.next() {
+                Some(value) => prompt = Some(value),
+                None => {
+                    eprintln!("--prompt needs a value");
+                    process::exit(2);
+                }
+            },
+            _ => path = Some(arg),
+        }
+    }
+
     let mut repl = Repl::new();
-    let stdin = io::stdin();
-    let mut handle = stdin.lock();
+    if let Some(prompt) = prompt {
+        repl.set_prompt(&prompt);
+    }
+    // a StdinLock can't move to the thread the REPL evaluates on
+    let mut handle = BufReader::new(io::stdin());
     let mut stdout = io::stdout();
 
-    repl.start(&mut handle, &mut stdout)?;
-
-    Ok(())
+    let code = match path {
+        Some(path) => repl.run_file(&path, &mut handle, &mut stdout, &mut io::stderr())?,
+        None => repl.start(&mut handle, &mut stdout)?,
+    };
+    process::exit(code as i32)
 }
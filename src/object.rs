@@ -1,39 +1,64 @@
-use crate::ast::{BlockStatement, Identifier};
+use crate::ast::{BlockStatement, Identifier, Span};
 use crate::environment::Environment;
+use crate::evaluator::Evaluator;
 use std::any::Any;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt;
 use std::rc::Rc;
 
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
 pub enum ObjectType {
     Integer,
+    Float,
     String,
     Boolean,
     Null,
     ReturnValue,
     Function,
     Error,
+    CaughtError,
     Builtin,
+    Array,
+    Hash,
+    Range,
+    Break,
+    Continue,
+    TailCall,
+    PartialFunction,
+    MemoizedFunction,
+    ComposedFunction,
+    Exit,
 }
 
 impl fmt::Display for ObjectType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             ObjectType::Integer => write!(f, "INTEGER"),
+            ObjectType::Float => write!(f, "FLOAT"),
             ObjectType::String => write!(f, "STRING"),
             ObjectType::Boolean => write!(f, "BOOLEAN"),
             ObjectType::Null => write!(f, "NULL"),
             ObjectType::Function => write!(f, "FUNCTION"),
             ObjectType::ReturnValue => write!(f, "RETURN_VALUE"),
-            ObjectType::Error => write!(f, "ERROR"),
+            ObjectType::Error | ObjectType::CaughtError => write!(f, "ERROR"),
             ObjectType::Builtin => write!(f, "BUILTIN"),
+            ObjectType::Array => write!(f, "ARRAY"),
+            ObjectType::Hash => write!(f, "HASH"),
+            ObjectType::Range => write!(f, "RANGE"),
+            ObjectType::Break => write!(f, "BREAK"),
+            ObjectType::Continue => write!(f, "CONTINUE"),
+            ObjectType::TailCall => write!(f, "TAIL_CALL"),
+            ObjectType::PartialFunction => write!(f, "PARTIAL_FUNCTION"),
+            ObjectType::MemoizedFunction => write!(f, "MEMOIZED_FUNCTION"),
+            ObjectType::ComposedFunction => write!(f, "COMPOSED_FUNCTION"),
+            ObjectType::Exit => write!(f, "EXIT"),
         }
     }
 }
 
 /// BuiltinFunction Type
-pub type BuiltinFunction = fn(args: Vec<Box<dyn Object>>) -> Box<dyn Object>;
+pub type BuiltinFunction = fn(&mut Evaluator, Vec<Box<dyn Object>>) -> Box<dyn Object>;
 
 // Trait for all object type
 pub trait Object: fmt::Debug {
@@ -45,6 +70,9 @@ pub trait Object: fmt::Debug {
 
     /// Returns self as Any for downcasting
     fn as_any(&self) -> &dyn Any;
+
+    /// For proper Clone
+    fn clone_box(&self) -> Box<dyn Object>;
 }
 
 /// Integer object
@@ -71,6 +99,40 @@ impl Object for Integer {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    fn clone_box(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
+}
+
+/// Float object
+#[derive(Debug, Clone, PartialEq)]
+pub struct FloatObj {
+    pub value: f64,
+}
+
+impl FloatObj {
+    pub fn new(value: f64) -> Self {
+        FloatObj { value }
+    }
+}
+
+impl Object for FloatObj {
+    fn type_(&self) -> ObjectType {
+        ObjectType::Float
+    }
+
+    fn inspect(&self) -> String {
+        format!("{:?}", self.value)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
 }
 
 /// String object
@@ -97,6 +159,10 @@ impl Object for StringObj {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    fn clone_box(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
 }
 
 /// Boolean object
@@ -123,6 +189,10 @@ impl Object for Boolean {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    fn clone_box(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
 }
 
 /// Null object
@@ -135,6 +205,12 @@ impl Null {
     }
 }
 
+impl Default for Null {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Object for Null {
     fn type_(&self) -> ObjectType {
         ObjectType::Null
@@ -147,10 +223,14 @@ impl Object for Null {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    fn clone_box(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
 }
 
 /// ReturnValue struct
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ReturnValue {
     pub value: Box<dyn Object>,
 }
@@ -173,12 +253,370 @@ impl Object for ReturnValue {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    fn clone_box(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
+}
+
+/// Range object, a lazy sequence of integers from `start` up to but not
+/// including `end`, advancing by `step`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Range {
+    pub start: i64,
+    pub end: i64,
+    pub step: i64,
+}
+
+impl Range {
+    pub fn new(start: i64, end: i64, step: i64) -> Self {
+        Range { start, end, step }
+    }
+
+    /// Yields the values in the range one at a time
+    pub fn values(&self) -> impl Iterator<Item = i64> {
+        let (end, step) = (self.end, self.step);
+        std::iter::successors(Some(self.start), move |&value| value.checked_add(step))
+            .take_while(move |&value| (step > 0 && value < end) || (step < 0 && value > end))
+    }
+
+    /// Number of values in the range, worked out without visiting them
+    pub fn len(&self) -> u64 {
+        let span = self.end as i128 - self.start as i128;
+        let step = self.step as i128;
+        if step == 0 || span.signum() != step.signum() {
+            return 0;
+        }
+        ((span.abs() + step.abs() - 1) / step.abs()) as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The value at `index`, worked out without visiting the values before it
+    pub fn get(&self, index: u64) -> Option<i64> {
+        if index >= self.len() {
+            return None;
+        }
+        // in range, so the value lies between start and end
+        Some((self.start as i128 + index as i128 * self.step as i128) as i64)
+    }
+}
+
+impl Object for Range {
+    fn type_(&self) -> ObjectType {
+        ObjectType::Range
+    }
+
+    fn inspect(&self) -> String {
+        format!("range({}, {}, {})", self.start, self.end, self.step)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
+}
+
+/// Break signal, propagated out of blocks up to the enclosing loop, or up to
+/// the loop with the given label
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Break {
+    pub label: Option<String>,
+}
+
+impl Break {
+    pub fn new(label: Option<String>) -> Self {
+        Break { label }
+    }
+}
+
+impl Object for Break {
+    fn type_(&self) -> ObjectType {
+        ObjectType::Break
+    }
+
+    fn inspect(&self) -> String {
+        match &self.label {
+            Some(label) => format!("break '{}", label),
+            None => "break".to_string(),
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
+}
+
+/// Continue signal, propagated out of blocks up to the enclosing loop, or up
+/// to the loop with the given label
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Continue {
+    pub label: Option<String>,
+}
+
+impl Continue {
+    pub fn new(label: Option<String>) -> Self {
+        Continue { label }
+    }
+}
+
+impl Object for Continue {
+    fn type_(&self) -> ObjectType {
+        ObjectType::Continue
+    }
+
+    fn inspect(&self) -> String {
+        match &self.label {
+            Some(label) => format!("continue '{}", label),
+            None => "continue".to_string(),
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
+}
+
+/// A call in tail position, propagated out of the function body so the
+/// caller's `apply_function` can make it without growing the stack
+#[derive(Debug, Clone)]
+pub struct TailCall {
+    pub function: Box<dyn Object>,
+    pub args: Vec<Box<dyn Object>>,
+    /// Name the function is called through, for its stack frame
+    pub name: Option<String>,
+}
+
+impl TailCall {
+    pub fn new(function: Box<dyn Object>, args: Vec<Box<dyn Object>>) -> Self {
+        TailCall {
+            function,
+            args,
+            name: None,
+        }
+    }
+
+    pub fn with_name(mut self, name: Option<String>) -> Self {
+        self.name = name;
+        self
+    }
+}
+
+impl Object for TailCall {
+    fn type_(&self) -> ObjectType {
+        ObjectType::TailCall
+    }
+
+    fn inspect(&self) -> String {
+        format!("tail call {}", self.function.inspect())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
+}
+
+/// A callable made by `partial()`, which calls `func` with `partial_args`
+/// ahead of the arguments it is given
+#[derive(Debug, Clone)]
+pub struct PartialFunction {
+    pub func: Box<dyn Object>,
+    pub partial_args: Vec<Box<dyn Object>>,
+}
+
+impl PartialFunction {
+    pub fn new(func: Box<dyn Object>, partial_args: Vec<Box<dyn Object>>) -> Self {
+        PartialFunction { func, partial_args }
+    }
+}
+
+impl Object for PartialFunction {
+    fn type_(&self) -> ObjectType {
+        ObjectType::PartialFunction
+    }
+
+    fn inspect(&self) -> String {
+        let args: Vec<String> = self.partial_args.iter().map(|a| a.inspect()).collect();
+        format!("partial({}, {})", self.func.inspect(), args.join(", "))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
+}
+
+/// A callable made by `memoize()`, which remembers what `func` returned for
+/// each list of arguments. Copies of it share one cache, so a memoized
+/// function that calls itself by name hits the cache on the way down.
+#[derive(Debug, Clone)]
+pub struct MemoizedFunction {
+    pub func: Box<dyn Object>,
+    pub cache: Rc<RefCell<MemoCache>>,
+}
+
+impl MemoizedFunction {
+    pub fn new(func: Box<dyn Object>) -> Self {
+        MemoizedFunction {
+            func,
+            cache: Rc::new(RefCell::new(MemoCache::default())),
+        }
+    }
+}
+
+/// Results remembered by a memoized function, keyed by the arguments'
+/// values so that only calls with equal arguments share a result
+#[derive(Debug, Default)]
+pub struct MemoCache {
+    /// Calls whose arguments can all be hash keys
+    keyed: HashMap<Vec<HashKey>, Box<dyn Object>>,
+    /// Every other call, found by comparing arguments with `object_eq`
+    unkeyed: Vec<CachedCall>,
+}
+
+/// The arguments of a call and what it returned
+type CachedCall = (Vec<Box<dyn Object>>, Box<dyn Object>);
+
+impl MemoCache {
+    /// The result remembered for a call with `args`
+    pub fn get(&self, args: &[Box<dyn Object>]) -> Option<Box<dyn Object>> {
+        match hash_keys(args) {
+            Some(keys) => self.keyed.get(&keys).cloned(),
+            None => self
+                .unkeyed
+                .iter()
+                .find(|(cached_args, _)| cached_args.as_slice() == args)
+                .map(|(_, result)| result.clone()),
+        }
+    }
+
+    /// Remembers `result` for a call with `args`
+    pub fn insert(&mut self, args: Vec<Box<dyn Object>>, result: Box<dyn Object>) {
+        match hash_keys(&args) {
+            Some(keys) => {
+                self.keyed.insert(keys, result);
+            }
+            None => self.unkeyed.push((args, result)),
+        }
+    }
+}
+
+/// Hash keys for every argument, or None if any of them is not hashable
+fn hash_keys(args: &[Box<dyn Object>]) -> Option<Vec<HashKey>> {
+    args.iter()
+        .map(|arg| object_to_hash_key(arg.as_ref()))
+        .collect()
+}
+
+impl Object for MemoizedFunction {
+    fn type_(&self) -> ObjectType {
+        ObjectType::MemoizedFunction
+    }
+
+    fn inspect(&self) -> String {
+        format!("memoize({})", self.func.inspect())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
+}
+
+/// A callable made by `compose()` or `pipe()`. The first of `functions` gets
+/// the call's arguments and each later one gets the previous result. With no
+/// functions it returns its first argument.
+#[derive(Debug, Clone)]
+pub struct ComposedFunction {
+    pub functions: Vec<Box<dyn Object>>,
+}
+
+impl ComposedFunction {
+    pub fn new(functions: Vec<Box<dyn Object>>) -> Self {
+        ComposedFunction { functions }
+    }
+}
+
+impl Object for ComposedFunction {
+    fn type_(&self) -> ObjectType {
+        ObjectType::ComposedFunction
+    }
+
+    fn inspect(&self) -> String {
+        "composed function".to_string()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
+}
+
+/// Raised by `exit()`. Like an error it stops evaluation and travels out to
+/// whoever is running the program, which decides what exiting means.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExitValue {
+    pub code: i64,
+}
+
+impl ExitValue {
+    pub fn new(code: i64) -> Self {
+        ExitValue { code }
+    }
+}
+
+impl Object for ExitValue {
+    fn type_(&self) -> ObjectType {
+        ObjectType::Exit
+    }
+
+    fn inspect(&self) -> String {
+        format!("exit({})", self.code)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
 }
 
 /// Function
 #[derive(Debug)]
 pub struct Function {
+    /// Set for functions declared with `fn name(...) { ... }`
+    pub name: Option<String>,
     pub parameters: Vec<Identifier>,
+    /// Receives any arguments beyond `parameters` as an array
+    pub rest: Option<Identifier>,
     pub body: BlockStatement,
     pub env: Rc<RefCell<Environment>>,
 }
@@ -190,11 +628,25 @@ impl Function {
         env: Rc<RefCell<Environment>>,
     ) -> Self {
         Function {
+            name: None,
             parameters,
+            rest: None,
             body,
             env,
         }
     }
+
+    /// Adds a rest parameter that collects the extra arguments
+    pub fn with_rest(mut self, rest: Option<Identifier>) -> Self {
+        self.rest = rest;
+        self
+    }
+
+    /// Gives the function a name, shown when it is inspected
+    pub fn with_name(mut self, name: String) -> Self {
+        self.name = Some(name);
+        self
+    }
 }
 
 impl Object for Function {
@@ -204,13 +656,21 @@ impl Object for Function {
 
     fn inspect(&self) -> String {
         let mut out = String::new();
-        let params: Vec<String> = self.parameters.iter().map(|p| p.value.clone()).collect();
+        let mut params: Vec<String> = self.parameters.iter().map(|p| p.value.clone()).collect();
+        if let Some(rest) = &self.rest {
+            params.push(format!("...{}", rest.value));
+        }
 
-        out.push_str("fn(");
+        out.push_str("fn");
+        if let Some(name) = &self.name {
+            out.push(' ');
+            out.push_str(name);
+        }
+        out.push('(');
         out.push_str(&params.join(", "));
         out.push_str(") {\n");
-        out.push_str(&format!("  {}\n", self.body.to_string()));
-        out.push_str("}");
+        out.push_str(&format!("  {}\n", self.body));
+        out.push('}');
 
         out
     }
@@ -218,12 +678,18 @@ impl Object for Function {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    fn clone_box(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
 }
 
 impl Clone for Function {
     fn clone(&self) -> Self {
         Function {
+            name: self.name.clone(),
             parameters: self.parameters.clone(),
+            rest: self.rest.clone(),
             body: self.body.clone(),
             env: Rc::clone(&self.env),
         }
@@ -234,11 +700,38 @@ impl Clone for Function {
 #[derive(Debug, Clone, PartialEq)]
 pub struct Error {
     pub message: String,
+    /// Where in the source the error was raised, if known
+    pub span: Option<Span>,
+    /// Function calls in progress when the error was raised, innermost first
+    pub stack_trace: Vec<String>,
 }
 
 impl Error {
     pub fn new(message: String) -> Self {
-        Error { message }
+        Error {
+            message,
+            span: None,
+            stack_trace: Vec::new(),
+        }
+    }
+
+    pub fn with_span(message: String, span: Span) -> Self {
+        Error {
+            message,
+            span: Some(span),
+            stack_trace: Vec::new(),
+        }
+    }
+
+    /// Sets the call frames the error was raised in
+    pub fn with_stack_trace(mut self, stack_trace: Vec<String>) -> Self {
+        self.stack_trace = stack_trace;
+        self
+    }
+
+    /// The call frames the error was raised in, innermost first
+    pub fn stack_trace(&self) -> &[String] {
+        &self.stack_trace
     }
 }
 
@@ -248,23 +741,72 @@ impl Object for Error {
     }
 
     fn inspect(&self) -> String {
-        format!("ERROR: {}", self.message)
+        match self.span {
+            Some(span) => format!("ERROR: {} at {}", self.message, span),
+            None => format!("ERROR: {}", self.message),
+        }
     }
 
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    fn clone_box(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
 }
 
-/// Builtin Function
+/// An error bound by `catch`. Unlike an Error it is an ordinary value, so it
+/// can be stored, passed to functions and returned without propagating.
 #[derive(Debug, Clone, PartialEq)]
+pub struct CaughtError {
+    pub error: Error,
+}
+
+impl CaughtError {
+    pub fn new(error: Error) -> Self {
+        CaughtError { error }
+    }
+}
+
+impl Object for CaughtError {
+    fn type_(&self) -> ObjectType {
+        ObjectType::CaughtError
+    }
+
+    fn inspect(&self) -> String {
+        self.error.inspect()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
+}
+
+/// Builtin Function
+#[derive(Debug, Clone)]
 pub struct Builtin {
     pub func: BuiltinFunction,
+    /// Receives error arguments rather than the error propagating past it
+    pub catches_errors: bool,
 }
 
 impl Builtin {
     pub fn new(func: BuiltinFunction) -> Self {
-        Builtin { func }
+        Builtin {
+            func,
+            catches_errors: false,
+        }
+    }
+
+    /// Marks the builtin as one that inspects errors, as `is_error` does
+    pub fn catching_errors(mut self) -> Self {
+        self.catches_errors = true;
+        self
     }
 }
 
@@ -280,4 +822,351 @@ impl Object for Builtin {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    fn clone_box(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
+}
+
+/// Array object
+#[derive(Debug, Clone)]
+pub struct Array {
+    pub elements: Vec<Box<dyn Object>>,
+}
+
+impl Array {
+    pub fn new(elements: Vec<Box<dyn Object>>) -> Self {
+        Array { elements }
+    }
+}
+
+impl Object for Array {
+    fn type_(&self) -> ObjectType {
+        ObjectType::Array
+    }
+
+    fn inspect(&self) -> String {
+        let elements: Vec<String> = self.elements.iter().map(|e| e.inspect()).collect();
+
+        format!("[{}]", elements.join(", "))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
+}
+
+/// Key of a Hash object. Only integers, booleans and strings can be keys.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum HashKey {
+    Integer(i64),
+    Boolean(bool),
+    String(String),
+}
+
+impl HashKey {
+    /// Rebuilds the object this key was made from
+    pub fn to_object(&self) -> Box<dyn Object> {
+        match self {
+            HashKey::Integer(value) => Box::new(Integer::new(*value)),
+            HashKey::Boolean(value) => Box::new(Boolean::new(*value)),
+            HashKey::String(value) => Box::new(StringObj::new(value.clone())),
+        }
+    }
+}
+
+impl fmt::Display for HashKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HashKey::Integer(value) => write!(f, "{}", value),
+            HashKey::Boolean(value) => write!(f, "{}", value),
+            HashKey::String(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Converts an object to a hash key, or None if its type is not hashable
+pub fn object_to_hash_key(obj: &dyn Object) -> Option<HashKey> {
+    if let Some(int) = obj.as_any().downcast_ref::<Integer>() {
+        return Some(HashKey::Integer(int.value));
+    }
+    if let Some(boolean) = obj.as_any().downcast_ref::<Boolean>() {
+        return Some(HashKey::Boolean(boolean.value));
+    }
+    obj.as_any()
+        .downcast_ref::<StringObj>()
+        .map(|string| HashKey::String(string.value.clone()))
+}
+
+/// Hash object
+#[derive(Debug, Clone)]
+pub struct Hash {
+    pub pairs: HashMap<HashKey, Box<dyn Object>>,
+}
+
+impl Hash {
+    pub fn new(pairs: HashMap<HashKey, Box<dyn Object>>) -> Self {
+        Hash { pairs }
+    }
+}
+
+impl Object for Hash {
+    fn type_(&self) -> ObjectType {
+        ObjectType::Hash
+    }
+
+    fn inspect(&self) -> String {
+        let mut pairs: Vec<String> = self
+            .pairs
+            .iter()
+            .map(|(k, v)| format!("{}: {}", k, v.inspect()))
+            .collect();
+        pairs.sort();
+
+        format!("{{{}}}", pairs.join(", "))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
+}
+
+/// Objects display as they inspect, so they can be passed straight to
+/// `write!` and `format!`
+impl fmt::Display for dyn Object {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+impl fmt::Display for Integer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+impl fmt::Display for FloatObj {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+impl fmt::Display for StringObj {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+impl fmt::Display for Boolean {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+impl fmt::Display for Null {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+impl fmt::Display for ReturnValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+impl fmt::Display for Break {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+impl fmt::Display for Continue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+impl fmt::Display for CaughtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+impl fmt::Display for TailCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+impl fmt::Display for PartialFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+impl fmt::Display for MemoizedFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+impl fmt::Display for ComposedFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+impl fmt::Display for ExitValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+impl fmt::Display for Builtin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+impl fmt::Display for Array {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+impl fmt::Display for Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inspect())
+    }
+}
+
+/// Structural equality between objects of any type
+///
+/// Unlike the language's `==`, which compares `1 == 1.0` as true, objects
+/// of different types are never equal here. Errors compare by message alone,
+/// functions are equal when they share a definition and closure environment,
+/// and builtins are never equal.
+pub fn object_eq(a: &dyn Object, b: &dyn Object) -> bool {
+    if a.type_() != b.type_() {
+        return false;
+    }
+
+    let (a, b) = (a.as_any(), b.as_any());
+    if let (Some(a), Some(b)) = (a.downcast_ref::<Integer>(), b.downcast_ref::<Integer>()) {
+        return a == b;
+    }
+    if let (Some(a), Some(b)) = (a.downcast_ref::<FloatObj>(), b.downcast_ref::<FloatObj>()) {
+        return a == b;
+    }
+    if let (Some(a), Some(b)) = (a.downcast_ref::<StringObj>(), b.downcast_ref::<StringObj>()) {
+        return a == b;
+    }
+    if let (Some(a), Some(b)) = (a.downcast_ref::<Boolean>(), b.downcast_ref::<Boolean>()) {
+        return a == b;
+    }
+    if let (Some(a), Some(b)) = (a.downcast_ref::<Range>(), b.downcast_ref::<Range>()) {
+        return a == b;
+    }
+    if let (Some(a), Some(b)) = (a.downcast_ref::<ExitValue>(), b.downcast_ref::<ExitValue>()) {
+        return a == b;
+    }
+    if let (Some(a), Some(b)) = (a.downcast_ref::<Error>(), b.downcast_ref::<Error>()) {
+        return a.message == b.message;
+    }
+    if let (Some(a), Some(b)) = (
+        a.downcast_ref::<CaughtError>(),
+        b.downcast_ref::<CaughtError>(),
+    ) {
+        return a.error.message == b.error.message;
+    }
+    if let (Some(a), Some(b)) = (
+        a.downcast_ref::<ReturnValue>(),
+        b.downcast_ref::<ReturnValue>(),
+    ) {
+        return a.value == b.value;
+    }
+    if let (Some(a), Some(b)) = (a.downcast_ref::<Array>(), b.downcast_ref::<Array>()) {
+        return a.elements == b.elements;
+    }
+    if let (Some(a), Some(b)) = (a.downcast_ref::<Hash>(), b.downcast_ref::<Hash>()) {
+        return a.pairs == b.pairs;
+    }
+    if let (Some(a), Some(b)) = (a.downcast_ref::<Function>(), b.downcast_ref::<Function>()) {
+        return a.name == b.name
+            && a.parameters == b.parameters
+            && a.rest == b.rest
+            && a.body == b.body
+            && a.env.borrow().same_scope(&b.env.borrow());
+    }
+
+    if let (Some(a), Some(b)) = (
+        a.downcast_ref::<PartialFunction>(),
+        b.downcast_ref::<PartialFunction>(),
+    ) {
+        return a.func == b.func && a.partial_args == b.partial_args;
+    }
+
+    if let (Some(a), Some(b)) = (
+        a.downcast_ref::<MemoizedFunction>(),
+        b.downcast_ref::<MemoizedFunction>(),
+    ) {
+        return Rc::ptr_eq(&a.cache, &b.cache);
+    }
+
+    if let (Some(a), Some(b)) = (
+        a.downcast_ref::<ComposedFunction>(),
+        b.downcast_ref::<ComposedFunction>(),
+    ) {
+        return a.functions == b.functions;
+    }
+
+    if let (Some(a), Some(b)) = (a.downcast_ref::<Break>(), b.downcast_ref::<Break>()) {
+        return a == b;
+    }
+    if let (Some(a), Some(b)) = (a.downcast_ref::<Continue>(), b.downcast_ref::<Continue>()) {
+        return a == b;
+    }
+
+    a.is::<Null>()
+}
+
+impl PartialEq for Box<dyn Object> {
+    fn eq(&self, other: &Box<dyn Object>) -> bool {
+        object_eq(self.as_ref(), other.as_ref())
+    }
+}
+
+/// Lets a result be compared with a concretely typed expectation, as in
+/// `assert_eq!(result, Box::new(Integer::new(5)))`
+impl<T: Object + 'static> PartialEq<Box<T>> for Box<dyn Object> {
+    fn eq(&self, other: &Box<T>) -> bool {
+        object_eq(self.as_ref(), other.as_ref())
+    }
 }
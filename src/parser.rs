@@ -4,9 +4,14 @@
 //! The parser converts tokens into an Abstract Syntax Tree (AST).
 
 use crate::ast::{
-    BlockStatement, Boolean, CallExpression, DummyExpression, Expression, ExpressionStatement,
-    FunctionLiteral, Identifier, IfExpression, InfixExpression, IntegerLiteral, LetStatement,
-    PrefixExpression, Program, ReturnStatement, Statement, StringLiteral,
+    ArrayDestructurePattern, ArrayLiteral, AssignStatement, BlockStatement, Boolean,
+    BreakStatement, CallExpression, ConstStatement, ContinueStatement, DestructureStatement,
+    DoWhileStatement, DummyExpression, Expression, ExpressionStatement, FloatLiteral, ForStatement,
+    FunctionLiteral, HashDestructurePattern, HashLiteral, Identifier, IfExpression,
+    IndexAssignExpression, IndexExpression, InfixExpression, IntegerLiteral, LabeledStatement,
+    LetStatement, LoopStatement, MatchArm, MatchExpression, MethodCallExpression, NullLiteral,
+    PrefixExpression, Program, ReturnStatement, SpreadExpression, Statement, StringLiteral,
+    TryCatchExpression, TypeofExpression, WhileLetStatement, WhileStatement, WildcardExpression,
 };
 use crate::lexer::Lexer;
 use crate::token::{Token, TokenType};
@@ -27,6 +32,10 @@ pub struct Parser {
     peek_token: Token,
     /// Collection of parsing errors
     errors: Vec<String>,
+    /// Whether the lexer reported an error while reading the current token
+    cur_token_reported: bool,
+    /// Whether the lexer reported an error while reading the next token
+    peek_token_reported: bool,
 
     /// Registry of functions for parsing prefix expressions
     prefix_parse_fns: HashMap<TokenType, PrefixParseFn>,
@@ -36,25 +45,39 @@ pub struct Parser {
 
 /// Operator precedence levels for expression parsing
 #[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
-enum Precedence {
+pub(crate) enum Precedence {
     Lowest,
+    Assign,      // =
+    LogicalOr,   // ||
+    LogicalAnd,  // &&
+    Coalesce,    // ??
     Equals,      // ==
-    LessGreater, // > or <
+    LessGreater, // > or < or >= or <=
     Sum,         // +
-    Product,     // *
+    Product,     // * / %
+    Power,       // **
     Prefix,      // -X or !X
+    Index,       // array[index]
     Call,        // myFunction(X)
 }
 
 impl Precedence {
     /// Maps token types to their precedence levels
-    fn from_token_type(token_type: &TokenType) -> Self {
+    pub(crate) fn from_token_type(token_type: &TokenType) -> Self {
         match token_type {
+            TokenType::Assign => Precedence::Assign,
+            TokenType::Or => Precedence::LogicalOr,
+            TokenType::And => Precedence::LogicalAnd,
+            TokenType::NullCoalesce => Precedence::Coalesce,
             TokenType::Eq | TokenType::NotEq => Precedence::Equals,
-            TokenType::Lt | TokenType::Gt => Precedence::LessGreater,
+            TokenType::Lt | TokenType::Gt | TokenType::Lte | TokenType::Gte => {
+                Precedence::LessGreater
+            }
             TokenType::Plus | TokenType::Minus => Precedence::Sum,
-            TokenType::Slash | TokenType::Asterisk => Precedence::Product,
-            TokenType::Lparen => Precedence::Call,
+            TokenType::Slash | TokenType::Asterisk | TokenType::Percent => Precedence::Product,
+            TokenType::Power => Precedence::Power,
+            TokenType::Lbracket => Precedence::Index,
+            TokenType::Lparen | TokenType::Dot => Precedence::Call,
             _ => Precedence::Lowest,
         }
     }
@@ -64,39 +87,63 @@ impl Parser {
     /// Creates a new Parser with registered parsing functions
     pub fn new(mut lexer: Lexer) -> Self {
         let cur_token = lexer.next_token();
+        let cur_token_reported = !lexer.errors().is_empty();
+        let reported = lexer.errors().len();
         let peek_token = lexer.next_token();
+        let peek_token_reported = lexer.errors().len() > reported;
 
+        let errors = lexer.errors().to_vec();
         let mut p = Parser {
             lexer,
             cur_token,
             peek_token,
-            errors: Vec::new(),
+            errors,
+            cur_token_reported,
+            peek_token_reported,
             prefix_parse_fns: HashMap::new(),
             infix_parse_fns: HashMap::new(),
         };
 
         // Register prefix parse functions
         p.register_prefix(TokenType::Int, Parser::parse_integer_literal);
+        p.register_prefix(TokenType::Float, Parser::parse_float_literal);
         p.register_prefix(TokenType::Bang, Parser::parse_prefix_expression);
         p.register_prefix(TokenType::Minus, Parser::parse_prefix_expression);
+        p.register_prefix(TokenType::Typeof, Parser::parse_typeof_expression);
         p.register_prefix(TokenType::True, Parser::parse_boolean);
         p.register_prefix(TokenType::False, Parser::parse_boolean);
+        p.register_prefix(TokenType::Null, Parser::parse_null);
         p.register_prefix(TokenType::Lparen, Parser::parse_grouped_expression);
         p.register_prefix(TokenType::If, Parser::parse_if_expression);
+        p.register_prefix(TokenType::Unless, Parser::parse_unless_expression);
         p.register_prefix(TokenType::Ident, Parser::parse_identifier);
         p.register_prefix(TokenType::Function, Parser::parse_function_literal);
         p.register_prefix(TokenType::String, Parser::parse_string_literal);
+        p.register_prefix(TokenType::Lbracket, Parser::parse_array_literal);
+        p.register_prefix(TokenType::Lbrace, Parser::parse_hash_literal);
+        p.register_prefix(TokenType::Match, Parser::parse_match_expression);
+        p.register_prefix(TokenType::Try, Parser::parse_try_catch_expression);
 
         // Register infix parse functions
         p.register_infix(TokenType::Plus, Parser::parse_infix_expression);
         p.register_infix(TokenType::Minus, Parser::parse_infix_expression);
         p.register_infix(TokenType::Slash, Parser::parse_infix_expression);
         p.register_infix(TokenType::Asterisk, Parser::parse_infix_expression);
+        p.register_infix(TokenType::Percent, Parser::parse_infix_expression);
+        p.register_infix(TokenType::Power, Parser::parse_infix_expression);
         p.register_infix(TokenType::Eq, Parser::parse_infix_expression);
         p.register_infix(TokenType::NotEq, Parser::parse_infix_expression);
         p.register_infix(TokenType::Lt, Parser::parse_infix_expression);
         p.register_infix(TokenType::Gt, Parser::parse_infix_expression);
+        p.register_infix(TokenType::Lte, Parser::parse_infix_expression);
+        p.register_infix(TokenType::Gte, Parser::parse_infix_expression);
+        p.register_infix(TokenType::And, Parser::parse_infix_expression);
+        p.register_infix(TokenType::Or, Parser::parse_infix_expression);
+        p.register_infix(TokenType::NullCoalesce, Parser::parse_infix_expression);
         p.register_infix(TokenType::Lparen, Parser::parse_call_expression);
+        p.register_infix(TokenType::Lbracket, Parser::parse_index_expression);
+        p.register_infix(TokenType::Dot, Parser::parse_method_call_expression);
+        p.register_infix(TokenType::Assign, Parser::parse_assign_expression);
 
         p
     }
@@ -108,8 +155,9 @@ impl Parser {
         };
 
         while self.cur_token.token_type != TokenType::Eof {
-            if let Some(stmt) = self.parse_statement() {
-                program.statements.push(stmt);
+            match self.parse_statement() {
+                Some(stmt) => program.statements.push(stmt),
+                None => self.synchronize(),
             }
             self.next_token();
         }
@@ -117,7 +165,35 @@ impl Parser {
         program
     }
 
-    /// Returns any errors encountered during parsing
+    /// Skips the rest of a statement that failed to parse, so that parsing
+    /// resumes at the next statement rather than reporting follow-on errors
+    ///
+    /// Stops on a `;` or `}`, or just before a token that starts a statement.
+    fn synchronize(&mut self) {
+        loop {
+            match self.cur_token.token_type {
+                TokenType::Semicolon | TokenType::Rbrace | TokenType::Eof => return,
+                _ => {}
+            }
+
+            match self.peek_token.token_type {
+                TokenType::Let
+                | TokenType::Const
+                | TokenType::Return
+                | TokenType::If
+                | TokenType::Unless
+                | TokenType::Function
+                | TokenType::While
+                | TokenType::Do
+                | TokenType::Loop
+                | TokenType::For
+                | TokenType::Eof => return,
+                _ => self.next_token(),
+            }
+        }
+    }
+
+    /// Returns any errors encountered during parsing, including the lexer's
     pub fn errors(&self) -> &[String] {
         &self.errors
     }
@@ -126,7 +202,21 @@ impl Parser {
     fn parse_statement(&mut self) -> Option<Box<dyn Statement>> {
         match self.cur_token.token_type {
             TokenType::Let => self.parse_let_statement(),
+            TokenType::Const => self.parse_const_statement(),
+            TokenType::Ident if self.peek_token_is(&TokenType::Assign) => {
+                self.parse_assign_statement()
+            }
             TokenType::Return => self.parse_return_statement(),
+            TokenType::While => self.parse_while_statement(),
+            TokenType::Do => self.parse_do_while_statement(),
+            TokenType::Loop => self.parse_loop_statement(),
+            TokenType::Function if self.peek_token_is(&TokenType::Ident) => {
+                self.parse_function_declaration()
+            }
+            TokenType::For => self.parse_for_statement(),
+            TokenType::Break => self.parse_break_statement(),
+            TokenType::Continue => self.parse_continue_statement(),
+            TokenType::Label => self.parse_labeled_statement(),
             _ => self.parse_expression_statement(),
         }
     }
@@ -200,6 +290,133 @@ impl Parser {
     fn parse_let_statement(&mut self) -> Option<Box<dyn Statement>> {
         let token = self.cur_token.clone();
 
+        if self.peek_token_is(&TokenType::Lbracket) {
+            self.next_token();
+            let pattern = self.parse_array_destructure_pattern()?;
+            return self.parse_destructure_statement(token, Box::new(pattern));
+        }
+
+        if self.peek_token_is(&TokenType::Lbrace) {
+            self.next_token();
+            let pattern = self.parse_hash_destructure_pattern()?;
+            return self.parse_destructure_statement(token, Box::new(pattern));
+        }
+
+        if !self.expect_peek(TokenType::Ident) {
+            return None;
+        }
+
+        let name = Identifier {
+            token: self.cur_token.clone(),
+            value: self.cur_token.literal.clone(),
+        };
+
+        if !self.expect_peek(TokenType::Assign) {
+            return None;
+        }
+
+        self.next_token();
+
+        let value = self.parse_expression(Precedence::Lowest);
+
+        if self.peek_token_is(&TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        let stmt = LetStatement { token, name, value };
+
+        Some(Box::new(stmt))
+    }
+
+    /// Parses the `[a, _, c]` pattern of `let [a, _, c] = value;`
+    fn parse_array_destructure_pattern(&mut self) -> Option<ArrayDestructurePattern> {
+        let token = self.cur_token.clone();
+        let mut elements = Vec::new();
+
+        while !self.peek_token_is(&TokenType::Rbracket) {
+            if !self.expect_peek(TokenType::Ident) {
+                return None;
+            }
+
+            let name = (self.cur_token.literal != "_").then(|| Identifier {
+                token: self.cur_token.clone(),
+                value: self.cur_token.literal.clone(),
+            });
+            elements.push(name);
+
+            if !self.peek_token_is(&TokenType::Rbracket) && !self.expect_peek(TokenType::Comma) {
+                return None;
+            }
+        }
+        self.next_token();
+
+        Some(ArrayDestructurePattern { token, elements })
+    }
+
+    /// Parses the `{x, y: b}` pattern of `let {x, y: b} = value;`
+    fn parse_hash_destructure_pattern(&mut self) -> Option<HashDestructurePattern> {
+        let token = self.cur_token.clone();
+        let mut pairs = Vec::new();
+
+        while !self.peek_token_is(&TokenType::Rbrace) {
+            if !self.expect_peek(TokenType::Ident) {
+                return None;
+            }
+            let key = Identifier {
+                token: self.cur_token.clone(),
+                value: self.cur_token.literal.clone(),
+            };
+
+            let name = if self.peek_token_is(&TokenType::Colon) {
+                self.next_token();
+                if !self.expect_peek(TokenType::Ident) {
+                    return None;
+                }
+                Identifier {
+                    token: self.cur_token.clone(),
+                    value: self.cur_token.literal.clone(),
+                }
+            } else {
+                key.clone()
+            };
+            pairs.push((key, name));
+
+            if !self.peek_token_is(&TokenType::Rbrace) && !self.expect_peek(TokenType::Comma) {
+                return None;
+            }
+        }
+        self.next_token();
+
+        Some(HashDestructurePattern { token, pairs })
+    }
+
+    /// Parses the `= value;` that follows a destructuring pattern
+    fn parse_destructure_statement(
+        &mut self,
+        token: Token,
+        pattern: Box<dyn Expression>,
+    ) -> Option<Box<dyn Statement>> {
+        if !self.expect_peek(TokenType::Assign) {
+            return None;
+        }
+
+        self.next_token();
+        let value = self.parse_expression(Precedence::Lowest)?;
+
+        if self.peek_token_is(&TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        Some(Box::new(DestructureStatement {
+            token,
+            pattern,
+            value,
+        }))
+    }
+
+    fn parse_const_statement(&mut self) -> Option<Box<dyn Statement>> {
+        let token = self.cur_token.clone();
+
         if !self.expect_peek(TokenType::Ident) {
             return None;
         }
@@ -213,39 +430,286 @@ impl Parser {
             return None;
         }
 
-        self.next_token();
+        self.next_token();
+
+        let value = self.parse_expression(Precedence::Lowest)?;
+
+        if self.peek_token_is(&TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        Some(Box::new(ConstStatement { token, name, value }))
+    }
+
+    fn parse_assign_statement(&mut self) -> Option<Box<dyn Statement>> {
+        let target = Identifier {
+            token: self.cur_token.clone(),
+            value: self.cur_token.literal.clone(),
+        };
+
+        self.next_token();
+        let token = self.cur_token.clone();
+
+        self.next_token();
+        let value = self.parse_expression(Precedence::Lowest)?;
+
+        if self.peek_token_is(&TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        Some(Box::new(AssignStatement {
+            token,
+            target,
+            value,
+        }))
+    }
+
+    fn parse_return_statement(&mut self) -> Option<Box<dyn Statement>> {
+        let token = self.cur_token.clone();
+
+        self.next_token();
+
+        let return_value = self.parse_expression(Precedence::Lowest);
+
+        if self.peek_token_is(&TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        let stmt = ReturnStatement {
+            token,
+            return_value,
+        };
+
+        Some(Box::new(stmt))
+    }
+
+    fn parse_do_while_statement(&mut self) -> Option<Box<dyn Statement>> {
+        let token = self.cur_token.clone();
+
+        if !self.expect_peek(TokenType::Lbrace) {
+            return None;
+        }
+
+        let body = self.parse_block_statement();
+
+        if !self.expect_peek(TokenType::While) || !self.expect_peek(TokenType::Lparen) {
+            return None;
+        }
+
+        self.next_token();
+        let condition = self.parse_expression(Precedence::Lowest)?;
+
+        if !self.expect_peek(TokenType::Rparen) {
+            return None;
+        }
+
+        if self.peek_token_is(&TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        Some(Box::new(DoWhileStatement {
+            token,
+            body,
+            condition,
+        }))
+    }
+
+    fn parse_loop_statement(&mut self) -> Option<Box<dyn Statement>> {
+        let token = self.cur_token.clone();
+
+        if !self.expect_peek(TokenType::Lbrace) {
+            return None;
+        }
+
+        let body = self.parse_block_statement();
+
+        if self.peek_token_is(&TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        Some(Box::new(LoopStatement { token, body }))
+    }
+
+    fn parse_while_statement(&mut self) -> Option<Box<dyn Statement>> {
+        if self.peek_token_is(&TokenType::Let) {
+            return self.parse_while_let_statement();
+        }
+
+        let token = self.cur_token.clone();
+
+        if !self.expect_peek(TokenType::Lparen) {
+            return None;
+        }
+
+        self.next_token();
+        let condition = self.parse_expression(Precedence::Lowest)?;
+
+        if !self.expect_peek(TokenType::Rparen) {
+            return None;
+        }
+
+        if !self.expect_peek(TokenType::Lbrace) {
+            return None;
+        }
+
+        let body = self.parse_block_statement();
+
+        if self.peek_token_is(&TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        Some(Box::new(WhileStatement {
+            token,
+            condition,
+            body,
+        }))
+    }
+
+    fn parse_while_let_statement(&mut self) -> Option<Box<dyn Statement>> {
+        let token = self.cur_token.clone();
+        self.next_token();
+
+        if !self.expect_peek(TokenType::Ident) {
+            return None;
+        }
+
+        let pattern = Identifier {
+            token: self.cur_token.clone(),
+            value: self.cur_token.literal.clone(),
+        };
+
+        if !self.expect_peek(TokenType::Assign) {
+            return None;
+        }
+
+        self.next_token();
+        let expression = self.parse_expression(Precedence::Lowest)?;
+
+        if !self.expect_peek(TokenType::Lbrace) {
+            return None;
+        }
+
+        let body = self.parse_block_statement();
+
+        if self.peek_token_is(&TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        Some(Box::new(WhileLetStatement {
+            token,
+            pattern,
+            expression,
+            body,
+        }))
+    }
+
+    fn parse_for_statement(&mut self) -> Option<Box<dyn Statement>> {
+        let token = self.cur_token.clone();
+
+        if !self.expect_peek(TokenType::Lparen) {
+            return None;
+        }
+
+        if !self.expect_peek(TokenType::Ident) {
+            return None;
+        }
+
+        let iterator_name = Identifier {
+            token: self.cur_token.clone(),
+            value: self.cur_token.literal.clone(),
+        };
+
+        if !self.expect_peek(TokenType::In) {
+            return None;
+        }
+
+        self.next_token();
+        let iterable = self.parse_expression(Precedence::Lowest)?;
+
+        if !self.expect_peek(TokenType::Rparen) {
+            return None;
+        }
+
+        if !self.expect_peek(TokenType::Lbrace) {
+            return None;
+        }
+
+        let body = self.parse_block_statement();
+
+        if self.peek_token_is(&TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        Some(Box::new(ForStatement {
+            token,
+            iterator_name,
+            iterable,
+            body,
+        }))
+    }
+
+    fn parse_break_statement(&mut self) -> Option<Box<dyn Statement>> {
+        let token = self.cur_token.clone();
+        let label = self.parse_optional_label();
+
+        if self.peek_token_is(&TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        Some(Box::new(BreakStatement { token, label }))
+    }
 
-        let value = self.parse_expression(Precedence::Lowest);
+    fn parse_continue_statement(&mut self) -> Option<Box<dyn Statement>> {
+        let token = self.cur_token.clone();
+        let label = self.parse_optional_label();
 
         if self.peek_token_is(&TokenType::Semicolon) {
             self.next_token();
         }
 
-        let stmt = LetStatement { token, name, value };
+        Some(Box::new(ContinueStatement { token, label }))
+    }
 
-        Some(Box::new(stmt))
+    /// Parses the label after `break` or `continue`, if there is one
+    fn parse_optional_label(&mut self) -> Option<String> {
+        if !self.peek_token_is(&TokenType::Label) {
+            return None;
+        }
+
+        self.next_token();
+        Some(self.cur_token.literal.clone())
     }
 
-    fn parse_return_statement(&mut self) -> Option<Box<dyn Statement>> {
+    /// Parses a loop prefixed with a label, as in `'outer: while (x) { ... }`
+    fn parse_labeled_statement(&mut self) -> Option<Box<dyn Statement>> {
         let token = self.cur_token.clone();
 
+        if !self.expect_peek(TokenType::Colon) {
+            return None;
+        }
         self.next_token();
 
-        let return_value = match self.parse_expression(Precedence::Lowest) {
-            Some(expr) => Some(expr),
-            None => None,
-        };
-
-        if self.peek_token_is(&TokenType::Semicolon) {
-            self.next_token();
+        match self.cur_token.token_type {
+            TokenType::While | TokenType::Do | TokenType::Loop | TokenType::For => {}
+            _ => {
+                self.errors.push(format!(
+                    "expected a loop after label '{}, got {:?} instead at line {}, column {}",
+                    token.literal,
+                    self.cur_token.token_type,
+                    self.cur_token.line,
+                    self.cur_token.column
+                ));
+                return None;
+            }
         }
 
-        let stmt = ReturnStatement {
-            token,
-            return_value,
-        };
+        let body = self.parse_statement()?;
 
-        Some(Box::new(stmt))
+        Some(Box::new(LabeledStatement {
+            label: token.literal.clone(),
+            token,
+            body,
+        }))
     }
 
     fn parse_identifier(&mut self) -> Option<Box<dyn Expression>> {
@@ -261,7 +725,15 @@ impl Parser {
             value: 0,
         };
 
-        match self.cur_token.literal.parse::<i64>() {
+        // `_` separates digits for readability, as in `1_000_000`
+        let literal = self.cur_token.literal.replace('_', "");
+        let (digits, radix) = match literal.get(..2) {
+            Some("0x" | "0X") => (&literal[2..], 16),
+            Some("0b" | "0B") => (&literal[2..], 2),
+            _ => (literal.as_str(), 10),
+        };
+
+        match i64::from_str_radix(digits, radix) {
             Ok(value) => {
                 lit.value = value;
                 Some(Box::new(lit))
@@ -274,6 +746,20 @@ impl Parser {
         }
     }
 
+    fn parse_float_literal(&mut self) -> Option<Box<dyn Expression>> {
+        match self.cur_token.literal.replace('_', "").parse::<f64>() {
+            Ok(value) => Some(Box::new(FloatLiteral {
+                token: self.cur_token.clone(),
+                value,
+            })),
+            Err(_) => {
+                let msg = format!("could not parse {} as float", self.cur_token.literal);
+                self.errors.push(msg);
+                None
+            }
+        }
+    }
+
     fn parse_string_literal(&mut self) -> Option<Box<dyn Expression>> {
         Some(Box::new(StringLiteral {
             token: self.cur_token.clone(),
@@ -299,6 +785,16 @@ impl Parser {
         }
     }
 
+    /// Parses `typeof expr`, binding as tightly as the other prefix operators
+    fn parse_typeof_expression(&mut self) -> Option<Box<dyn Expression>> {
+        let token = self.cur_token.clone();
+
+        self.next_token();
+        let expr = self.parse_expression(Precedence::Prefix)?;
+
+        Some(Box::new(TypeofExpression { token, expr }))
+    }
+
     fn parse_infix_expression(&mut self, left: Box<dyn Expression>) -> Option<Box<dyn Expression>> {
         let mut expression = InfixExpression {
             token: self.cur_token.clone(),
@@ -307,7 +803,11 @@ impl Parser {
             right: Box::new(DummyExpression),
         };
 
-        let precedence = self.cur_precedence();
+        // `**` is right-associative, so its right operand binds one level lower
+        let precedence = match self.cur_precedence() {
+            Precedence::Power => Precedence::Product,
+            precedence => precedence,
+        };
         self.next_token();
 
         match self.parse_expression(precedence) {
@@ -320,6 +820,29 @@ impl Parser {
     }
 
     fn parse_if_expression(&mut self) -> Option<Box<dyn Expression>> {
+        let if_expression = self.parse_conditional()?;
+        Some(Box::new(if_expression))
+    }
+
+    /// Parses `unless (condition) { body } else { alt }` into an if
+    /// expression with the branches swapped, so it runs `body` exactly
+    /// when `if (!condition)` would
+    fn parse_unless_expression(&mut self) -> Option<Box<dyn Expression>> {
+        let mut if_expression = self.parse_conditional()?;
+
+        let body = if_expression.consequence;
+        if_expression.consequence = if_expression.alternative.take().unwrap_or(BlockStatement {
+            token: body.token.clone(),
+            statements: Vec::new(),
+        });
+        if_expression.alternative = Some(body);
+
+        Some(Box::new(if_expression))
+    }
+
+    /// Parses the `(condition) { consequence } else { alternative }` shared
+    /// by `if` and `unless`, starting from the keyword
+    fn parse_conditional(&mut self) -> Option<IfExpression> {
         let token = self.cur_token.clone();
 
         if !self.expect_peek(TokenType::Lparen) {
@@ -353,12 +876,12 @@ impl Parser {
             None
         };
 
-        Some(Box::new(IfExpression {
+        Some(IfExpression {
             token,
             condition,
             consequence,
             alternative,
-        }))
+        })
     }
 
     fn parse_block_statement(&mut self) -> BlockStatement {
@@ -377,15 +900,51 @@ impl Parser {
         BlockStatement { token, statements }
     }
 
+    /// Parses `fn name(params) { body }` as sugar for
+    /// `let name = fn name(params) { body };`
+    fn parse_function_declaration(&mut self) -> Option<Box<dyn Statement>> {
+        let let_token = Token {
+            token_type: TokenType::Let,
+            literal: "let".to_string(),
+            ..self.cur_token.clone()
+        };
+
+        let function = self.parse_function_literal()?;
+        let name = function
+            .as_any()
+            .downcast_ref::<FunctionLiteral>()
+            .and_then(|literal| literal.name.clone())?;
+
+        if self.peek_token_is(&TokenType::Semicolon) {
+            self.next_token();
+        }
+
+        Some(Box::new(LetStatement {
+            token: let_token,
+            name,
+            value: Some(function),
+        }))
+    }
+
     fn parse_function_literal(&mut self) -> Option<Box<dyn Expression>> {
         let token = self.cur_token.clone();
 
+        let name = if self.peek_token_is(&TokenType::Ident) {
+            self.next_token();
+            Some(Identifier {
+                token: self.cur_token.clone(),
+                value: self.cur_token.literal.clone(),
+            })
+        } else {
+            None
+        };
+
         if !self.expect_peek(TokenType::Lparen) {
             // Add error, but continue parsing with defaults
             self.errors.push("Expected '(' after fn".to_string());
         }
 
-        let parameters = self.parse_function_parameters();
+        let (parameters, rest) = self.parse_function_parameters();
 
         if !self.expect_peek(TokenType::Lbrace) {
             // Add error, but continue parsing with defaults
@@ -397,44 +956,61 @@ impl Parser {
 
         Some(Box::new(FunctionLiteral {
             token,
+            name,
             parameters,
+            rest,
             body,
         }))
     }
 
-    fn parse_function_parameters(&mut self) -> Vec<Identifier> {
+    /// Parses the parameter list, returning the plain parameters and the
+    /// optional trailing `...rest` parameter
+    fn parse_function_parameters(&mut self) -> (Vec<Identifier>, Option<Identifier>) {
         let mut identifiers = Vec::new();
+        let mut rest = None;
 
         // Handle empty parameter list
         if self.peek_token_is(&TokenType::Rparen) {
             self.next_token();
-            return identifiers;
+            return (identifiers, rest);
         }
 
-        // Parse first parameter
-        self.next_token();
-        let ident = Identifier {
-            token: self.cur_token.clone(),
-            value: self.cur_token.literal.clone(),
-        };
-        identifiers.push(ident);
-
-        // Parse subsequent parameters
-        while self.peek_token_is(&TokenType::Comma) {
-            self.next_token();
+        loop {
             self.next_token();
 
-            let ident = Identifier {
-                token: self.cur_token.clone(),
-                value: self.cur_token.literal.clone(),
-            };
-            identifiers.push(ident);
+            if self.cur_token_is(TokenType::Ellipsis) {
+                let ellipsis = self.cur_token.clone();
+                if !self.expect_peek(TokenType::Ident) {
+                    return (identifiers, rest);
+                }
+                rest = Some(Identifier {
+                    token: self.cur_token.clone(),
+                    value: self.cur_token.literal.clone(),
+                });
+
+                if self.peek_token_is(&TokenType::Comma) {
+                    self.errors.push(format!(
+                        "rest parameter must be the last parameter at line {}, column {}",
+                        ellipsis.line, ellipsis.column
+                    ));
+                }
+            } else {
+                identifiers.push(Identifier {
+                    token: self.cur_token.clone(),
+                    value: self.cur_token.literal.clone(),
+                });
+            }
+
+            if !self.peek_token_is(&TokenType::Comma) {
+                break;
+            }
+            self.next_token();
         }
 
         // Try to expect the closing parenthesis, but continue even if there's an error
         self.expect_peek(TokenType::Rparen);
 
-        identifiers
+        (identifiers, rest)
     }
 
     fn parse_call_expression(
@@ -458,26 +1034,266 @@ impl Parser {
             return args;
         }
 
-        // parse first argument
+        loop {
+            self.next_token();
+            if let Some(arg) = self.parse_call_argument() {
+                args.push(arg);
+            }
+
+            if !self.peek_token_is(&TokenType::Comma) {
+                break;
+            }
+            self.next_token();
+        }
+
+        if !self.expect_peek(TokenType::Rparen) {
+            return Vec::new();
+        }
+
+        args
+    }
+
+    /// Parses one call argument, which may be spread with a leading `...`
+    fn parse_call_argument(&mut self) -> Option<Box<dyn Expression>> {
+        if !self.cur_token_is(TokenType::Ellipsis) {
+            return self.parse_expression(Precedence::Lowest);
+        }
+
+        let token = self.cur_token.clone();
+        self.next_token();
+        let value = self.parse_expression(Precedence::Lowest)?;
+
+        Some(Box::new(SpreadExpression { token, value }))
+    }
+
+    fn parse_index_expression(&mut self, left: Box<dyn Expression>) -> Option<Box<dyn Expression>> {
+        let token = self.cur_token.clone();
+
+        self.next_token();
+        let index = self.parse_expression(Precedence::Lowest)?;
+
+        if !self.expect_peek(TokenType::Rbracket) {
+            return None;
+        }
+
+        Some(Box::new(IndexExpression { token, left, index }))
+    }
+
+    /// Parses `target = value`, where the target must be an index expression
+    ///
+    /// The value is parsed at the lowest precedence so that assignment is
+    /// right associative: `a[0] = b[0] = 1` assigns 1 to both.
+    fn parse_assign_expression(
+        &mut self,
+        left: Box<dyn Expression>,
+    ) -> Option<Box<dyn Expression>> {
+        let token = self.cur_token.clone();
+
+        let target = match left.as_any().downcast_ref::<IndexExpression>() {
+            Some(target) => target.clone(),
+            None => {
+                self.errors.push(format!(
+                    "invalid assignment target {} at line {}, column {}",
+                    left, token.line, token.column
+                ));
+                return None;
+            }
+        };
+
+        self.next_token();
+        let value = self.parse_expression(Precedence::Lowest)?;
+
+        Some(Box::new(IndexAssignExpression {
+            token,
+            target,
+            value,
+        }))
+    }
+
+    fn parse_method_call_expression(
+        &mut self,
+        object: Box<dyn Expression>,
+    ) -> Option<Box<dyn Expression>> {
+        let token = self.cur_token.clone();
+
+        if !self.expect_peek(TokenType::Ident) {
+            return None;
+        }
+
+        let method = Identifier {
+            token: self.cur_token.clone(),
+            value: self.cur_token.literal.clone(),
+        };
+
+        if !self.expect_peek(TokenType::Lparen) {
+            return None;
+        }
+
+        let arguments = self.parse_expression_list(TokenType::Rparen)?;
+
+        Some(Box::new(MethodCallExpression {
+            token,
+            object,
+            method,
+            arguments,
+        }))
+    }
+
+    fn parse_array_literal(&mut self) -> Option<Box<dyn Expression>> {
+        let token = self.cur_token.clone();
+        let elements = self.parse_expression_list(TokenType::Rbracket)?;
+
+        Some(Box::new(ArrayLiteral { token, elements }))
+    }
+
+    /// Parses a hash literal. Blocks are only parsed where a statement body is
+    /// expected (after `if`, `fn`, ...), so a `{` in expression position is a hash.
+    fn parse_hash_literal(&mut self) -> Option<Box<dyn Expression>> {
+        let token = self.cur_token.clone();
+        let mut pairs = Vec::new();
+
+        while !self.peek_token_is(&TokenType::Rbrace) {
+            self.next_token();
+            let key = self.parse_expression(Precedence::Lowest)?;
+
+            if !self.expect_peek(TokenType::Colon) {
+                return None;
+            }
+
+            self.next_token();
+            let value = self.parse_expression(Precedence::Lowest)?;
+
+            pairs.push((key, value));
+
+            if !self.peek_token_is(&TokenType::Rbrace) && !self.expect_peek(TokenType::Comma) {
+                return None;
+            }
+        }
+
+        if !self.expect_peek(TokenType::Rbrace) {
+            return None;
+        }
+
+        Some(Box::new(HashLiteral { token, pairs }))
+    }
+
+    /// Parses `match (subject) { pattern => body, ... }`
+    ///
+    /// Arms are separated by commas and a trailing comma is allowed. A bare
+    /// `_` pattern becomes a wildcard.
+    fn parse_match_expression(&mut self) -> Option<Box<dyn Expression>> {
+        let token = self.cur_token.clone();
+
+        if !self.expect_peek(TokenType::Lparen) {
+            return None;
+        }
+
+        self.next_token();
+        let subject = self.parse_expression(Precedence::Lowest)?;
+
+        if !self.expect_peek(TokenType::Rparen) || !self.expect_peek(TokenType::Lbrace) {
+            return None;
+        }
+
+        let mut arms = Vec::new();
+        while !self.peek_token_is(&TokenType::Rbrace) {
+            self.next_token();
+            let pattern: Box<dyn Expression> =
+                if self.cur_token_is(TokenType::Ident) && self.cur_token.literal == "_" {
+                    Box::new(WildcardExpression {
+                        token: self.cur_token.clone(),
+                    })
+                } else {
+                    self.parse_expression(Precedence::Lowest)?
+                };
+
+            if !self.expect_peek(TokenType::Arrow) {
+                return None;
+            }
+
+            self.next_token();
+            let body = self.parse_expression(Precedence::Lowest)?;
+
+            arms.push(MatchArm { pattern, body });
+
+            if !self.peek_token_is(&TokenType::Rbrace) && !self.expect_peek(TokenType::Comma) {
+                return None;
+            }
+        }
+
+        if !self.expect_peek(TokenType::Rbrace) {
+            return None;
+        }
+
+        Some(Box::new(MatchExpression {
+            token,
+            subject,
+            arms,
+        }))
+    }
+
+    /// Parses `try { body } catch (name) { handler }`
+    fn parse_try_catch_expression(&mut self) -> Option<Box<dyn Expression>> {
+        let token = self.cur_token.clone();
+
+        if !self.expect_peek(TokenType::Lbrace) {
+            return None;
+        }
+        let try_block = self.parse_block_statement();
+
+        if !self.expect_peek(TokenType::Catch)
+            || !self.expect_peek(TokenType::Lparen)
+            || !self.expect_peek(TokenType::Ident)
+        {
+            return None;
+        }
+        let error_name = Identifier {
+            token: self.cur_token.clone(),
+            value: self.cur_token.literal.clone(),
+        };
+
+        if !self.expect_peek(TokenType::Rparen) || !self.expect_peek(TokenType::Lbrace) {
+            return None;
+        }
+        let catch_block = self.parse_block_statement();
+
+        Some(Box::new(TryCatchExpression {
+            token,
+            try_block,
+            error_name,
+            catch_block,
+        }))
+    }
+
+    /// Parses a comma separated list of expressions terminated by `end`
+    fn parse_expression_list(&mut self, end: TokenType) -> Option<Vec<Box<dyn Expression>>> {
+        let mut list = Vec::new();
+
+        if self.peek_token_is(&end) {
+            self.next_token();
+            return Some(list);
+        }
+
+        // parse first element
         self.next_token();
-        if let Some(arg) = self.parse_expression(Precedence::Lowest) {
-            args.push(arg);
+        if let Some(exp) = self.parse_expression(Precedence::Lowest) {
+            list.push(exp);
         }
 
         while self.peek_token_is(&TokenType::Comma) {
             self.next_token(); // consume comma
             self.next_token();
 
-            if let Some(arg) = self.parse_expression(Precedence::Lowest) {
-                args.push(arg);
+            if let Some(exp) = self.parse_expression(Precedence::Lowest) {
+                list.push(exp);
             }
         }
 
-        if !self.expect_peek(TokenType::Rparen) {
-            return Vec::new();
+        if !self.expect_peek(end) {
+            return None;
         }
 
-        args
+        Some(list)
     }
 
     fn register_prefix(&mut self, token_type: TokenType, function: PrefixParseFn) {
@@ -496,9 +1312,15 @@ impl Parser {
         Precedence::from_token_type(&self.cur_token.token_type)
     }
 
+    /// Advances by one token, taking on any errors the lexer hit reading it
     fn next_token(&mut self) {
         self.cur_token = self.peek_token.clone();
+        self.cur_token_reported = self.peek_token_reported;
+        let reported = self.lexer.errors().len();
         self.peek_token = self.lexer.next_token();
+        self.peek_token_reported = self.lexer.errors().len() > reported;
+        self.errors
+            .extend_from_slice(&self.lexer.errors()[reported..]);
     }
 
     fn peek_token_is(&self, token_type: &TokenType) -> bool {
@@ -512,6 +1334,12 @@ impl Parser {
         }))
     }
 
+    fn parse_null(&mut self) -> Option<Box<dyn Expression>> {
+        Some(Box::new(NullLiteral {
+            token: self.cur_token.clone(),
+        }))
+    }
+
     fn cur_token_is(&self, t: TokenType) -> bool {
         self.cur_token.token_type == t
     }
@@ -527,15 +1355,39 @@ impl Parser {
     }
 
     fn no_prefix_parse_fn_error(&mut self, token_type: &TokenType) {
-        let msg = format!("no prefix parse function for {:?} found", token_type);
+        // the lexer has already said what is wrong with an illegal token
+        if *token_type == TokenType::Illegal && self.cur_token_reported {
+            return;
+        }
+
+        let msg = format!(
+            "no prefix parse function for {:?} found at line {}, column {}",
+            token_type, self.cur_token.line, self.cur_token.column
+        );
         self.errors.push(msg);
     }
 
     fn peek_error(&mut self, t: TokenType) {
         let msg = format!(
-            "expected next token to be {:?}, got {:?} instead",
-            t, self.peek_token.token_type
+            "expected next token to be {:?}, got {:?} instead at line {}, column {}",
+            t, self.peek_token.token_type, self.peek_token.line, self.peek_token.column
         );
         self.errors.push(msg);
     }
 }
+
+/// Parses source code into a Program, or returns the lexer and parser errors
+impl TryFrom<&str> for Program {
+    type Error = Vec<String>;
+
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        let mut parser = Parser::new(Lexer::new(input.to_string()));
+        let program = parser.parse_program();
+
+        if parser.errors().is_empty() {
+            Ok(program)
+        } else {
+            Err(parser.errors().to_vec())
+        }
+    }
+}
@@ -1,56 +1,308 @@
 use crate::environment::Environment;
-use crate::evaluator::eval;
-use crate::lexer::Lexer;
+use crate::evaluator::{Evaluator, STACK_SIZE};
+use crate::lexer::{tokenize, Lexer};
+use crate::object::{Error, ExitValue};
 use crate::parser::Parser;
+use crate::token::TokenType;
+use std::fs;
 use std::io::{self, BufRead, Write};
+use std::panic;
+use std::thread;
+use std::time::Instant;
 
 const PROMPT: &str = ">> ";
+const CONTINUATION_PROMPT: &str = "... ";
 
 pub struct Repl {
     prompt: String,
+    /// Shown instead of `prompt` while an unfinished input is continued
+    continuation_prompt: String,
+    /// Inputs evaluated without errors this session, written out by `:save`
+    history: Vec<String>,
+    /// Print how long each input took to evaluate, toggled by `:timing`
+    timing_enabled: bool,
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Repl {
     pub fn new() -> Self {
+        Self::with_prompt(PROMPT)
+    }
+
+    /// Creates a Repl that shows `prompt` before each input
+    pub fn with_prompt(prompt: impl Into<String>) -> Self {
         Repl {
-            prompt: PROMPT.to_string(),
+            prompt: prompt.into(),
+            continuation_prompt: CONTINUATION_PROMPT.to_string(),
+            history: Vec::new(),
+            timing_enabled: false,
         }
     }
 
-    pub fn start<R: BufRead, W: Write>(&mut self, input: &mut R, output: &mut W) -> io::Result<()> {
+    /// Sets the prompt shown before each input
+    pub fn set_prompt(&mut self, prompt: &str) {
+        self.prompt = prompt.to_string();
+    }
+
+    /// Sets the prompt shown before each line that continues an unfinished
+    /// input
+    pub fn set_continuation_prompt(&mut self, prompt: &str) {
+        self.continuation_prompt = prompt.to_string();
+    }
+
+    /// Reads and evaluates lines until the input ends or a program calls
+    /// `exit()`, returning the exit code. Running out of input exits with 0.
+    ///
+    /// The evaluator takes over `input` and `output`, so builtins such as
+    /// `puts` and `input` share them with the prompt. It runs on a thread of
+    /// its own with room for its default depth limits.
+    pub fn start<R: BufRead + Send, W: Write + Send>(
+        &mut self,
+        input: &mut R,
+        output: &mut W,
+    ) -> io::Result<i64> {
+        with_large_stack(|| self.read_eval_loop(input, output))?
+    }
+
+    fn read_eval_loop<R: BufRead, W: Write>(
+        &mut self,
+        input: &mut R,
+        output: &mut W,
+    ) -> io::Result<i64> {
         let mut line = String::new();
-        let mut env = Environment::new();
+        let mut evaluator = Evaluator::new().with_io(input, output);
 
-        writeln!(output, "Ruskey Console")?;
-        writeln!(output, "Type command below")?;
+        writeln!(evaluator.output(), "Ruskey Console")?;
+        writeln!(evaluator.output(), "Type command below")?;
 
         loop {
-            output.write_all(self.prompt.as_bytes())?;
-            output.flush()?;
+            let prompt = if line.is_empty() {
+                &self.prompt
+            } else {
+                &self.continuation_prompt
+            };
+            evaluator.output().write_all(prompt.as_bytes())?;
+            evaluator.output().flush()?;
 
-            if input.read_line(&mut line)? == 0 {
-                return Ok(());
+            if evaluator.input().read_line(&mut line)? == 0 {
+                return Ok(0);
             }
 
-            let lexer = Lexer::new(line.clone());
-            let mut parser = Parser::new(lexer);
-            let program = parser.parse_program();
+            let exit = if line.trim_start().starts_with(':') {
+                self.run_command(line.trim(), &mut evaluator)?
+            } else if is_complete_input(&line) {
+                self.eval_input(&line, &mut evaluator)?
+            } else {
+                // keep reading lines until the brackets are closed
+                continue;
+            };
 
-            if !parser.errors().is_empty() {
-                writeln!(output, "Parser errors:")?;
-                for error in parser.errors() {
-                    writeln!(output, "\t{}", error)?;
+            if let Some(code) = exit {
+                return Ok(code);
+            }
+            line.clear(); // Reset line buffer
+        }
+    }
+
+    /// Evaluates one complete input and prints its value, or the errors it
+    /// produced. Returns the exit code if the input called `exit()`.
+    fn eval_input(&mut self, source: &str, evaluator: &mut Evaluator) -> io::Result<Option<i64>> {
+        let lexer = Lexer::new(source.to_string());
+        let mut parser = Parser::new(lexer);
+        let program = parser.parse_program();
+
+        if !parser.errors().is_empty() {
+            write_parser_errors(evaluator.output(), parser.errors())?;
+            return Ok(None);
+        }
+
+        let start = Instant::now();
+        let evaluated = evaluator.eval_program(&program);
+        let elapsed = start.elapsed();
+
+        if let Some(exit) = evaluated.as_any().downcast_ref::<ExitValue>() {
+            return Ok(Some(exit.code));
+        }
+
+        let output = evaluator.output();
+
+        if let Some(error) = evaluated.as_any().downcast_ref::<Error>() {
+            write_error(output, error)?;
+        } else {
+            self.history.push(source.trim_end().to_string());
+            if evaluated.type_() != crate::object::ObjectType::Function {
+                writeln!(output, "{}", evaluated)?;
+            }
+        }
+
+        // written as a comment so it reads apart from the value
+        if self.timing_enabled {
+            writeln!(output, "// evaluated in {:?}", elapsed)?;
+        }
+
+        Ok(None)
+    }
+
+    /// Runs the program in the file at `path` in a fresh environment and
+    /// returns its exit code: the code passed to `exit()`, 1 if the file
+    /// can't be read, doesn't parse or stops with an error, and 0 otherwise
+    ///
+    /// Unlike the interactive REPL, the value of the program is not printed,
+    /// and errors go to `errors` rather than `output`.
+    pub fn run_file<R: BufRead + Send, W: Write + Send, E: Write + Send>(
+        &mut self,
+        path: &str,
+        input: &mut R,
+        output: &mut W,
+        errors: &mut E,
+    ) -> io::Result<i64> {
+        with_large_stack(|| self.run_source(path, input, output, errors))?
+    }
+
+    fn run_source<R: BufRead, W: Write, E: Write>(
+        &mut self,
+        path: &str,
+        input: &mut R,
+        output: &mut W,
+        errors: &mut E,
+    ) -> io::Result<i64> {
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(err) => {
+                writeln!(errors, "could not read {}: {}", path, err)?;
+                return Ok(1);
+            }
+        };
+
+        let mut parser = Parser::new(Lexer::new(source));
+        let program = parser.parse_program();
+        if !parser.errors().is_empty() {
+            write_parser_errors(errors, parser.errors())?;
+            return Ok(1);
+        }
+
+        let mut evaluator = Evaluator::new().with_io(input, output);
+        let evaluated = evaluator.eval_program(&program);
+
+        if let Some(exit) = evaluated.as_any().downcast_ref::<ExitValue>() {
+            Ok(exit.code)
+        } else if let Some(error) = evaluated.as_any().downcast_ref::<Error>() {
+            evaluator.output().flush()?;
+            write_error(errors, error)?;
+            Ok(1)
+        } else {
+            Ok(0)
+        }
+    }
+
+    /// Evaluates the inputs saved in a file, as if they were typed in
+    fn load(&mut self, path: &str, evaluator: &mut Evaluator) -> io::Result<Option<i64>> {
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(err) => {
+                writeln!(evaluator.output(), "could not read {}: {}", path, err)?;
+                return Ok(None);
+            }
+        };
+
+        let mut pending = String::new();
+        for line in source.lines() {
+            pending.push_str(line);
+            pending.push('\n');
+
+            if !is_complete_input(&pending) {
+                continue;
+            }
+            if !pending.trim().is_empty() {
+                if let Some(code) = self.eval_input(&pending, evaluator)? {
+                    return Ok(Some(code));
                 }
-            } else {
-                let evaluated = eval(&program, &mut env);
+            }
+            pending.clear();
+        }
+
+        // an unfinished last input still runs, to report its parser errors
+        if pending.is_empty() {
+            Ok(None)
+        } else {
+            self.eval_input(&pending, evaluator)
+        }
+    }
 
-                if evaluated.type_() != crate::object::ObjectType::Function {
-                    writeln!(output, "{}", evaluated.inspect())?;
+    /// Runs a `:command` line, returning the exit code if it ran a program
+    /// that called `exit()`
+    ///
+    /// - `:ast <code>` prints the parsed program's syntax tree
+    /// - `:tokens <code>` prints the tokens the lexer produces, one per line
+    /// - `:save <file>` writes the inputs evaluated so far to a file
+    /// - `:load <file>` evaluates a file input by input, printing each value
+    /// - `:reset` drops every binding and the session history
+    /// - `:env` prints each bound name with its value
+    /// - `:timing on|off` turns printing evaluation times on or off
+    fn run_command(&mut self, line: &str, evaluator: &mut Evaluator) -> io::Result<Option<i64>> {
+        let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+        let args = args.trim();
+
+        match command {
+            ":save" | ":load" if args.is_empty() => {
+                writeln!(evaluator.output(), "usage: {} <file>", command)?;
+            }
+            ":save" => {
+                let mut session = self.history.join("\n");
+                session.push('\n');
+
+                if let Err(err) = fs::write(args, session) {
+                    writeln!(evaluator.output(), "could not write {}: {}", args, err)?;
+                }
+            }
+            ":load" => return self.load(args, evaluator),
+            ":reset" => {
+                evaluator.set_environment(Environment::new());
+                self.history.clear();
+                writeln!(evaluator.output(), "Environment cleared.")?;
+            }
+            ":env" => {
+                let env = evaluator.environment().clone();
+                for name in env.names() {
+                    if let Some(value) = env.get(&name) {
+                        writeln!(evaluator.output(), "{} = {}", name, value)?;
+                    }
                 }
             }
+            ":timing" => match args {
+                "on" => self.timing_enabled = true,
+                "off" => self.timing_enabled = false,
+                _ => writeln!(evaluator.output(), "usage: :timing on|off")?,
+            },
+            ":ast" => {
+                let mut parser = Parser::new(Lexer::new(args.to_string()));
+                let program = parser.parse_program();
 
-            line.clear(); // Reset line buffer
+                if !parser.errors().is_empty() {
+                    write_parser_errors(evaluator.output(), parser.errors())?;
+                } else {
+                    writeln!(evaluator.output(), "{:#?}", program)?;
+                }
+            }
+            ":tokens" => {
+                for tok in tokenize(args) {
+                    writeln!(
+                        evaluator.output(),
+                        "Type:{:?}, Literal:{}",
+                        tok.token_type,
+                        tok.literal
+                    )?;
+                }
+            }
+            _ => writeln!(evaluator.output(), "unknown command: {}", command)?,
         }
+
+        Ok(None)
     }
 
     pub fn start_lexer_mode<R: BufRead, W: Write>(
@@ -68,15 +320,8 @@ impl Repl {
                 return Ok(()); // EOF reached
             }
 
-            let mut lexer = Lexer::new(line.clone());
-
-            loop {
-                let tok = lexer.next_token();
+            for tok in tokenize(&line) {
                 writeln!(output, "Type:{:?}, Literal:{}", tok.token_type, tok.literal)?;
-
-                if tok.token_type == crate::token::TokenType::Eof {
-                    break;
-                }
             }
 
             line.clear(); // Reset line buffer
@@ -104,10 +349,7 @@ impl Repl {
             let program = parser.parse_program();
 
             if !parser.errors().is_empty() {
-                writeln!(output, "Parser errors:")?;
-                for error in parser.errors() {
-                    writeln!(output, "\t{}", error)?;
-                }
+                write_parser_errors(output, parser.errors())?;
             } else {
                 writeln!(output, "{}", program)?;
             }
@@ -116,3 +358,57 @@ impl Repl {
         }
     }
 }
+
+fn write_parser_errors(output: &mut dyn Write, errors: &[String]) -> io::Result<()> {
+    writeln!(output, "Parser errors:")?;
+    for error in errors {
+        writeln!(output, "\t{}", error)?;
+    }
+    Ok(())
+}
+
+/// Runs `f` on a thread with `STACK_SIZE` of stack and waits for its result
+fn with_large_stack<T: Send>(f: impl FnOnce() -> T + Send) -> io::Result<T> {
+    thread::scope(|scope| {
+        let handle = thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn_scoped(scope, f)?;
+        Ok(handle
+            .join()
+            .unwrap_or_else(|err| panic::resume_unwind(err)))
+    })
+}
+
+/// Writes an error followed by its stack trace, in which a run of identical
+/// frames, as deep recursion leaves, is shown once with a count
+fn write_error(output: &mut dyn Write, error: &Error) -> io::Result<()> {
+    writeln!(output, "{}", error)?;
+    for frames in error.stack_trace().chunk_by(|a, b| a == b) {
+        writeln!(output, "    {}", frames[0])?;
+        match frames.len() {
+            1 => {}
+            2 => writeln!(output, "    {}", frames[1])?,
+            n => writeln!(output, "    ... repeated {} more times", n - 1)?,
+        }
+    }
+    Ok(())
+}
+
+/// Whether `source` closes every brace, parenthesis and bracket it opens
+///
+/// The REPL keeps reading lines until this holds, so a function body or
+/// array literal can span several lines. Brackets inside strings don't count.
+/// Input with too many closing brackets is complete, and fails to parse.
+pub fn is_complete_input(source: &str) -> bool {
+    let mut depth = 0;
+
+    for tok in tokenize(source) {
+        match tok.token_type {
+            TokenType::Lbrace | TokenType::Lparen | TokenType::Lbracket => depth += 1,
+            TokenType::Rbrace | TokenType::Rparen | TokenType::Rbracket => depth -= 1,
+            _ => {}
+        }
+    }
+
+    depth <= 0
+}
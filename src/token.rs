@@ -1,4 +1,5 @@
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TokenType {
     /// Represents an invalid or unknown token
     Illegal,
@@ -7,7 +8,10 @@ pub enum TokenType {
     // Identifiers + Literals
     Ident,
     Int,
+    Float,
     String,
+    /// Loop label, written `'name`
+    Label,
 
     // Operators
     Assign,
@@ -15,40 +19,74 @@ pub enum TokenType {
     Minus,
     Bang,
     Asterisk,
+    Power,
     Slash,
+    Percent,
     Lt,
     Gt,
+    Lte,
+    Gte,
     Eq,
     NotEq,
+    And,
+    Or,
+    NullCoalesce,
 
     // Delimiters
     Comma,
     Semicolon,
+    Colon,
+    Arrow,
+    Ellipsis,
+    Dot,
     Lparen,
     Rparen,
     Lbrace,
     Rbrace,
+    Lbracket,
+    Rbracket,
 
     // Keywords
     Function,
     Let,
+    Const,
     True,
     False,
+    Null,
     If,
+    Unless,
     Else,
     Return,
+    While,
+    Do,
+    Loop,
+    For,
+    In,
+    Break,
+    Continue,
+    Typeof,
+    Match,
+    Try,
+    Catch,
 }
 
 /// Represents a token in the Monkey programming language
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Token {
     pub token_type: TokenType,
     pub literal: String,
+    /// Line the token starts on (1-based, 0 if unknown)
+    pub line: usize,
+    /// Column the token starts at (1-based, 0 if unknown)
+    pub column: usize,
 }
 
 impl Token {
     /// Creates a new token with the specified type and literal value
     ///
+    /// The position is left unknown; the lexer stamps it when producing tokens.
+    ///
     /// # Arguments
     /// * `token_type` - The type of token to create
     /// * `literal` - The literal string value of the token
@@ -56,6 +94,8 @@ impl Token {
         Token {
             token_type,
             literal,
+            line: 0,
+            column: 0,
         }
     }
 
@@ -68,11 +108,25 @@ impl Token {
         match ident {
             "fn" => TokenType::Function,
             "let" => TokenType::Let,
+            "const" => TokenType::Const,
             "true" => TokenType::True,
             "false" => TokenType::False,
+            "null" => TokenType::Null,
             "if" => TokenType::If,
+            "unless" => TokenType::Unless,
             "else" => TokenType::Else,
             "return" => TokenType::Return,
+            "while" => TokenType::While,
+            "do" => TokenType::Do,
+            "loop" => TokenType::Loop,
+            "for" => TokenType::For,
+            "in" => TokenType::In,
+            "break" => TokenType::Break,
+            "continue" => TokenType::Continue,
+            "typeof" => TokenType::Typeof,
+            "match" => TokenType::Match,
+            "try" => TokenType::Try,
+            "catch" => TokenType::Catch,
             _ => TokenType::Ident,
         }
     }
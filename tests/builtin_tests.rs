@@ -1,8 +1,11 @@
+use ruskey::ast::Program;
+use ruskey::builtins::{write_print, write_puts};
 use ruskey::environment::Environment;
-use ruskey::evaluator::eval;
+use ruskey::evaluator::{eval, Evaluator};
 use ruskey::lexer::Lexer;
-use ruskey::object::{Error, Integer, Object, ObjectType};
+use ruskey::object::{Array, Boolean, Error, ExitValue, Integer, Object, ObjectType, StringObj};
 use ruskey::parser::Parser;
+use std::io::Cursor;
 
 #[test]
 fn test_builtin_functions() {
@@ -29,6 +32,14 @@ fn test_builtin_functions() {
             input: r#"len("hello world")"#,
             expected: Expected::Int(11),
         },
+        Test {
+            input: "len([1, 2, 3])",
+            expected: Expected::Int(3),
+        },
+        Test {
+            input: "len([])",
+            expected: Expected::Int(0),
+        },
         Test {
             input: "len(1)",
             expected: Expected::Error("argument to `len` not supported, got INTEGER".to_string()),
@@ -44,7 +55,7 @@ fn test_builtin_functions() {
 
         match test.expected {
             Expected::Int(expected) => {
-                test_integer_object(&evaluated, expected);
+                test_integer_object(evaluated.as_ref(), expected);
             }
             Expected::Error(expected) => {
                 let error = evaluated
@@ -69,7 +80,7 @@ fn test_eval(input: &str) -> Box<dyn Object> {
     eval(&program, &mut env)
 }
 
-fn test_integer_object(obj: &Box<dyn Object>, expected: i64) {
+fn test_integer_object(obj: &dyn Object, expected: i64) {
     assert_eq!(obj.type_(), ObjectType::Integer, "Object is not Integer");
     let integer = obj.as_any().downcast_ref::<Integer>().unwrap();
     assert_eq!(
@@ -78,3 +89,1568 @@ fn test_integer_object(obj: &Box<dyn Object>, expected: i64) {
         integer.value, expected
     );
 }
+
+#[test]
+fn test_puts_and_print_return_null() {
+    for input in [r#"puts("hello", 1)"#, r#"print("hello", 1)"#, "puts()"] {
+        let evaluated = test_eval(input);
+        assert_eq!(
+            evaluated.type_(),
+            ObjectType::Null,
+            "{} did not return NULL",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_puts_output() {
+    let args: Vec<Box<dyn Object>> = vec![
+        Box::new(StringObj::new("hello".to_string())),
+        Box::new(Integer::new(5)),
+    ];
+
+    let mut out = Vec::new();
+    write_puts(&mut out, &args).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "hello\n5\n");
+
+    let mut out = Vec::new();
+    write_puts(&mut out, &[]).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "");
+}
+
+#[test]
+fn test_print_output() {
+    let args: Vec<Box<dyn Object>> = vec![
+        Box::new(StringObj::new("hello".to_string())),
+        Box::new(Integer::new(5)),
+    ];
+
+    let mut out = Vec::new();
+    write_print(&mut out, &args).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "hello 5");
+}
+
+#[test]
+fn test_type_builtin() {
+    let tests = vec![
+        ("type(5)", "INTEGER"),
+        ("type(2.5)", "FLOAT"),
+        (r#"type("hi")"#, "STRING"),
+        ("type(true)", "BOOLEAN"),
+        ("type(if (false) { 1 })", "NULL"),
+        ("type([1, 2])", "ARRAY"),
+        ("type({})", "HASH"),
+        ("type(fn(x) { x })", "FUNCTION"),
+        ("type(len)", "BUILTIN"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let string = evaluated
+            .as_any()
+            .downcast_ref::<StringObj>()
+            .unwrap_or_else(|| panic!("{} did not return a String", input));
+        assert_eq!(string.value, expected, "wrong type for {}", input);
+    }
+
+    let evaluated = test_eval("type(1, 2)");
+    let error = evaluated
+        .as_any()
+        .downcast_ref::<Error>()
+        .expect("Object is not Error");
+    assert_eq!(error.message, "wrong number of arguments. got=2, want=1");
+}
+
+#[test]
+fn test_conversion_builtins() {
+    let tests = vec![
+        (r#"int("42")"#, 42),
+        (r#"int("-7")"#, -7),
+        ("int(9)", 9),
+        ("int(3.9)", 3),
+        ("int(-3.9)", -3),
+        ("int(true)", 1),
+        ("int(false)", 0),
+    ];
+
+    for (input, expected) in tests {
+        test_integer_object(test_eval(input).as_ref(), expected);
+    }
+
+    let tests = vec![
+        ("str(5)", "5"),
+        (r#"str("hi")"#, "hi"),
+        ("str(true)", "true"),
+        ("str([1, 2])", "[1, 2]"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let string = evaluated
+            .as_any()
+            .downcast_ref::<StringObj>()
+            .unwrap_or_else(|| panic!("{} did not return a String", input));
+        assert_eq!(string.value, expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        ("bool(0)", false),
+        ("bool(1)", true),
+        ("bool(0.0)", false),
+        ("bool(true)", true),
+        ("bool(false)", false),
+        ("bool(if (false) { 1 })", false),
+        (r#"bool("")"#, false),
+        (r#"bool("x")"#, true),
+        ("bool([])", false),
+        ("bool({})", false),
+        ("bool([0])", true),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let boolean = evaluated
+            .as_any()
+            .downcast_ref::<Boolean>()
+            .unwrap_or_else(|| panic!("{} did not return a Boolean", input));
+        assert_eq!(boolean.value, expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (r#"int("abc")"#, r#"could not convert "abc" to INTEGER"#),
+        ("int([1])", "argument to `int` not supported, got ARRAY"),
+        (
+            "int(100000000000000000000000.0)",
+            "could not convert 1e23 to INTEGER",
+        ),
+        ("int(pow(10.0, 400))", "could not convert inf to INTEGER"),
+        ("int(pow(-1.0, 0.5))", "could not convert NaN to INTEGER"),
+        ("str(1, 2)", "wrong number of arguments. got=2, want=1"),
+        ("bool()", "wrong number of arguments. got=0, want=1"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_array_builtins() {
+    let tests = vec![
+        ("first([1, 2, 3])", "1"),
+        ("first([])", "null"),
+        ("last([1, 2, 3])", "3"),
+        ("last([])", "null"),
+        ("rest([1, 2, 3])", "[2, 3]"),
+        ("rest([1])", "[]"),
+        ("rest([])", "null"),
+        ("push([], 1)", "[1]"),
+        ("push([1, 2], [3])", "[1, 2, [3]]"),
+        ("pop([1, 2, 3])", "[1, 2]"),
+        ("pop([])", "null"),
+        // the original array is left untouched
+        ("let a = [1, 2, 3]; let b = rest(a); a", "[1, 2, 3]"),
+        ("let a = [1, 2, 3]; let b = push(a, 4); a", "[1, 2, 3]"),
+        ("let a = [1, 2, 3]; let b = pop(a); a", "[1, 2, 3]"),
+        ("let a = [1, 2, 3]; let b = push(a, 4); b", "[1, 2, 3, 4]"),
+        // ranges are read without making every value
+        ("first(range(0, 9000000000000000000))", "0"),
+        ("last(range(0, 9000000000000000000))", "8999999999999999999"),
+        ("last(range(1, 10, 4))", "9"),
+        ("last(range(10, 0, -3))", "1"),
+        ("first(range(0))", "null"),
+        ("last(range(0))", "null"),
+        (
+            "rest(range(0, 9000000000000000000))",
+            "range(1, 9000000000000000000, 1)",
+        ),
+        ("push(rest(range(5, 0, -2)), 0)", "[3, 1, 0]"),
+        ("rest(range(1))", "range(1, 1, 1)"),
+        ("len(rest(range(1)))", "0"),
+        ("rest(range(0))", "null"),
+        (
+            "last(range(-9223372036854775807 - 1, 9223372036854775807))",
+            "9223372036854775806",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        ("first(1)", "argument to `first` must be ARRAY, got INTEGER"),
+        (
+            r#"last("ab")"#,
+            "argument to `last` must be ARRAY, got STRING",
+        ),
+        ("rest([1], [2])", "wrong number of arguments. got=2, want=1"),
+        ("push([1])", "wrong number of arguments. got=1, want=2"),
+        (
+            "push(1, 1)",
+            "argument to `push` must be ARRAY, got INTEGER",
+        ),
+        ("pop()", "wrong number of arguments. got=0, want=1"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_higher_order_builtins() {
+    let tests = vec![
+        ("map([1, 2, 3], fn(x) { x * 2 })", "[2, 4, 6]"),
+        ("map([], fn(x) { x * 2 })", "[]"),
+        (r#"map(["a", "bc"], len)"#, "[1, 2]"),
+        ("let n = 10; map([1, 2], fn(x) { x + n })", "[11, 12]"),
+        ("filter([1, 2, 3, 4], fn(x) { x % 2 == 0 })", "[2, 4]"),
+        ("filter([1, 2], fn(x) { false })", "[]"),
+        ("reduce([1, 2, 3, 4], 0, fn(acc, x) { acc + x })", "10"),
+        ("reduce([], 5, fn(acc, x) { acc + x })", "5"),
+        (
+            "reduce([1, 2, 3], [], fn(acc, x) { push(acc, x * x) })",
+            "[1, 4, 9]",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (
+            "map(1, fn(x) { x })",
+            "argument to `map` must be ARRAY, got INTEGER",
+        ),
+        (
+            "map([1], 2)",
+            "argument to `map` must be FUNCTION, got INTEGER",
+        ),
+        ("filter([1])", "wrong number of arguments. got=1, want=2"),
+        (
+            "reduce([1], fn(a, b) { a }, 0)",
+            "argument to `reduce` must be FUNCTION, got INTEGER",
+        ),
+        (
+            "map([1, 2], fn(x) { x + true })",
+            "type mismatch: INTEGER + BOOLEAN",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_sort_builtin() {
+    let tests = vec![
+        ("sort([3, 1, 2])", "[1, 2, 3]"),
+        ("sort([])", "[]"),
+        (r#"sort(["b", "a", "c"])"#, "[a, b, c]"),
+        ("sort([2.5, 1, 2])", "[1, 2, 2.5]"),
+        ("sort([3, 1, 2], fn(a, b) { b - a })", "[3, 2, 1]"),
+        (
+            r#"sort(["ccc", "a", "bb"], fn(a, b) { len(a) - len(b) })"#,
+            "[a, bb, ccc]",
+        ),
+        ("let a = [3, 1, 2]; let b = sort(a); a", "[3, 1, 2]"),
+        // equal elements keep their order
+        (
+            r#"sort(["bb", "a", "cc", "d"], fn(a, b) { len(a) - len(b) })"#,
+            "[a, d, bb, cc]",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        ("sort([true, false])", "cannot sort values of type BOOLEAN"),
+        ("sort(1)", "argument to `sort` must be ARRAY, got INTEGER"),
+        (
+            "sort([1, 2], 3)",
+            "argument to `sort` must be FUNCTION, got INTEGER",
+        ),
+        (
+            "sort([1, 2], fn(a, b) { true })",
+            "comparator for `sort` must return INTEGER, got BOOLEAN",
+        ),
+        ("sort()", "wrong number of arguments. got=0, want=1 or 2"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+
+    // a comparator that is not a consistent order still gives back every element
+    let evaluated = test_eval(
+        "let xs = map(range(50), fn(x) { (x * 7919) % 1009 }); \
+         let ys = sort(xs, fn(a, b) { ((a * 31 + b * 17) % 3) - 1 }); \
+         [len(ys), sort(ys) == sort(xs)]",
+    );
+    assert_eq!(evaluated.inspect(), "[50, true]");
+
+    // the first failing comparison stops the sort
+    let evaluated = test_eval(
+        r#"let calls = 0;
+           let result = try {
+               sort(range(100), fn(a, b) { calls = calls + 1; if (calls == 10) { error("stop") } b - a })
+           } catch (e) { error_message(e) };
+           [result, calls]"#,
+    );
+    assert_eq!(evaluated.inspect(), "[stop, 10]");
+
+    // which element of the pair is reported first depends on the sort algorithm
+    let evaluated = test_eval(r#"sort([1, "a"])"#);
+    let error = evaluated
+        .as_any()
+        .downcast_ref::<Error>()
+        .expect("Object is not Error");
+    assert!(
+        error.message == "cannot sort mixed types: INTEGER and STRING"
+            || error.message == "cannot sort mixed types: STRING and INTEGER",
+        "wrong error message. got={}",
+        error.message
+    );
+}
+
+#[test]
+fn test_string_builtins() {
+    let tests = vec![
+        (r#"split("a,b,c", ",")"#, r#"["a", "b", "c"]"#),
+        (r#"split("abc", "")"#, r#"["a", "b", "c"]"#),
+        (r#"split("a--b", "--")"#, r#"["a", "b"]"#),
+        (r#"split("abc", ",")"#, r#"["abc"]"#),
+        (r#"join(["x", "y"], "-")"#, r#""x-y""#),
+        (r#"join([], "-")"#, r#""""#),
+        (r#"join([1, 2, 3], ", ")"#, r#""1, 2, 3""#),
+        (r#"trim("  hi  ")"#, r#""hi""#),
+        (r#"trim("\tno edges\n")"#, r#""no edges""#),
+        (r#"upper("Hello")"#, r#""HELLO""#),
+        (r#"lower("Hello")"#, r#""hello""#),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let actual = match evaluated.as_any().downcast_ref::<StringObj>() {
+            Some(string) => format!("{:?}", string.value),
+            None => {
+                let parts: Vec<String> = evaluated
+                    .as_any()
+                    .downcast_ref::<Array>()
+                    .unwrap_or_else(|| panic!("{} returned {}", input, evaluated.inspect()))
+                    .elements
+                    .iter()
+                    .map(|e| format!("{:?}", e.inspect()))
+                    .collect();
+                format!("[{}]", parts.join(", "))
+            }
+        };
+        assert_eq!(actual, expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (
+            r#"split(1, ",")"#,
+            "argument to `split` must be STRING, got INTEGER",
+        ),
+        (
+            r#"split("a", 1)"#,
+            "argument to `split` must be STRING, got INTEGER",
+        ),
+        (r#"split("a")"#, "wrong number of arguments. got=1, want=2"),
+        (
+            r#"join("ab", "-")"#,
+            "argument to `join` must be ARRAY, got STRING",
+        ),
+        (
+            "join([1], 2)",
+            "argument to `join` must be STRING, got INTEGER",
+        ),
+        ("trim(1)", "argument to `trim` must be STRING, got INTEGER"),
+        (
+            r#"upper("a", "b")"#,
+            "wrong number of arguments. got=2, want=1",
+        ),
+        ("lower([])", "argument to `lower` must be STRING, got ARRAY"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_string_search_builtins() {
+    let tests = vec![
+        (r#"contains("hello world", "world")"#, true),
+        (r#"contains("hello world", "moon")"#, false),
+        (r#"contains("abc", "")"#, true),
+        ("contains([1, 2, 3], 2)", true),
+        ("contains([1, 2, 3], 4)", false),
+        (r#"contains([1, 2, 3], "1")"#, false),
+        (r#"contains(["a", "b"], "b")"#, true),
+        (r#"contains([[1]], ["1"])"#, false),
+        ("contains([[1]], [1])", true),
+        ("contains([1.0], 1)", true),
+        ("contains([2], 2.0)", true),
+        (r#"contains([{"a": [1]}], {"a": [1]})"#, true),
+        (r#"starts_with("hello", "he")"#, true),
+        (r#"starts_with("hello", "lo")"#, false),
+        (r#"ends_with("hello", "lo")"#, true),
+        (r#"ends_with("hello", "he")"#, false),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let boolean = evaluated
+            .as_any()
+            .downcast_ref::<Boolean>()
+            .unwrap_or_else(|| panic!("{} did not return a Boolean", input));
+        assert_eq!(boolean.value, expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (r#"replace("aaa", "a", "b")"#, "bbb"),
+        (r#"replace("hello world", "world", "there")"#, "hello there"),
+        (r#"replace("abc", "x", "y")"#, "abc"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let string = evaluated
+            .as_any()
+            .downcast_ref::<StringObj>()
+            .unwrap_or_else(|| panic!("{} did not return a String", input));
+        assert_eq!(string.value, expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (
+            r#"contains(1, "a")"#,
+            "argument to `contains` must be STRING, got INTEGER",
+        ),
+        (
+            r#"starts_with("a")"#,
+            "wrong number of arguments. got=1, want=2",
+        ),
+        (
+            r#"ends_with("a", 1)"#,
+            "argument to `ends_with` must be STRING, got INTEGER",
+        ),
+        (
+            r#"replace("a", "b")"#,
+            "wrong number of arguments. got=2, want=3",
+        ),
+        (
+            r#"replace("a", "b", 3)"#,
+            "argument to `replace` must be STRING, got INTEGER",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_math_builtins() {
+    let tests = vec![
+        ("abs(-5)", "5"),
+        ("abs(5)", "5"),
+        ("abs(-2.5)", "2.5"),
+        ("sqrt(4)", "2.0"),
+        ("sqrt(2.25)", "1.5"),
+        ("floor(3.7)", "3"),
+        ("floor(-3.2)", "-4"),
+        ("floor(3)", "3"),
+        ("ceil(3.2)", "4"),
+        ("ceil(-3.7)", "-3"),
+        ("floor(-9223372036854775808.0)", "-9223372036854775808"),
+        ("int(-9223372036854775808.5)", "-9223372036854775808"),
+        ("pow(2, 8)", "256"),
+        ("pow(2, 0)", "1"),
+        ("pow(2.0, 3)", "8.0"),
+        ("pow(4, 0.5)", "2.0"),
+        ("min(3, 7)", "3"),
+        ("min(7, 3)", "3"),
+        ("max(3, 7)", "7"),
+        ("min(1.5, 0.5)", "0.5"),
+        ("max(1.5, 0.5)", "1.5"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    assert_eq!(test_eval("sqrt(4)").type_(), ObjectType::Float);
+    assert_eq!(test_eval("floor(3.7)").type_(), ObjectType::Integer);
+
+    let tests = vec![
+        (
+            r#"abs("a")"#,
+            "argument to `abs` must be INTEGER or FLOAT, got STRING",
+        ),
+        ("sqrt(-1)", "argument to `sqrt` must not be negative"),
+        (
+            "floor(true)",
+            "argument to `floor` must be INTEGER or FLOAT, got BOOLEAN",
+        ),
+        ("ceil()", "wrong number of arguments. got=0, want=1"),
+        (
+            "ceil(9223372036854775807.0)",
+            "could not convert 9.223372036854776e18 to INTEGER",
+        ),
+        (
+            "floor(-pow(10.0, 400))",
+            "could not convert -inf to INTEGER",
+        ),
+        ("ceil(pow(-1.0, 0.5))", "could not convert NaN to INTEGER"),
+        (
+            "pow(2, -1)",
+            "negative exponent: integer power requires a non-negative exponent",
+        ),
+        ("pow(2, 64)", "integer overflow"),
+        ("abs(-9223372036854775807 - 1)", "integer overflow"),
+        ("pow(2)", "wrong number of arguments. got=1, want=2"),
+        (
+            "min(1, 2.0)",
+            "arguments to `min` must be the same type, got INTEGER and FLOAT",
+        ),
+        (
+            r#"max(1, "2")"#,
+            "argument to `max` must be INTEGER or FLOAT, got STRING",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_range_builtin() {
+    let tests = vec![
+        ("range(5)", "range(0, 5, 1)"),
+        ("range(1, 5)", "range(1, 5, 1)"),
+        ("range(0, 10, 2)", "range(0, 10, 2)"),
+        ("push(range(5), 5)", "[0, 1, 2, 3, 4, 5]"),
+        ("push(range(1, 5), 5)", "[1, 2, 3, 4, 5]"),
+        ("push(range(0, 10, 2), 10)", "[0, 2, 4, 6, 8, 10]"),
+        ("push(range(5, 0, -2), 0)", "[5, 3, 1, 0]"),
+        ("push(range(3, 1), 0)", "[0]"),
+        ("map(range(3), fn(x) { x * x })", "[0, 1, 4]"),
+        ("len(range(0, 10, 3))", "4"),
+        ("len(range(0))", "0"),
+        ("len(range(0, 10, 5))", "2"),
+        ("len(range(5, 0, -2))", "3"),
+        ("len(range(3, 1))", "0"),
+        ("len(range(1, 3, -1))", "0"),
+        ("len(range(9223372036854775807))", "9223372036854775807"),
+        (
+            "len(range(-9223372036854775807 - 1, 9223372036854775807, 4))",
+            "4611686018427387904",
+        ),
+        (
+            "len(range(9223372036854775807, -9223372036854775807 - 1, -3))",
+            "6148914691236517205",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    assert_eq!(test_eval("range(3)").type_(), ObjectType::Range);
+
+    let tests = vec![
+        ("range()", "wrong number of arguments. got=0, want=1 to 3"),
+        (
+            "range(1, 2, 3, 4)",
+            "wrong number of arguments. got=4, want=1 to 3",
+        ),
+        (
+            "range(1.5)",
+            "argument to `range` must be INTEGER, got FLOAT",
+        ),
+        ("range(0, 5, 0)", "range step must not be zero"),
+        (
+            "len(range(-9223372036854775807 - 1, 9223372036854775807))",
+            "integer overflow",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_assert_builtin() {
+    for input in [
+        "assert(true)",
+        "assert(1 == 1)",
+        r#"assert(5, "never shown")"#,
+    ] {
+        let evaluated = test_eval(input);
+        assert_eq!(
+            evaluated.type_(),
+            ObjectType::Null,
+            "wrong result for {}",
+            input
+        );
+    }
+
+    let tests = vec![
+        ("assert(false)", "assertion failed"),
+        ("assert(1 == 2)", "assertion failed"),
+        (r#"assert(false, "oops")"#, "assertion failed: oops"),
+        ("assert()", "wrong number of arguments. got=0, want=1 to 2"),
+        (
+            "assert(true, 1, 2)",
+            "wrong number of arguments. got=3, want=1 to 2",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+
+    // a failed assertion stops the script
+    let evaluated = test_eval("assert(false, \"stop\"); 5");
+    assert_eq!(evaluated.type_(), ObjectType::Error);
+}
+
+#[test]
+fn test_error_builtins() {
+    let tests = vec![
+        (r#"error("negative input")"#, "negative input"),
+        (
+            r#"let check = fn(x) { if (x < 0) { error("negative input") } x }; check(-1); 5"#,
+            "negative input",
+        ),
+        (r#"len(error("inner"))"#, "inner"),
+        (
+            r#"let f = fn() { error("deep"); 1 }; let g = fn() { f() + 1 }; g()"#,
+            "deep",
+        ),
+        (
+            "error(5)",
+            "argument to `error` must be STRING, got INTEGER",
+        ),
+        ("error()", "wrong number of arguments. got=0, want=1"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+
+    let tests = vec![
+        (r#"is_error(error("x"))"#, true),
+        ("is_error(1 + true)", true),
+        ("is_error(5)", false),
+        (r#"is_error("error")"#, false),
+        (
+            r#"let safe_sqrt = fn(x) { if (x < 0) { return error("negative"); } sqrt(x) }; is_error(safe_sqrt(-4))"#,
+            true,
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let boolean = evaluated
+            .as_any()
+            .downcast_ref::<Boolean>()
+            .unwrap_or_else(|| panic!("{} did not return a Boolean", input));
+        assert_eq!(boolean.value, expected, "wrong result for {}", input);
+    }
+}
+
+#[test]
+fn test_input_builtins() {
+    let stdin = Cursor::new("first line\r\nsecond line\nlast".as_bytes());
+    let mut stdout = Vec::new();
+    let mut evaluator = Evaluator::new().with_io(stdin, &mut stdout);
+
+    let tests = vec![
+        (r#"input("> ")"#, "first line"),
+        ("input()", "second line"),
+        ("readline()", "last"),
+    ];
+
+    for (input, expected) in tests {
+        let program = Program::try_from(input).unwrap();
+        let evaluated = evaluator.eval_program(&program);
+        let string = evaluated
+            .as_any()
+            .downcast_ref::<StringObj>()
+            .unwrap_or_else(|| panic!("{} did not return a String", input));
+        assert_eq!(string.value, expected);
+    }
+
+    let program = Program::try_from("readline()").unwrap();
+    assert_eq!(evaluator.eval_program(&program).type_(), ObjectType::Null);
+
+    let tests = vec![
+        (
+            "input(1)",
+            "argument to `input` must be STRING, got INTEGER",
+        ),
+        (
+            r#"input("a", "b")"#,
+            "wrong number of arguments. got=2, want=0 to 1",
+        ),
+        ("readline(1)", "wrong number of arguments. got=1, want=0"),
+    ];
+
+    for (input, expected) in tests {
+        let program = Program::try_from(input).unwrap();
+        let evaluated = evaluator.eval_program(&program);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+
+    drop(evaluator);
+    assert_eq!(String::from_utf8(stdout).unwrap(), "> ");
+}
+
+#[test]
+fn test_exit_builtin() {
+    let tests = vec![
+        ("exit()", 0),
+        ("exit(3)", 3),
+        ("exit(2); 5", 2),
+        ("let f = fn() { exit(4); 1 }; f() + 1", 4),
+        ("map([1, 2], fn(x) { if (x == 2) { exit(x) } x })", 2),
+        ("for (i in range(10)) { if (i == 5) { exit(i) } }", 5),
+        ("is_error(exit(6))", 6),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let exit = evaluated
+            .as_any()
+            .downcast_ref::<ExitValue>()
+            .unwrap_or_else(|| panic!("{} did not exit", input));
+        assert_eq!(exit.code, expected);
+    }
+
+    let evaluated = test_eval(r#"exit("1")"#);
+    let error = evaluated.as_any().downcast_ref::<Error>().unwrap();
+    assert_eq!(
+        error.message,
+        "argument to `exit` must be INTEGER, got STRING"
+    );
+}
+
+#[test]
+fn test_format_builtin() {
+    let tests = vec![
+        (r#"format("{} + {} = {}", 1, 2, 3)"#, "1 + 2 = 3"),
+        (
+            r#"format("Hello, {}! You are {} years old.", "Ada", 36)"#,
+            "Hello, Ada! You are 36 years old.",
+        ),
+        (r#"format("no placeholders")"#, "no placeholders"),
+        (r#"format("{}{}", [1, 2], true)"#, "[1, 2]true"),
+        (r#"format("")"#, ""),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let string = evaluated
+            .as_any()
+            .downcast_ref::<StringObj>()
+            .unwrap_or_else(|| panic!("{} did not return a String", input));
+        assert_eq!(string.value, expected);
+    }
+
+    let tests = vec![
+        (
+            r#"format("{} and {}", 1)"#,
+            "format string expects 2 arguments, got 1",
+        ),
+        (
+            r#"format("{}", 1, 2)"#,
+            "format string expects 1 arguments, got 2",
+        ),
+        (
+            "format(1)",
+            "argument to `format` must be STRING, got INTEGER",
+        ),
+        (
+            "format()",
+            "wrong number of arguments. got=0, want=at least 1",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_hash_builtins() {
+    let tests = vec![
+        (r#"keys({"a": 1, "b": 2})"#, "[a, b]"),
+        (r#"values({"a": 1, "b": 2})"#, "[1, 2]"),
+        (r#"entries({"a": 1, "b": 2})"#, "[[a, 1], [b, 2]]"),
+        (r#"{"b": 2, "a": 1}.entries()"#, "[[a, 1], [b, 2]]"),
+        ("keys({})", "[]"),
+        ("entries({})", "[]"),
+        ("keys({1: true, 2: false})", "[1, 2]"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        ("keys([1])", "argument to `keys` must be HASH, got ARRAY"),
+        (
+            "values(1)",
+            "argument to `values` must be HASH, got INTEGER",
+        ),
+        (
+            "entries({}, {})",
+            "wrong number of arguments. got=2, want=1",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_delete_builtin() {
+    let tests = vec![
+        (
+            r#"let h = {"a": 1}; let without = delete(h, "a"); keys(without).len()"#,
+            "0",
+        ),
+        (
+            r#"let h = {"a": 1}; let without = delete(h, "a"); h["a"]"#,
+            "1",
+        ),
+        (r#"delete({"a": 1, "b": 2}, "b")"#, "{a: 1}"),
+        (r#"delete({"a": 1}, "missing")"#, "{a: 1}"),
+        ("delete({1: true, 2: false}, 1)", "{2: false}"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (
+            r#"delete([1], "a")"#,
+            "argument to `delete` must be HASH, got ARRAY",
+        ),
+        ("delete({}, [1])", "unusable as hash key: ARRAY"),
+        ("delete({})", "wrong number of arguments. got=1, want=2"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_unbind_builtin() {
+    let tests = vec![
+        (r#"let x = 5; unbind("x"); x"#, "identifier not found: x"),
+        (r#"unbind("x")"#, "identifier not found: x"),
+        (
+            r#"const x = 5; unbind("x")"#,
+            "cannot unbind const binding 'x'",
+        ),
+        (
+            "unbind(1)",
+            "argument to `unbind` must be STRING, got INTEGER",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+
+    // unbinding inside a function only touches the function's own scope
+    let tests = vec![
+        (r#"let x = 1; let f = fn(x) { unbind("x"); x }; f(2)"#, 1),
+        (
+            r#"let x = 1; let f = fn() { let x = 2; unbind("x"); x }; f()"#,
+            1,
+        ),
+        (r#"let x = 1; let y = 2; unbind("y"); let y = 3; x + y"#, 4),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let integer = evaluated
+            .as_any()
+            .downcast_ref::<Integer>()
+            .unwrap_or_else(|| panic!("{} did not return an Integer", input));
+        assert_eq!(integer.value, expected, "wrong result for {}", input);
+    }
+}
+
+#[test]
+fn test_predicate_builtins() {
+    let tests = vec![
+        ("any([1, 2, 3], fn(x) { x > 2 })", true),
+        ("any([1, 2, 3], fn(x) { x > 3 })", false),
+        ("any([], fn(x) { true })", false),
+        ("all([2, 4, 6], fn(x) { x % 2 == 0 })", true),
+        ("all([2, 3, 6], fn(x) { x % 2 == 0 })", false),
+        ("all([], fn(x) { false })", true),
+        ("none([1, 3, 5], fn(x) { x % 2 == 0 })", true),
+        ("none([1, 2, 5], fn(x) { x % 2 == 0 })", false),
+        ("range(10).any(fn(x) { x == 9 })", true),
+        // evaluation stops at the first element that decides the result
+        ("any([1, 2, true], fn(x) { x + 1 == 3 })", true),
+        ("all([1, 2, true], fn(x) { x + 1 == 3 })", false),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let boolean = evaluated
+            .as_any()
+            .downcast_ref::<Boolean>()
+            .unwrap_or_else(|| panic!("{} did not return a Boolean", input));
+        assert_eq!(boolean.value, expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (
+            "any(1, fn(x) { x })",
+            "argument to `any` must be ARRAY, got INTEGER",
+        ),
+        (
+            "all([1], 2)",
+            "argument to `all` must be FUNCTION, got INTEGER",
+        ),
+        ("none([1])", "wrong number of arguments. got=1, want=2"),
+        (
+            "none([1, true], fn(x) { x + 1 == 0 })",
+            "type mismatch: BOOLEAN + INTEGER",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_zip_builtins() {
+    let tests = vec![
+        (
+            r#"zip([1, 2, 3], ["a", "b", "c"])"#,
+            "[[1, a], [2, b], [3, c]]",
+        ),
+        ("zip([1, 2, 3], [4])", "[[1, 4]]"),
+        ("zip([1], [4, 5, 6])", "[[1, 4]]"),
+        ("zip([], [1])", "[]"),
+        ("zip(range(2), [true, false])", "[[0, true], [1, false]]"),
+        (
+            "zip_with([1, 2, 3], [10, 20, 30], fn(a, b) { a + b })",
+            "[11, 22, 33]",
+        ),
+        ("zip_with([1, 2, 3], [10], fn(a, b) { a * b })", "[10]"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (
+            "zip(1, [1])",
+            "argument to `zip` must be ARRAY, got INTEGER",
+        ),
+        (
+            r#"zip([1], "a")"#,
+            "argument to `zip` must be ARRAY, got STRING",
+        ),
+        ("zip([1])", "wrong number of arguments. got=1, want=2"),
+        (
+            "zip_with([1], [2], 3)",
+            "argument to `zip_with` must be FUNCTION, got INTEGER",
+        ),
+        (
+            "zip_with([1], {}, fn(a, b) { a })",
+            "argument to `zip_with` must be ARRAY, got HASH",
+        ),
+        (
+            "zip_with([1], [true], fn(a, b) { a + b })",
+            "type mismatch: INTEGER + BOOLEAN",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_enumerate_builtin() {
+    let tests = vec![
+        (r#"enumerate(["a", "b", "c"])"#, "[[0, a], [1, b], [2, c]]"),
+        (r#"enumerate(["a", "b"], 1)"#, "[[1, a], [2, b]]"),
+        ("enumerate([])", "[]"),
+        ("enumerate(range(5, 7))", "[[0, 5], [1, 6]]"),
+        (
+            r#"let arr = ["x", "y", "z"]; let out = ""; for (pair in enumerate(arr)) { let i = pair[0]; let v = pair[1]; out = out + str(i) + v; } out"#,
+            "0x1y2z",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (
+            "enumerate(1)",
+            "argument to `enumerate` must be ARRAY, got INTEGER",
+        ),
+        (
+            r#"enumerate([1], "a")"#,
+            "argument to `enumerate` must be INTEGER, got STRING",
+        ),
+        (
+            "enumerate()",
+            "wrong number of arguments. got=0, want=1 to 2",
+        ),
+        ("enumerate([1, 2], 9223372036854775807)", "integer overflow"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_flatten_builtins() {
+    let tests = vec![
+        ("flatten([[1, 2], [3, 4], [5]])", "[1, 2, 3, 4, 5]"),
+        ("flatten([1, [2, 3]])", "[1, 2, 3]"),
+        ("flatten([[1, [2]], []])", "[1, [2]]"),
+        ("flatten([])", "[]"),
+        (
+            "flat_map([1, 2, 3], fn(x) { [x, x * 2] })",
+            "[1, 2, 2, 4, 3, 6]",
+        ),
+        ("flat_map([1, 2], fn(x) { x })", "[1, 2]"),
+        ("[1, 2].flat_map(fn(x) { [] })", "[]"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (
+            "flatten(1)",
+            "argument to `flatten` must be ARRAY, got INTEGER",
+        ),
+        (
+            "flatten([], [])",
+            "wrong number of arguments. got=2, want=1",
+        ),
+        (
+            "flat_map([1], 2)",
+            "argument to `flat_map` must be FUNCTION, got INTEGER",
+        ),
+        (
+            "flat_map([1], fn(x) { x + true })",
+            "type mismatch: INTEGER + BOOLEAN",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_numeric_array_builtins() {
+    let tests = vec![
+        ("sum([1, 2, 3, 4])", "10"),
+        ("sum([])", "0"),
+        ("sum([1, 2.5])", "3.5"),
+        ("sum(range(101))", "5050"),
+        ("product([1, 2, 3, 4])", "24"),
+        ("product([])", "1"),
+        ("product([2, 0.5])", "1.0"),
+        ("avg([2, 4, 6])", "4.0"),
+        ("avg([1, 2])", "1.5"),
+        ("[1, 2, 3].sum()", "6"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    assert_eq!(test_eval("avg([2, 4, 6])").type_(), ObjectType::Float);
+    assert_eq!(test_eval("product([2, 0.5])").type_(), ObjectType::Float);
+
+    let tests = vec![
+        ("avg([])", "cannot take `avg` of an empty array"),
+        (
+            r#"sum([1, "2"])"#,
+            "elements of `sum` must be INTEGER or FLOAT, got STRING",
+        ),
+        (
+            "avg([true])",
+            "elements of `avg` must be INTEGER or FLOAT, got BOOLEAN",
+        ),
+        (
+            "product(5)",
+            "argument to `product` must be ARRAY, got INTEGER",
+        ),
+        ("sum([9223372036854775807, 1])", "integer overflow"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_unique_and_count_by_builtins() {
+    let tests = vec![
+        ("unique([1, 2, 1, 3, 2])", "[1, 2, 3]"),
+        ("unique([])", "[]"),
+        // duplicates are found with the same equality as `==`
+        ("unique([1, 1.0, 2.0, 2])", "[1, 2.0]"),
+        ("unique([[1, 2], [1, 2], [2, 1]])", "[[1, 2], [2, 1]]"),
+        (r#"unique([{"a": 1}, {"a": 1}, "a", "a"])"#, "[{a: 1}, a]"),
+        (
+            "count_by([1, 2, 1, 3, 2, 2], fn(x) { x })",
+            "{1: 2, 2: 3, 3: 1}",
+        ),
+        (
+            r#"count_by(["apple", "avocado", "banana"], fn(s) { first(split(s, "")) })"#,
+            "{a: 2, b: 1}",
+        ),
+        (
+            "count_by(range(10), fn(x) { x % 2 == 0 })",
+            "{false: 5, true: 5}",
+        ),
+        ("count_by([], fn(x) { x })", "{}"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (
+            "unique(1)",
+            "argument to `unique` must be ARRAY, got INTEGER",
+        ),
+        (
+            "count_by([1], 2)",
+            "argument to `count_by` must be FUNCTION, got INTEGER",
+        ),
+        (
+            "count_by([1], fn(x) { [x] })",
+            "unusable as hash key: ARRAY",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_find_builtins() {
+    let tests = vec![
+        ("find([1, 2, 3, 4], fn(x) { x > 2 })", "3"),
+        ("find([1, 2, 3], fn(x) { x > 10 })", "null"),
+        ("find([], fn(x) { true })", "null"),
+        (r#"find(["a", "bb", "cc"], fn(s) { len(s) == 2 })"#, "bb"),
+        ("find_index([1, 2, 3, 4], fn(x) { x == 3 })", "2"),
+        ("find_index([1, 2, 3], fn(x) { x == 10 })", "-1"),
+        ("range(5, 10).find_index(fn(x) { x == 7 })", "2"),
+        // the predicate is not called past the first match
+        ("find([1, 2, true], fn(x) { x + 1 == 3 })", "2"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (
+            "find(1, fn(x) { x })",
+            "argument to `find` must be ARRAY, got INTEGER",
+        ),
+        (
+            "find_index([1], 2)",
+            "argument to `find_index` must be FUNCTION, got INTEGER",
+        ),
+        (
+            "find([true], fn(x) { x + 1 })",
+            "type mismatch: BOOLEAN + INTEGER",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_slice_builtin() {
+    let tests = vec![
+        ("slice([1, 2, 3, 4, 5], 1, 3)", "[2, 3]"),
+        ("slice([1, 2, 3, 4, 5], -2)", "[4, 5]"),
+        ("slice([1, 2, 3, 4, 5], 1, -1)", "[2, 3, 4]"),
+        ("slice([1, 2, 3], 0, 10)", "[1, 2, 3]"),
+        ("slice([1, 2, 3], -10, 1)", "[1]"),
+        ("slice([1, 2, 3], 2, 1)", "[]"),
+        ("slice([1, 2, 3], 5)", "[]"),
+        ("slice(range(10), 7)", "[7, 8, 9]"),
+        ("slice(range(0, 9000000000000000000), 2, 5)", "[2, 3, 4]"),
+        (
+            "slice(range(0, 9000000000000000000), -2)",
+            "[8999999999999999998, 8999999999999999999]",
+        ),
+        ("slice(range(10, 0, -2), 1, -1)", "[8, 6, 4]"),
+        (
+            "slice(range(-9223372036854775807 - 1, 9223372036854775807), -1)",
+            "[9223372036854775806]",
+        ),
+        (r#"slice("hello", 1, 4)"#, "ell"),
+        (r#"slice("world", -3)"#, "rld"),
+        (r#"slice("héllo", 1, 2)"#, "é"),
+        (r#""hello".slice(3)"#, "lo"),
+        ("[1, 2, 3].slice(0, 2)", "[1, 2]"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (
+            "slice([1])",
+            "wrong number of arguments. got=1, want=2 or 3",
+        ),
+        (
+            r#"slice([1], "a")"#,
+            "argument to `slice` must be INTEGER, got STRING",
+        ),
+        (
+            "slice(5, 1)",
+            "argument to `slice` must be ARRAY or STRING, got INTEGER",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_partial_builtin() {
+    let tests = vec![
+        (
+            "let add = fn(a, b) { a + b }; let add_five = partial(add, 5); add_five(3)",
+            "8",
+        ),
+        (
+            "let f = fn(a, b, c) { a * 100 + b * 10 + c }; partial(partial(f, 1), 2)(3)",
+            "123",
+        ),
+        ("let f = fn(a, b, c) { [a, b, c] }; partial(f, 1, 2)(3)", "[1, 2, 3]"),
+        ("let greet = partial(format, \"{}, {}!\"); greet(\"hi\", \"bob\")", "hi, bob!"),
+        ("map([1, 2, 3], partial(fn(a, b) { a * b }, 10))", "[10, 20, 30]"),
+        (
+            "let count = fn(n) { if (n == 0) { return 0; } partial(count, n - 1)() + 1 }; count(50)",
+            "50",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (
+            "partial(fn(x) { x })",
+            "wrong number of arguments. got=1, want=at least 2",
+        ),
+        (
+            "partial(5, 1)",
+            "argument to `partial` must be FUNCTION, got INTEGER",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_memoize_builtin() {
+    let tests = vec![
+        (
+            "let fib = memoize(fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } }); fib(10)",
+            "55",
+        ),
+        (
+            "let fib = memoize(fn(n) { if (n < 2) { n } else { fib(n - 1) + fib(n - 2) } }); fib(90)",
+            "2880067194370816120",
+        ),
+        (
+            "let calls = 0; let square = memoize(fn(x) { calls = calls + 1; x * x }); [square(4), square(4), calls]",
+            "[16, 16, 1]",
+        ),
+        (
+            "let calls = 0; let f = memoize(fn(x) { calls = calls + 1; x }); f(1); f(\"1\"); f(1); calls",
+            "2",
+        ),
+        (
+            "let calls = 0; let add = memoize(fn(a, b) { calls = calls + 1; a + b }); [add(1, 2), add(2, 1), add(1, 2), calls]",
+            "[3, 3, 3, 2]",
+        ),
+        ("let sq = memoize(fn(x) { x * x }); map([2, 3, 2], sq)", "[4, 9, 4]"),
+        (
+            "let f = memoize(fn(x) { len(x) }); [f([\"a\", \"b\"]), f([\"a, b\"])]",
+            "[2, 1]",
+        ),
+        (
+            "let f = memoize(fn(...args) { len(args) }); [f(\"a\", \"b\"), f(\"a, STRING:b\")]",
+            "[2, 1]",
+        ),
+        (
+            "let calls = 0; let f = memoize(fn(x) { calls = calls + 1; x }); f([1, {\"k\": 2}]); f([1, {\"k\": 2}]); f([1, {\"k\": 3}]); calls",
+            "2",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (
+            "memoize(fn(x) { x }, 1)",
+            "wrong number of arguments. got=2, want=1",
+        ),
+        (
+            "memoize([1])",
+            "argument to `memoize` must be FUNCTION, got ARRAY",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_compose_and_pipe_builtins() {
+    let tests = vec![
+        (
+            "let inc = fn(x) { x + 1 }; let double = fn(x) { x * 2 }; let inc_then_double = pipe(inc, double); inc_then_double(3)",
+            "8",
+        ),
+        (
+            "let inc = fn(x) { x + 1 }; let double = fn(x) { x * 2 }; compose(inc, double)(3)",
+            "7",
+        ),
+        (
+            "let inc = fn(x) { x + 1 }; let double = fn(x) { x * 2 }; let square = fn(x) { x * x }; pipe(inc, double, square)(3)",
+            "64",
+        ),
+        (
+            "let inc = fn(x) { x + 1 }; let double = fn(x) { x * 2 }; let square = fn(x) { x * x }; compose(inc, double, square)(3)",
+            "19",
+        ),
+        ("pipe(fn(a, b) { a + b }, str)(1, 2)", "3"),
+        ("pipe(partial(fn(a, b) { a * b }, 3), str)(4)", "12"),
+        ("pipe()(5)", "5"),
+        ("compose()(\"same\")", "same"),
+        ("compose()()", "null"),
+        ("map([1, 2], pipe(fn(x) { x * 10 }))", "[10, 20]"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (
+            "pipe(fn(x) { x }, 1)",
+            "argument to `pipe` must be FUNCTION, got INTEGER",
+        ),
+        (
+            "compose(\"f\", fn(x) { x })",
+            "argument to `compose` must be FUNCTION, got STRING",
+        ),
+        (
+            "pipe(fn(x) { x / 0 }, fn(x) { x + 1 })(1)",
+            "division by zero",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("{} did not return an Error", input));
+        assert_eq!(error.message, expected);
+    }
+}
@@ -1,5 +1,9 @@
 use ruskey::environment::Environment;
-use ruskey::object::Integer;
+use ruskey::object::{Integer, Null, Object, ObjectType, ReturnValue};
+use std::cell::RefCell;
+use std::hint::black_box;
+use std::rc::Rc;
+use std::time::Instant;
 
 #[test]
 fn test_environment() {
@@ -12,10 +16,113 @@ fn test_environment() {
     env.set("x".to_string(), val);
 
     // Retrieve the value and verify it's correct
-    let retrieved = env.get(&"x".to_string());
+    let retrieved = env.get("x");
     assert!(retrieved.is_some());
 
     let int_obj = retrieved.unwrap();
     let integer = int_obj.as_any().downcast_ref::<Integer>().unwrap();
     assert_eq!(integer.value, 5);
 }
+
+#[test]
+fn test_assign_updates_owning_scope() {
+    let outer = Rc::new(RefCell::new(Environment::new()));
+    outer
+        .borrow_mut()
+        .set("x".to_string(), Box::new(Integer::new(1)));
+
+    let inner = Environment::new_enclosed(Rc::clone(&outer));
+    assert!(inner.assign("x", Box::new(Integer::new(2))));
+    assert!(!inner.assign("y", Box::new(Integer::new(3))));
+
+    let x = outer.borrow().get("x").unwrap();
+    assert_eq!(x.as_any().downcast_ref::<Integer>().unwrap().value, 2);
+    assert!(outer.borrow().get("y").is_none());
+}
+
+#[test]
+fn test_clones_share_bindings() {
+    let mut env = Environment::new();
+    let copy = env.clone();
+
+    env.set("x".to_string(), Box::new(Integer::new(5)));
+
+    assert!(copy.get("x").is_some());
+}
+
+#[test]
+fn test_clone_preserves_return_values() {
+    let mut env = Environment::new();
+    env.set(
+        "r".to_string(),
+        Box::new(ReturnValue::new(Box::new(Integer::new(7)))),
+    );
+    env.set("n".to_string(), Box::new(Null::new()));
+
+    let copy = env.clone();
+    let value = copy.get("r").unwrap();
+    assert_eq!(value.type_(), ObjectType::ReturnValue);
+    let returned = value.as_any().downcast_ref::<ReturnValue>().unwrap();
+    assert_eq!(returned.value.inspect(), "7");
+
+    let cloned: Box<dyn Object> = value.clone();
+    assert_eq!(cloned.type_(), ObjectType::ReturnValue);
+    assert_eq!(cloned.inspect(), "7");
+    assert_eq!(copy.get("n").unwrap().type_(), ObjectType::Null);
+}
+
+#[test]
+fn test_names_include_outer_scopes() {
+    let outer = Rc::new(RefCell::new(Environment::new()));
+    outer
+        .borrow_mut()
+        .set("b".to_string(), Box::new(Integer::new(1)));
+    outer
+        .borrow_mut()
+        .set("a".to_string(), Box::new(Integer::new(2)));
+
+    let mut inner = Environment::new_enclosed(outer);
+    inner.set("c".to_string(), Box::new(Integer::new(3)));
+    inner.set("a".to_string(), Box::new(Integer::new(4)));
+
+    assert_eq!(inner.names(), vec!["a", "b", "c"]);
+    assert!(Environment::new().names().is_empty());
+}
+
+/// Times 10,000 reads of a variable from an enclosing scope, looked up with a
+/// string literal and with a freshly allocated `String` as callers had to
+/// before `get` took `&str`
+///
+/// Run with `cargo test --release --test environment_tests -- --ignored --nocapture`
+#[test]
+#[ignore]
+fn bench_get_by_str() {
+    const READS: usize = 10_000;
+
+    let outer = Rc::new(RefCell::new(Environment::new()));
+    outer
+        .borrow_mut()
+        .set("x".to_string(), Box::new(Integer::new(1)));
+    let env = Environment::new_enclosed(outer);
+
+    let start = Instant::now();
+    for _ in 0..READS {
+        let name = String::from(black_box("x"));
+        black_box(env.get(&name));
+    }
+    let allocating = start.elapsed();
+
+    let start = Instant::now();
+    for _ in 0..READS {
+        black_box(env.get(black_box("x")));
+    }
+    let borrowed = start.elapsed();
+
+    println!(
+        "{} reads: {:?} with an allocated String, {:?} with &str ({:.2}x)",
+        READS,
+        allocating,
+        borrowed,
+        allocating.as_secs_f64() / borrowed.as_secs_f64()
+    );
+}
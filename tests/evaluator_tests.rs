@@ -1,7 +1,11 @@
+use ruskey::ast::Program;
 use ruskey::environment::Environment;
-use ruskey::evaluator::eval;
+use ruskey::evaluator::{eval, Evaluator, STACK_SIZE};
 use ruskey::lexer::Lexer;
-use ruskey::object::{Boolean, Error, Function, Integer, Null, Object, StringObj};
+use ruskey::object::{
+    object_eq, Array, Boolean, Error, FloatObj, Function, Hash, HashKey, Integer, Null, Object,
+    StringObj,
+};
 use ruskey::parser::Parser;
 
 #[test]
@@ -141,7 +145,7 @@ fn test_if_else_expressions() {
 
 fn test_null_object(obj: &dyn Object) {
     assert!(
-        obj.as_any().downcast_ref::<Null>().is_some(),
+        object_eq(obj, &Null::new()),
         "object is not Null, got={:?}",
         obj
     );
@@ -205,6 +209,8 @@ fn test_let_statements() {
         ("let a = 5 * 5; a;", 25),
         ("let a = 5; let b = a; b;", 5),
         ("let a = 5; let b = a; let c = a + b + 5; c;", 15),
+        ("let café = 5; let π = 3; café * π;", 15),
+        ("let 数 = fn(ä) { ä + 1 }; 数(1);", 2),
     ];
 
     for (input, expected) in tests {
@@ -213,6 +219,95 @@ fn test_let_statements() {
     }
 }
 
+#[test]
+fn test_array_destructuring() {
+    let tests = vec![
+        ("let [a, b, c] = [1, 2, 3]; a + b + c", Some(6)),
+        ("let [x, _] = [10, 20]; x", Some(10)),
+        ("let [_, y] = [10, 20]; y", Some(20)),
+        ("let [a] = [1, 2, 3]; a", Some(1)),
+        ("let [a, b] = [1]; b", None),
+        ("let [a, b] = []; a", None),
+        ("let f = fn() { let [a, b] = [1, 2]; a + b }; f()", Some(3)),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        match expected {
+            Some(integer) => test_integer_object(evaluated.as_ref(), integer),
+            None => test_null_object(evaluated.as_ref()),
+        }
+    }
+
+    let errors = vec![
+        ("let [a] = 5;", "cannot destructure INTEGER as an array"),
+        (
+            "const b = 1; let [a, b] = [1, 2];",
+            "cannot reassign const binding 'b'",
+        ),
+    ];
+
+    for (input, expected) in errors {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .expect("Expected Error");
+        assert_eq!(error.message, expected);
+    }
+
+    assert_eq!(
+        test_eval("let x = 1;\nlet [a] = 5;").inspect(),
+        "ERROR: cannot destructure INTEGER as an array at line 2, column 1"
+    );
+}
+
+#[test]
+fn test_hash_destructuring() {
+    assert_eq!(
+        test_eval(r#"let {name} = {"name": "Alice"}; name"#).inspect(),
+        "Alice"
+    );
+
+    let tests = vec![
+        (r#"let {x, y} = {"x": 1, "y": 2}; x + y"#, Some(3)),
+        (r#"let {x: a, y: b} = {"x": 1, "y": 2}; a - b"#, Some(-1)),
+        (r#"let {x, y: b} = {"x": 1, "y": 2}; x * 10 + b"#, Some(12)),
+        (r#"let {x, z} = {"x": 1}; z"#, None),
+        (r#"let {x} = {1: 2}; x"#, None),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        match expected {
+            Some(integer) => test_integer_object(evaluated.as_ref(), integer),
+            None => test_null_object(evaluated.as_ref()),
+        }
+    }
+
+    let errors = vec![
+        ("let {x} = [1];", "cannot destructure ARRAY as a hash"),
+        (
+            r#"const a = 1; let {x: a} = {"x": 2};"#,
+            "cannot reassign const binding 'a'",
+        ),
+    ];
+
+    for (input, expected) in errors {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .expect("Expected Error");
+        assert_eq!(error.message, expected);
+    }
+
+    assert_eq!(
+        test_eval("let x = 1;\nlet {a} = [1];").inspect(),
+        "ERROR: cannot destructure ARRAY as a hash at line 2, column 1"
+    );
+}
+
 #[test]
 fn test_function_object() {
     let input = "fn(x) { x + 2; };";
@@ -294,20 +389,91 @@ fn test_closures() {
     test_integer_object(evaluated.as_ref(), 5);
 }
 
+thread_local! {
+    /// Number of `Probe` objects alive on this thread
+    static LIVE_PROBES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// An object that counts its live copies, to see whether scopes are freed
+#[derive(Debug)]
+struct Probe;
+
+impl Probe {
+    fn new() -> Self {
+        LIVE_PROBES.with(|live| live.set(live.get() + 1));
+        Probe
+    }
+
+    fn live() -> usize {
+        LIVE_PROBES.with(|live| live.get())
+    }
+}
+
+impl Drop for Probe {
+    fn drop(&mut self) {
+        LIVE_PROBES.with(|live| live.set(live.get() - 1));
+    }
+}
+
+impl Object for Probe {
+    fn type_(&self) -> ruskey::object::ObjectType {
+        ruskey::object::ObjectType::Null
+    }
+
+    fn inspect(&self) -> String {
+        "probe".to_string()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn Object> {
+        Box::new(Probe::new())
+    }
+}
+
+#[test]
+fn test_call_scopes_are_freed() {
+    let tests = vec![
+        // an inner function lives in the scope it closes over
+        "let f = fn() { let p = probe; let g = fn() { p }; 1 }; f(); f(); f()",
+        "let f = fn() { let p = probe; fn g() { p }; 1 }; f(); f(); f()",
+        "let f = fn() { let p = probe; let g = fn() { p }; g() }; f(); f(); f()",
+        // assigned from a loop scope back into the call scope
+        "let f = fn() { let p = probe; let g = 0; for (i in [1]) { g = fn() { p } }; 1 }; f(); f()",
+    ];
+
+    for input in tests {
+        let mut env = Environment::new();
+        env.set("probe".to_string(), Box::new(Probe::new()));
+        let program = Program::try_from(input).unwrap();
+        eval(&program, &mut env);
+
+        // only the global binding is left
+        assert_eq!(Probe::live(), 1, "a scope was kept alive by {}", input);
+        drop(env);
+        assert_eq!(Probe::live(), 0, "a scope was kept alive by {}", input);
+    }
+
+    // a closure that escapes keeps its scope alive, and only that one
+    let mut env = Environment::new();
+    env.set("probe".to_string(), Box::new(Probe::new()));
+    let input = "let make = fn() { let p = probe; let n = 41; let g = fn() { n + 1 }; g };
+                 let h = 0; for (i in range(5)) { h = make() }; h()";
+    let program = Program::try_from(input).unwrap();
+    test_integer_object(eval(&program, &mut env).as_ref(), 42);
+    assert_eq!(Probe::live(), 2);
+}
+
 #[test]
 fn test_string_literal() {
     let input = r#""Hello World!""#;
 
     let evaluated = test_eval(input);
-    let string = evaluated
-        .as_any()
-        .downcast_ref::<StringObj>()
-        .expect("Expected StringObj");
-
     assert_eq!(
-        string.value, "Hello World!",
-        "String has wrong value. got={:?}",
-        string.value
+        evaluated,
+        Box::new(StringObj::new("Hello World!".to_string()))
     );
 }
 
@@ -316,15 +482,9 @@ fn test_string_concatenation() {
     let input = r#""Hello" + " " + "World!""#;
 
     let evaluated = test_eval(input);
-    let string = evaluated
-        .as_any()
-        .downcast_ref::<StringObj>()
-        .expect("Expected StringObj");
-
     assert_eq!(
-        string.value, "Hello World!",
-        "String has wrong value. got={:?}",
-        string.value
+        evaluated,
+        Box::new(StringObj::new("Hello World!".to_string()))
     );
 }
 
@@ -333,14 +493,1896 @@ fn test_string_error_operations() {
     let input = r#""Hello" - "World""#;
 
     let evaluated = test_eval(input);
+    assert_eq!(
+        evaluated,
+        Box::new(Error::new("unknown operator: STRING - STRING".to_string()))
+    );
+}
+
+#[test]
+fn test_array_literals() {
+    let input = "[1, 2 * 2, 3 + 3]";
+
+    let evaluated = test_eval(input);
+    let expected = Array::new(vec![
+        Box::new(Integer::new(1)),
+        Box::new(Integer::new(4)),
+        Box::new(Integer::new(6)),
+    ]);
+    assert_eq!(evaluated, Box::new(expected));
+}
+
+#[test]
+fn test_empty_and_nested_array_literals() {
+    let tests = vec![
+        ("[]", "[]"),
+        ("[[1, 2], [3]]", "[[1, 2], [3]]"),
+        ("let a = [1, 2, 3]; a;", "[1, 2, 3]"),
+        (
+            r#"[1, "two", true, [fn(x) { x }(4)]]"#,
+            "[1, two, true, [4]]",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert!(
+            evaluated.as_any().downcast_ref::<Array>().is_some(),
+            "object is not Array. got={:?}",
+            evaluated
+        );
+        assert_eq!(evaluated.inspect(), expected);
+    }
+}
+
+#[test]
+fn test_array_literal_error_propagation() {
+    let evaluated = test_eval("[1, 5 + true, 3]");
+    assert_eq!(
+        evaluated,
+        Box::new(Error::new("type mismatch: INTEGER + BOOLEAN".to_string()))
+    );
+}
+
+#[test]
+fn test_hash_literals() {
+    let input = r#"let two = "two";
+    {
+        "one": 10 - 9,
+        two: 1 + 1,
+        "thr" + "ee": 6 / 2,
+        4: 4,
+        true: 5,
+        false: 6
+    }"#;
+
+    let evaluated = test_eval(input);
+    let hash = evaluated
+        .as_any()
+        .downcast_ref::<Hash>()
+        .unwrap_or_else(|| panic!("object is not Hash. got={:?}", evaluated));
+
+    let expected = vec![
+        (HashKey::String("one".to_string()), 1),
+        (HashKey::String("two".to_string()), 2),
+        (HashKey::String("three".to_string()), 3),
+        (HashKey::Integer(4), 4),
+        (HashKey::Boolean(true), 5),
+        (HashKey::Boolean(false), 6),
+    ];
+
+    assert_eq!(
+        hash.pairs.len(),
+        expected.len(),
+        "hash has wrong num of pairs. got={}",
+        hash.pairs.len()
+    );
+
+    for (key, value) in expected {
+        let pair = hash
+            .pairs
+            .get(&key)
+            .unwrap_or_else(|| panic!("no pair for given key in pairs: {}", key));
+        test_integer_object(pair.as_ref(), value);
+    }
+}
+
+#[test]
+fn test_hash_literal_inspect() {
+    let tests = vec![
+        ("{}", "{}"),
+        (r#"{"one": 1, "two": 2}"#, "{one: 1, two: 2}"),
+        ("{1: true}", "{1: true}"),
+        ("{true: [1, 2]}", "{true: [1, 2]}"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert!(
+            evaluated.as_any().downcast_ref::<Hash>().is_some(),
+            "object is not Hash. got={:?}",
+            evaluated
+        );
+        assert_eq!(evaluated.inspect(), expected);
+    }
+}
+
+#[test]
+fn test_array_index_expressions() {
+    let tests = vec![
+        ("[1, 2, 3][0]", Some(1)),
+        ("[1, 2, 3][1]", Some(2)),
+        ("[1, 2, 3][2]", Some(3)),
+        ("let i = 0; [1][i];", Some(1)),
+        ("[1, 2, 3][1 + 1];", Some(3)),
+        ("let myArray = [1, 2, 3]; myArray[2];", Some(3)),
+        (
+            "let myArray = [1, 2, 3]; myArray[0] + myArray[1] + myArray[2];",
+            Some(6),
+        ),
+        (
+            "let myArray = [1, 2, 3]; let i = myArray[0]; myArray[i]",
+            Some(2),
+        ),
+        ("[[1, 2], [3, 4]][1][0]", Some(3)),
+        ("[1, 2, 3][3]", None),
+        ("[1, 2, 3][-1]", None),
+        ("[][0]", None),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+
+        match expected {
+            Some(integer) => test_integer_object(evaluated.as_ref(), integer),
+            None => test_null_object(evaluated.as_ref()),
+        }
+    }
+}
+
+#[test]
+fn test_hash_index_expressions() {
+    let tests = vec![
+        (r#"{"foo": 5}["foo"]"#, Some(5)),
+        (r#"{"x": 10}["x"]"#, Some(10)),
+        (r#"{"foo": 5}["bar"]"#, None),
+        (r#"let key = "foo"; {"foo": 5}[key]"#, Some(5)),
+        (r#"{}["foo"]"#, None),
+        ("{5: 5}[5]", Some(5)),
+        ("{true: 5}[true]", Some(5)),
+        ("{false: 5}[false]", Some(5)),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+
+        match expected {
+            Some(integer) => test_integer_object(evaluated.as_ref(), integer),
+            None => test_null_object(evaluated.as_ref()),
+        }
+    }
+}
+
+#[test]
+fn test_index_operator_not_supported() {
+    let evaluated = test_eval("5[0]");
     let error = evaluated
         .as_any()
         .downcast_ref::<Error>()
         .expect("Expected Error");
 
+    assert_eq!(error.message, "index operator not supported: INTEGER");
+}
+
+fn test_float_object(obj: &dyn Object, expected: f64) {
+    let float = obj
+        .as_any()
+        .downcast_ref::<FloatObj>()
+        .unwrap_or_else(|| panic!("object is not Float. got={:?}", obj));
     assert_eq!(
-        error.message, "unknown operator: STRING - STRING",
-        "wrong error message. got={:?}",
-        error.message
+        float.value, expected,
+        "object has wrong value. got={}, want={}",
+        float.value, expected
     );
 }
+
+#[test]
+fn test_eval_float_expression() {
+    let tests = vec![
+        ("1.5", 1.5),
+        ("-2.5", -2.5),
+        ("1.5 + 1.25", 2.75),
+        ("5.0 - 0.5", 4.5),
+        ("1.5 * 2.0", 3.0),
+        ("7.0 / 2.0", 3.5),
+        ("1 + 0.5", 1.5),
+        ("0.5 + 1", 1.5),
+        ("3 * 1.5", 4.5),
+        ("7 / 2.0", 3.5),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        test_float_object(evaluated.as_ref(), expected);
+    }
+}
+
+#[test]
+fn test_float_comparisons() {
+    let tests = vec![
+        ("1.5 < 2.5", true),
+        ("1.5 > 2.5", false),
+        ("2.0 == 2", true),
+        ("2 != 2.5", true),
+        ("0.1 + 0.2 == 0.3", false),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        test_boolean_object(evaluated.as_ref(), expected);
+    }
+}
+
+#[test]
+fn test_float_inspect() {
+    assert_eq!(test_eval("2.0").inspect(), "2.0");
+    assert_eq!(test_eval("1.5 * 3").inspect(), "4.5");
+}
+
+#[test]
+fn test_modulo_operator() {
+    let tests = vec![
+        ("7 % 3", 1),
+        ("6 % 3", 0),
+        ("2 + 7 % 3", 3),
+        ("(2 + 7) % 4", 1),
+        // modulo is Euclidean: the result is never negative
+        ("-7 % 3", 2),
+        ("7 % -3", 1),
+        ("-7 % -3", 2),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        test_integer_object(evaluated.as_ref(), expected);
+    }
+}
+
+#[test]
+fn test_float_modulo_operator() {
+    test_float_object(test_eval("7.5 % 2").as_ref(), 1.5);
+    test_float_object(test_eval("-7.5 % 2").as_ref(), 0.5);
+}
+
+#[test]
+fn test_division_by_zero() {
+    for input in [
+        "5 / 0", "0 / 0", "-5 / 0", "5 % 0", "5.0 / 0", "5 / 0.0", "5.0 % 0",
+    ] {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("no error object returned. got={:?}", evaluated));
+
+        assert_eq!(
+            error.message, "division by zero",
+            "wrong error for {}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_power_operator() {
+    let tests = vec![
+        ("2 ** 10", 1024),
+        ("2 ** 0", 1),
+        ("(-2) ** 3", -8),
+        ("2 ** 3 ** 2", 512),
+        ("(2 ** 3) ** 2", 64),
+        ("3 * 2 ** 2", 12),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        test_integer_object(evaluated.as_ref(), expected);
+    }
+
+    test_float_object(test_eval("2.0 ** 3").as_ref(), 8.0);
+    test_float_object(test_eval("4 ** 0.5").as_ref(), 2.0);
+    test_float_object(test_eval("2 ** -1.0").as_ref(), 0.5);
+}
+
+#[test]
+fn test_power_operator_errors() {
+    let tests = vec![
+        (
+            "2 ** -1",
+            "negative exponent: integer power requires a non-negative exponent",
+        ),
+        ("2 ** 64", "integer overflow"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("no error object returned. got={:?}", evaluated));
+
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_comparison_operators() {
+    let tests = vec![
+        ("1 < 2", true),
+        ("2 < 2", false),
+        ("1 > 2", false),
+        ("3 > 2", true),
+        ("1 <= 2", true),
+        ("2 <= 2", true),
+        ("3 <= 2", false),
+        ("1 >= 2", false),
+        ("2 >= 2", true),
+        ("3 >= 2", true),
+        ("-1 <= -1", true),
+        ("1.5 <= 1.5", true),
+        ("1.5 >= 2", false),
+        ("2 >= 1.5", true),
+        (r#""abc" <= "abd""#, true),
+        (r#""abc" <= "abc""#, true),
+        (r#""b" >= "a""#, true),
+        (r#""a" >= "b""#, false),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        test_boolean_object(evaluated.as_ref(), expected);
+    }
+}
+
+#[test]
+fn test_logical_operators() {
+    let tests = vec![
+        ("true && true", true),
+        ("true && false", false),
+        ("false && true", false),
+        ("false || true", true),
+        ("false || false", false),
+        ("true || false", true),
+        ("1 < 2 && 2 < 3", true),
+        ("1 > 2 || 2 > 3", false),
+        ("5 && 0", false),
+        ("5 && 1", true),
+        (r#""" || []"#, false),
+        ("if (false) { 1 } || true", true),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        test_boolean_object(evaluated.as_ref(), expected);
+    }
+}
+
+#[test]
+fn test_logical_operators_short_circuit() {
+    // the right operand would produce an error if it were evaluated
+    let tests = vec![
+        ("false && (5 + true)", false),
+        ("true || (5 + true)", true),
+        ("false && missing", false),
+        ("true || missing()", true),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        test_boolean_object(evaluated.as_ref(), expected);
+    }
+
+    let evaluated = test_eval("true && missing");
+    let error = evaluated
+        .as_any()
+        .downcast_ref::<Error>()
+        .expect("Expected Error");
+    assert_eq!(error.message, "identifier not found: missing");
+}
+
+#[test]
+fn test_null_coalesce() {
+    test_integer_object(test_eval("null ?? 42").as_ref(), 42);
+    test_integer_object(test_eval("5 ?? 42").as_ref(), 5);
+    test_null_object(test_eval("null ?? null").as_ref());
+    test_integer_object(test_eval("null ?? null ?? 99").as_ref(), 99);
+    test_boolean_object(test_eval("false ?? true").as_ref(), false);
+    assert_eq!(
+        test_eval("let x = null; x ?? \"default\"").inspect(),
+        "default"
+    );
+
+    // the right operand would produce an error if it were evaluated
+    test_integer_object(test_eval("1 ?? missing").as_ref(), 1);
+}
+
+#[test]
+fn test_while_statements() {
+    let tests = vec![
+        ("let i = 0; while (i < 5) { let i = i + 1; }; i", 5),
+        ("let i = 10; while (i < 5) { let i = i + 1; }; i", 10),
+        (
+            "let sum = 0; let i = 1; while (i <= 4) { let sum = sum + i; let i = i + 1; }; sum",
+            10,
+        ),
+        (
+            "let f = fn() { let i = 0; while (true) { if (i == 3) { return i; } let i = i + 1; } }; f()",
+            3,
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        test_integer_object(evaluated.as_ref(), expected);
+    }
+
+    test_null_object(test_eval("while (false) { 1 }").as_ref());
+
+    let evaluated = test_eval("while (1 + true) { 1 }");
+    let error = evaluated
+        .as_any()
+        .downcast_ref::<Error>()
+        .expect("Expected Error");
+    assert_eq!(error.message, "type mismatch: INTEGER + BOOLEAN");
+}
+
+#[test]
+fn test_labeled_break_and_continue() {
+    let tests = vec![
+        (
+            "let n = 0; 'outer: while (true) { while (true) { n = n + 1; break 'outer; } n = 100; }; n",
+            1,
+        ),
+        (
+            "let sum = 0; 'outer: for (x in [1, 2, 3]) { for (y in [10, 20]) { if (x == 2) { continue 'outer; } sum = sum + y; } sum = sum + x; }; sum",
+            64,
+        ),
+        (
+            "let n = 0; 'rows: for (i in [1, 2, 3]) { 'cols: for (j in [1, 2, 3]) { if (j == 2) { continue 'rows; } if (i == 3) { break 'rows; } n = n + 1; } }; n",
+            2,
+        ),
+        (
+            "let n = 0; 'a: loop { 'b: loop { 'c: loop { n = n + 1; break 'b; } } n = n + 10; break; }; n",
+            11,
+        ),
+        (
+            "let i = 0; 'outer: do { i = i + 1; loop { continue 'outer; } } while (i < 3); i",
+            3,
+        ),
+        (
+            "let f = fn() { 'outer: while (true) { loop { return 7; } } }; f()",
+            7,
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        test_integer_object(evaluated.as_ref(), expected);
+    }
+}
+
+#[test]
+fn test_do_while_statements() {
+    let tests = vec![
+        ("let x = 0; do { x = x + 1; } while (x < 5); x", 5),
+        ("let x = 10; do { x = x + 1; } while (x < 5); x", 11),
+        ("do { return 42; } while (true)", 42),
+        (
+            "let f = fn() { let i = 0; do { i = i + 1; if (i == 3) { return i; } } while (true) }; f()",
+            3,
+        ),
+        (
+            "let i = 0; do { i = i + 1; if (i == 3) { break; } } while (true); i",
+            3,
+        ),
+        (
+            "let i = 0; let n = 0; do { i = i + 1; if (i % 2 == 0) { continue; } n = n + 1; } while (i < 5); n",
+            3,
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        test_integer_object(evaluated.as_ref(), expected);
+    }
+
+    test_null_object(test_eval("do { 1 } while (false)").as_ref());
+
+    let evaluated = test_eval("let n = 0; do { n = n + 1; } while (n + true)");
+    let error = evaluated
+        .as_any()
+        .downcast_ref::<Error>()
+        .expect("Expected Error");
+    assert_eq!(error.message, "type mismatch: INTEGER + BOOLEAN");
+}
+
+#[test]
+fn test_for_statements() {
+    let tests =
+        vec![
+        ("let sum = 0; for (x in [1, 2, 3]) { sum = sum + x; }; sum", 6),
+        ("let sum = 0; for (x in []) { sum = sum + x; }; sum", 0),
+        (
+            "let f = fn(arr) { for (x in arr) { if (x > 1) { return x * 10; } } }; f([1, 2, 3])",
+            20,
+        ),
+        ("let sum = 0; for (i in range(0, 3)) { sum = sum + i; }; sum", 3),
+        ("let sum = 0; for (i in range(10, 0, -3)) { sum = sum + i; }; sum", 22),
+        (
+            "let n = 0; for (i in range(0, 1000000000)) { if (i == 4) { break; } n = n + 1; }; n",
+            4,
+        ),
+        ("let i = 100; for (i in [1, 2]) { i }; i", 100),
+        ("const k = 1; for (k in [5]) { k }; k", 1),
+        ("let x = 1; for (i in [1, 2]) { let x = i * 10; }; x", 1),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        test_integer_object(evaluated.as_ref(), expected);
+    }
+
+    test_null_object(test_eval("for (x in [1, 2]) { x }").as_ref());
+
+    let evaluated = test_eval(
+        "let fs = []; for (x in [1, 2, 3]) { fs = push(fs, fn() { x }); }; map(fs, fn(f) { f() })",
+    );
+    assert_eq!(evaluated.inspect(), "[1, 2, 3]");
+
+    test_eval("for (x in [1]) { let inner = x; }; inner")
+        .as_any()
+        .downcast_ref::<Error>()
+        .expect("binding made in the body leaked out of the loop");
+
+    let evaluated = test_eval("for (x in 5) { x }");
+    let error = evaluated
+        .as_any()
+        .downcast_ref::<Error>()
+        .expect("Expected Error");
+    assert_eq!(error.message, "for loop over non-iterable: INTEGER");
+}
+
+#[test]
+fn test_break_and_continue() {
+    let tests = vec![
+        (
+            "let i = 0; while (true) { if (i == 3) { break; } let i = i + 1; }; i",
+            3,
+        ),
+        (
+            "let sum = 0; for (x in [1, 2, 3, 4]) { if (x == 3) { break; } sum = sum + x; }; sum",
+            3,
+        ),
+        (
+            "let sum = 0; for (x in [1, 2, 3, 4]) { if (x == 2) { continue; } sum = sum + x; }; sum",
+            8,
+        ),
+        (
+            "let i = 0; let n = 0; while (i < 5) { let i = i + 1; if (i % 2 == 0) { continue; } let n = n + 1; }; n",
+            3,
+        ),
+        (
+            "let sum = 0; for (x in [1, 2]) { for (y in [10, 20]) { if (y == 20) { break; } sum = sum + y; } sum = sum + x; }; sum",
+            23,
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        test_integer_object(evaluated.as_ref(), expected);
+    }
+
+    let tests = vec![
+        ("break;", "break outside loop"),
+        ("continue;", "continue outside loop"),
+        (
+            "let f = fn() { break; }; for (x in [1]) { f(); }",
+            "break outside loop",
+        ),
+        (
+            "'outer: loop { break 'inner; }",
+            "break to unknown label 'inner",
+        ),
+        (
+            "let f = fn() { continue 'outer; }; 'outer: for (x in [1]) { f(); }",
+            "continue to unknown label 'outer",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .expect("Expected Error");
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_hex_literals() {
+    let tests = vec![
+        ("0xFF", 255),
+        ("0x10", 16),
+        ("0xDEAD", 57005),
+        ("0xdead", 57005),
+        ("0x7FFFFFFFFFFFFFFF", i64::MAX),
+        ("0x10 + 0x1", 17),
+    ];
+
+    for (input, expected) in tests {
+        test_integer_object(test_eval(input).as_ref(), expected);
+    }
+
+    for input in ["0x", "0xG", "0x8000000000000000"] {
+        assert!(
+            Program::try_from(input).is_err(),
+            "{} should not parse",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_binary_literals() {
+    let tests = vec![
+        ("0b1010", 10),
+        ("0b11111111", 255),
+        ("0b0", 0),
+        ("0B11 * 0xF", 45),
+    ];
+
+    for (input, expected) in tests {
+        test_integer_object(test_eval(input).as_ref(), expected);
+    }
+
+    for input in ["0b", "0b2", "0b1021"] {
+        assert!(
+            Program::try_from(input).is_err(),
+            "{} should not parse",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_integer_overflow() {
+    let tests = vec![
+        "9223372036854775807 + 1",
+        "-9223372036854775807 - 2",
+        "9223372036854775807 * 2",
+        "(-9223372036854775807 - 1) / -1",
+        "(-9223372036854775807 - 1) % -1",
+        "-(-9223372036854775807 - 1)",
+        "2 ** 63",
+    ];
+
+    for input in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("no error object returned for {}", input));
+        assert_eq!(
+            error.message, "integer overflow",
+            "wrong error for {}",
+            input
+        );
+    }
+
+    test_integer_object(test_eval("9223372036854775806 + 1").as_ref(), i64::MAX);
+    test_integer_object(test_eval("-9223372036854775807 - 1").as_ref(), i64::MIN);
+}
+
+#[test]
+fn test_error_spans() {
+    let tests = vec![
+        (
+            "5 + true",
+            (1, 1),
+            "ERROR: type mismatch: INTEGER + BOOLEAN at line 1, column 1",
+        ),
+        (
+            "let a = 1;\nlet b = a + missing;",
+            (2, 13),
+            "ERROR: identifier not found: missing at line 2, column 13",
+        ),
+        (
+            "let f = fn(x) {\n  x * true\n};\nf(2)",
+            (2, 3),
+            "ERROR: type mismatch: INTEGER * BOOLEAN at line 2, column 3",
+        ),
+        (
+            "len(1)",
+            (1, 1),
+            "ERROR: argument to `len` not supported, got INTEGER at line 1, column 1",
+        ),
+        (
+            "let x = 1;\ny = 5;",
+            (2, 1),
+            "ERROR: identifier not found: y at line 2, column 1",
+        ),
+        (
+            "let f = fn() {\n  y = 1;\n};\nf()",
+            (2, 3),
+            "ERROR: identifier not found: y at line 2, column 3",
+        ),
+    ];
+
+    for (input, start, inspected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("no error object returned for {:?}", input));
+        let span = error.span.expect("error has no span");
+        assert_eq!(span.start, start, "wrong span for {:?}", input);
+        assert_eq!(evaluated.inspect(), inspected);
+    }
+}
+
+#[test]
+fn test_while_let_statements() {
+    let tests = vec![
+        (
+            "let n = 3; let total = 0; while let x = if (n > 0) { n } { total = total + x; n = n - 1; }; total",
+            6,
+        ),
+        (
+            "let next = fn(n) { if (n > 0) { n - 1 } }; let n = 5; let steps = 0; while let m = next(n) { n = m; steps = steps + 1; }; steps",
+            5,
+        ),
+        (
+            "let count = 0; while let x = if (false) { 1 } { count = count + 1; }; count",
+            0,
+        ),
+        (
+            "let n = 10; while let x = n { if (x == 7) { break; } n = n - 1; }; n",
+            7,
+        ),
+        (
+            "let x = 100; let n = 2; while let x = if (n > 0) { n } { n = n - 1; }; x",
+            100,
+        ),
+        (
+            "const c = 1; let n = 1; while let c = if (n > 0) { n + 4 } { n = n - 1; }; c",
+            1,
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        test_integer_object(evaluated.as_ref(), expected);
+    }
+
+    let evaluated = test_eval("while let x = missing { x }");
+    let error = evaluated
+        .as_any()
+        .downcast_ref::<Error>()
+        .expect("Expected Error");
+    assert_eq!(error.message, "identifier not found: missing");
+}
+
+#[test]
+fn test_named_functions() {
+    let tests = vec![
+        ("fn add(a, b) { a + b } add(2, 3)", 5),
+        ("fn double(x) { x * 2 }; double(21)", 42),
+        (
+            "fn fact(n) { if (n == 0) { 1 } else { n * fact(n - 1) } } fact(5)",
+            120,
+        ),
+        ("let f = fn g(x) { x + 1 }; f(1)", 2),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        test_integer_object(evaluated.as_ref(), expected);
+    }
+
+    let evaluated = test_eval("fn add(a, b) { a + b } add");
+    assert_eq!(evaluated.inspect(), "fn add(a, b) {\n  (a + b)\n}");
+}
+
+#[test]
+fn test_rest_parameters() {
+    let tests = vec![
+        ("let f = fn(x, ...rest) { rest }; f(1, 2, 3)", "[2, 3]"),
+        ("let f = fn(x, ...rest) { rest }; f(1)", "[]"),
+        ("let f = fn(...all) { all }; f()", "[]"),
+        ("let f = fn(...all) { len(all) }; f(1, 2, 3, 4)", "4"),
+        ("let f = fn(a, b, ...rest) { a + b }; f(1, 2, 3)", "3"),
+        ("let f = fn(x, ...rest) { x }; f(7, 8)", "7"),
+        (
+            "let f = fn(x, ...rest) { 1 }; f",
+            "fn(x, ...rest) {\n  1\n}",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+}
+
+#[test]
+fn test_spread_arguments() {
+    let tests = vec![
+        (
+            "let f = fn(a, b, c) { a + b + c }; let arr = [1, 2, 3]; f(...arr)",
+            "6",
+        ),
+        ("let f = fn(a, b, c) { a + b + c }; f(1, ...[2], 3)", "6"),
+        (
+            "let f = fn(a, b, c) { [a, b, c] }; f(...[1], ...[2, 3])",
+            "[1, 2, 3]",
+        ),
+        ("let f = fn(...all) { all }; f(...[])", "[]"),
+        ("let f = fn(x, ...rest) { rest }; f(...[1, 2, 3])", "[2, 3]"),
+        ("len(...[[1, 2]])", "2"),
+        (
+            "let f = fn(n) { if (n > 0) { return f(...[n - 1]); } n }; f(3)",
+            "0",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (
+            "let f = fn(x) { x }; f(...5)",
+            "spread argument must be ARRAY, got INTEGER",
+        ),
+        (
+            r#"let f = fn(x) { x }; f(..."ab")"#,
+            "spread argument must be ARRAY, got STRING",
+        ),
+        (
+            "let f = fn(x) { x }; f(...missing)",
+            "identifier not found: missing",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .expect("Expected Error");
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_hash_keys_keep_their_type() {
+    let tests = vec![
+        (r#"{1: "int", "1": "string"}[1]"#, "int"),
+        (r#"{1: "int", "1": "string"}["1"]"#, "string"),
+        (r#"{true: "bool", "true": "string"}[true]"#, "bool"),
+        (r#"{1 + 1: "two"}[2]"#, "two"),
+        (r#"{1 > 0: "yes"}[true]"#, "yes"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        ("{[1]: 1}", "unusable as hash key: ARRAY"),
+        ("{fn(x) { x }: 1}", "unusable as hash key: FUNCTION"),
+        ("{{}: 1}", "unusable as hash key: HASH"),
+        (r#"{"a": 1}[[1]]"#, "unusable as hash key: ARRAY"),
+        (r#"{"a": 1}[1.5]"#, "unusable as hash key: FLOAT"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("no error object returned for {}", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_method_calls() {
+    let tests = vec![
+        (r#""hello".upper()"#, "HELLO"),
+        (r#""  hi  ".trim().len()"#, "2"),
+        (r#""a,b".split(",")"#, "[a, b]"),
+        ("[1, 2, 3].len()", "3"),
+        ("let a = [1, 2]; a.push(3)", "[1, 2, 3]"),
+        ("[3, 1, 2].sort().first()", "1"),
+        (
+            "[1, 2, 3].map(fn(x) { x * 2 }).reduce(0, fn(a, b) { a + b })",
+            "12",
+        ),
+        ("range(0, 3).len()", "3"),
+        (r#"{"b": 2, "a": 1}.keys()"#, "[a, b]"),
+        (r#"{"b": 2, "a": 1}.values()"#, "[1, 2]"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (r#""hello".push(1)"#, "unknown method: STRING.push"),
+        ("5.len()", "unknown method: INTEGER.len"),
+        ("[1].upper()", "unknown method: ARRAY.upper"),
+        ("[1].push()", "wrong number of arguments. got=1, want=2"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("no error object returned for {}", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_index_assignment() {
+    let tests =
+        vec![
+        ("let arr = [1, 2, 3]; arr[0] = 99; arr[0]", "99"),
+        ("let arr = [1, 2, 3]; arr[2] = 99; arr", "[1, 2, 99]"),
+        ("let arr = [1, 2, 3]; arr[1] = 5", "5"),
+        ("let arr = [1, 2]; arr[0] = arr[1] = 7; arr", "[7, 7]"),
+        ("let grid = [[1, 2], [3, 4]]; grid[1][0] = 0; grid", "[[1, 2], [0, 4]]"),
+        ("let arr = [1]; let copy = arr; arr[0] = 2; copy", "[1]"),
+        (
+            "let arr = [0, 0, 0]; let i = 0; while (i < 3) { arr[i] = i * i; let i = i + 1; } arr",
+            "[0, 1, 4]",
+        ),
+        (
+            "let a = [[1, 2], [3, 4]]; let n = -1; let next = fn() { n = n + 1; n }; a[next()][0] = 9; [a, n]",
+            "[[[9, 2], [3, 4]], 0]",
+        ),
+        (
+            "let g = [[[0]]]; let n = 0; let next = fn() { n = n + 1; 0 }; g[next()][next()][next()] = 5; [g, n]",
+            "[[[[5]]], 3]",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        ("let arr = [1]; arr[1] = 2", "index out of range: 1"),
+        ("let grid = [[1]]; grid[0][3] = 2", "index out of range: 3"),
+        ("let arr = [1]; arr[-1] = 2", "index out of range: -1"),
+        (
+            r#"let arr = [1]; arr["a"] = 2"#,
+            "array index must be INTEGER, got STRING",
+        ),
+        (
+            "let n = 1; n[0] = 2",
+            "index assignment not supported: INTEGER",
+        ),
+        ("missing[0] = 1", "identifier not found: missing"),
+        ("[1, 2][0] = 1", "invalid assignment target: [1, 2]"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("no error object returned for {}", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_hash_assignment() {
+    let tests = vec![
+        (r#"let h = {"a": 1}; h["b"] = 2; h["b"]"#, "2"),
+        (r#"let h = {"a": 1}; h["a"] = 5; h["a"]"#, "5"),
+        (
+            r#"let h = {}; h[1] = "one"; h[true] = "yes"; len(h.keys())"#,
+            "2",
+        ),
+        (r#"let h = {"a": 1}; h["a"] = 5; h"#, "{a: 5}"),
+        (
+            r#"let h = {"xs": [1, 2]}; h["xs"][0] = 9; h["xs"]"#,
+            "[9, 2]",
+        ),
+        (r#"let a = [{"n": 1}]; a[0]["n"] = 2; a[0]["n"]"#, "2"),
+        (
+            r#"let counts = {}; for (w in ["a", "b", "a"]) { counts[w] = if (counts[w]) { counts[w] + 1 } else { 1 }; } counts["a"]"#,
+            "2",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (
+            r#"let s = "abc"; s[0] = "z""#,
+            "index assignment not supported: STRING",
+        ),
+        ("let h = {}; h[[1]] = 1", "unusable as hash key: ARRAY"),
+        (
+            "let h = {}; h[fn() {}] = 1",
+            "unusable as hash key: FUNCTION",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("no error object returned for {}", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_structural_equality() {
+    let tests = vec![
+        ("[1, 2] == [1, 2]", true),
+        ("[1, 2] == [1, 3]", false),
+        ("[1, 2] != [1, 3]", true),
+        ("[] == []", true),
+        ("[1] == [1, 1]", false),
+        (r#"[[1, [2]], "a"] == [[1, [2]], "a"]"#, true),
+        ("[1] == [1.0]", true),
+        (r#"[1] == ["1"]"#, false),
+        ("[true] == [true]", true),
+        ("{} == {}", true),
+        (r#"{"a": 1, "b": 2} == {"b": 2, "a": 1}"#, true),
+        (r#"{"a": 1} == {"a": 2}"#, false),
+        (r#"{"a": 1} == {"b": 1}"#, false),
+        (r#"{"a": 1} != {"a": 1, "b": 2}"#, true),
+        ("{1: [1, 2]} == {1: [1, 2]}", true),
+        ("let a = [1, 2]; let b = a; b[0] = 3; a == b", false),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        test_boolean_object(&*evaluated, expected);
+    }
+
+    let tests = vec![
+        ("[1] < [2]", "unknown operator: ARRAY < ARRAY"),
+        ("{} + {}", "unknown operator: HASH + HASH"),
+        ("[1] == {}", "type mismatch: ARRAY == HASH"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("no error object returned for {}", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_string_comparison() {
+    let tests = vec![
+        (r#""abc" < "abd""#, true),
+        (r#""z" > "a""#, true),
+        (r#""hello" == "hello""#, true),
+        (r#""hello" == "world""#, false),
+        (r#""a" != "b""#, true),
+        (r#""a" != "a""#, false),
+        (r#""10" < "9""#, true),
+        (r#""ab" < "abc""#, true),
+        (r#""" < "a""#, true),
+        (r#""B" < "a""#, true),
+        (r#""b" <= "b""#, true),
+        (r#""a" >= "b""#, false),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        test_boolean_object(&*evaluated, expected);
+    }
+
+    let tests = vec![
+        (r#""a" * "b""#, "unknown operator: STRING * STRING"),
+        (r#""a" / "b""#, "unknown operator: STRING / STRING"),
+        (r#""a" < 1"#, "type mismatch: STRING < INTEGER"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("no error object returned for {}", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_string_repeat() {
+    let tests = vec![
+        (r#""ab" * 4"#, "abababab"),
+        (r#""ha" * 3"#, "hahaha"),
+        (r#"3 * "ha""#, "hahaha"),
+        (r#""x" * 0"#, ""),
+        (r#""" * 5"#, ""),
+        (r#""-" * 2 + ">""#, "-->"),
+        (r#""" * 9223372036854775807"#, ""),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (r#""y" * -1"#, "negative repeat count: -1"),
+        (r#"-2 * "y""#, "negative repeat count: -2"),
+        (
+            r#""y" * 9223372036854775807"#,
+            "repeated string too long: 1 bytes * 9223372036854775807, limit is 268435456 bytes",
+        ),
+        (
+            r#"4611686018427387904 * "ab""#,
+            "repeated string too long: 2 bytes * 4611686018427387904, limit is 268435456 bytes",
+        ),
+        (
+            r#""abcd" * 67108865"#,
+            "repeated string too long: 4 bytes * 67108865, limit is 268435456 bytes",
+        ),
+        (r#""y" * 1.5"#, "type mismatch: STRING * FLOAT"),
+        (r#""y" + 1"#, "type mismatch: STRING + INTEGER"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("no error object returned for {}", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_typeof_operator() {
+    let tests = vec![
+        ("typeof 5", "INTEGER"),
+        ("typeof 1.5", "FLOAT"),
+        (r#"typeof "x""#, "STRING"),
+        ("typeof true", "BOOLEAN"),
+        ("typeof fn(x) { x }", "FUNCTION"),
+        ("typeof len", "BUILTIN"),
+        ("typeof [1, 2]", "ARRAY"),
+        ("typeof {}", "HASH"),
+        ("typeof if (false) { 1 }", "NULL"),
+        ("typeof 1 + 2", "type mismatch: STRING + INTEGER"),
+        (r#"typeof 1 == "INTEGER""#, "true"),
+        ("typeof typeof 1", "STRING"),
+        ("let x = [1]; typeof x[0]", "INTEGER"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let result = match evaluated.as_any().downcast_ref::<Error>() {
+            Some(error) => error.message.clone(),
+            None => evaluated.inspect(),
+        };
+        assert_eq!(result, expected, "wrong result for {}", input);
+    }
+
+    let evaluated = test_eval("typeof missing");
+    let error = evaluated.as_any().downcast_ref::<Error>().unwrap();
+    assert_eq!(error.message, "identifier not found: missing");
+}
+
+#[test]
+fn test_bang_operator_on_non_booleans() {
+    let tests = vec![
+        ("!0", true),
+        ("!1", false),
+        ("!-1", false),
+        ("!0.0", true),
+        ("!0.5", false),
+        (r#"!"""#, true),
+        (r#"!"x""#, false),
+        ("![]", true),
+        ("![0]", false),
+        ("!{}", true),
+        (r#"!{"a": 1}"#, false),
+        ("!if (false) { 1 }", true),
+        ("!fn() {}", false),
+        ("!len", false),
+        ("!!0", false),
+        (r#"!!"x""#, true),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        test_boolean_object(&*evaluated, expected);
+    }
+}
+
+#[test]
+fn test_truthiness_is_the_same_everywhere() {
+    let values = vec![
+        ("0", false),
+        ("1", true),
+        ("0.0", false),
+        ("2.5", true),
+        (r#""""#, false),
+        (r#""x""#, true),
+        ("[]", false),
+        ("[0]", true),
+        ("{}", false),
+        (r#"{"a": 1}"#, true),
+        ("null", false),
+        ("false", false),
+        ("fn() {}", true),
+    ];
+
+    for (value, expected) in values {
+        let checks = vec![
+            format!("if ({}) {{ true }} else {{ false }}", value),
+            format!("unless ({}) {{ false }} else {{ true }}", value),
+            format!("!!({})", value),
+            format!("bool({})", value),
+            format!("({}) && true", value),
+            format!(
+                "let n = 0; while (n == 0 && ({})) {{ n = 1 }}; n == 1",
+                value
+            ),
+            format!("len(filter([{}], fn(x) {{ x }})) == 1", value),
+        ];
+
+        for input in checks {
+            let evaluated = test_eval(&input);
+            assert_eq!(
+                evaluated.inspect(),
+                expected.to_string(),
+                "wrong result for {}",
+                input
+            );
+        }
+    }
+}
+
+#[test]
+fn test_loop_statements() {
+    let tests = vec![
+        ("let x = 0; loop { let x = x + 1; if (x == 5) { break; } }; x", 5),
+        (
+            "let x = 0; let evens = 0; loop { let x = x + 1; if (x > 6) { break; } if (x % 2 == 1) { continue; } let evens = evens + 1; }; evens",
+            3,
+        ),
+        (
+            "let f = fn() { let i = 0; loop { let i = i + 1; if (i == 4) { return i * 10; } } }; f()",
+            40,
+        ),
+        (
+            "let n = 0; loop { for (i in [1, 2, 3]) { if (i == 2) { break; } n = n + i; } break; }; n",
+            1,
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        test_integer_object(evaluated.as_ref(), expected);
+    }
+
+    test_null_object(test_eval("loop { break; }").as_ref());
+
+    let evaluated = test_eval("loop { 1 + true; }");
+    let error = evaluated
+        .as_any()
+        .downcast_ref::<Error>()
+        .expect("Expected Error");
+    assert_eq!(error.message, "type mismatch: INTEGER + BOOLEAN");
+}
+
+#[test]
+fn test_unless_expressions() {
+    let tests = vec![
+        ("unless (false) { 10 }", "10"),
+        ("unless (true) { 10 }", "null"),
+        ("unless (1 > 2) { 10 } else { 20 }", "10"),
+        ("unless (1 < 2) { 10 } else { 20 }", "20"),
+        (
+            r#"let x = 3; unless (x > 5) { "small" } else { "big" }"#,
+            "small",
+        ),
+        (
+            r#"let x = 8; unless (x > 5) { "small" } else { "big" }"#,
+            "big",
+        ),
+        ("unless (0) { 10 } else { 20 }", "10"),
+        ("unless (1) { 10 } else { 20 }", "20"),
+        (
+            "let f = fn(x) { unless (x) { return 1; } 2 }; f(false)",
+            "1",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+}
+
+#[test]
+fn test_match_expressions() {
+    let tests = vec![
+        (
+            r#"match (1) { 1 => "one", 2 => "two", _ => "other" }"#,
+            "one",
+        ),
+        (
+            r#"match (2) { 1 => "one", 2 => "two", _ => "other" }"#,
+            "two",
+        ),
+        (
+            r#"match (7) { 1 => "one", 2 => "two", _ => "other" }"#,
+            "other",
+        ),
+        (r#"match ("b") { "a" => 1, "b" => 2 }"#, "2"),
+        (r#"match (3) { 1 => "one", 2 => "two" }"#, "null"),
+        (r#"match (1) { "1" => "string", 1 => "int" }"#, "int"),
+        (
+            r#"match ([1, 2]) { [1, 2] => "pair", _ => "other" }"#,
+            "pair",
+        ),
+        (r#"match (1 + 1) { 1 + 1 => "two", }"#, "two"),
+        ("let x = 5; match (x) { _ => x * 2 }", "10"),
+        (
+            r#"let f = fn(n) { match (n % 2) { 0 => "even", _ => "odd" } }; f(3)"#,
+            "odd",
+        ),
+        (r#"match (true) { false => 0, true => 1 }"#, "1"),
+        ("match (1) { }", "null"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (
+            "match (missing) { _ => 1 }",
+            "identifier not found: missing",
+        ),
+        (
+            "match (1) { missing => 1 }",
+            "identifier not found: missing",
+        ),
+        (
+            "match (1) { 1 => 1 + true }",
+            "type mismatch: INTEGER + BOOLEAN",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("no error object returned for {}", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_try_catch_expressions() {
+    let tests = vec![
+        ("try { 1 + 1 } catch (err) { 0 }", "2"),
+        ("try { 1 + true } catch (err) { 0 }", "0"),
+        (
+            "try { 1 + true } catch (err) { error_message(err) }",
+            "type mismatch: INTEGER + BOOLEAN",
+        ),
+        (
+            r#"try { error("boom"); 5 } catch (e) { "caught " + error_message(e) }"#,
+            "caught boom",
+        ),
+        (
+            r#"let x = try { missing } catch (e) { "default" }; x"#,
+            "default",
+        ),
+        (
+            r#"try { try { error("inner") } catch (e) { error("outer: " + error_message(e)) } } catch (e) { error_message(e) }"#,
+            "outer: inner",
+        ),
+        (
+            r#"try { try { error("inner") } catch (e) { "handled" } } catch (e) { "not reached" }"#,
+            "handled",
+        ),
+        (
+            r#"let f = fn() { error("from f") }; try { f() } catch (e) { error_message(e) }"#,
+            "from f",
+        ),
+        (
+            r#"let f = fn() { try { return error("tail"); } catch (e) { error_message(e) } }; f()"#,
+            "tail",
+        ),
+        (
+            r#"let fail = fn() { error("deep") }; let f = fn() { try { return fail(); } catch (e) { "recovered" } }; f()"#,
+            "recovered",
+        ),
+        (
+            "let f = fn() { try { return 1; } catch (e) { 2 }; 3 }; f()",
+            "1",
+        ),
+        ("let err = 5; try { 1 / 0 } catch (err) { 0 }; err", "5"),
+        (
+            r#"let i = 0; while (true) { try { break; } catch (e) { 0 } }; "done""#,
+            "done",
+        ),
+        (r#"try { error("boom") } catch (e) { type(e) }"#, "ERROR"),
+        (
+            r#"try { error("boom") } catch (e) { e }"#,
+            "ERROR: boom at line 1, column 7",
+        ),
+        (
+            r#"let saved = try { error("boom") } catch (e) { e }; error_message(saved)"#,
+            "boom",
+        ),
+        (
+            r#"try { error("boom") } catch (e) { [e, is_error(e)] }"#,
+            "[ERROR: boom at line 1, column 7, true]",
+        ),
+        (
+            r#"let describe = fn(err) { "failed: " + error_message(err) }; try { error("boom") } catch (e) { describe(e) }"#,
+            "failed: boom",
+        ),
+        (
+            r#"let f = fn() { try { error("boom") } catch (e) { return e; } }; error_message(f())"#,
+            "boom",
+        ),
+        (
+            r#"let a = try { error("x") } catch (e) { e }; let b = try { error("x") } catch (e) { e }; a == b"#,
+            "true",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (
+            "try { 1 + true } catch (err) { error(err) }",
+            "type mismatch: INTEGER + BOOLEAN",
+        ),
+        (
+            r#"try { try { error("inner") } catch (e) { error(e) } } catch (e) { error(error_message(e) + "!") }"#,
+            "inner!",
+        ),
+        (r#"try { error("a") } catch (e) { error("b") }"#, "b"),
+        (
+            r#"try { error("a") } catch (e) { 1 + true }; 5"#,
+            "type mismatch: INTEGER + BOOLEAN",
+        ),
+        (
+            r#"let x = 1 + true; try { 1 } catch (e) { 2 }"#,
+            "type mismatch: INTEGER + BOOLEAN",
+        ),
+        (
+            "error_message(5)",
+            "argument to `error_message` must be ERROR, got INTEGER",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("no error object returned for {}", input));
+        assert_eq!(error.message, expected);
+    }
+}
+
+#[test]
+fn test_const_statements() {
+    let tests = vec![
+        ("const x = 5; x", "5"),
+        ("const x = 5; let f = fn() { let x = 10; x }; f()", "10"),
+        ("const x = 5; let f = fn() { let x = 10; x }; f(); x", "5"),
+        ("const x = 5; let f = fn(x) { x * 2 }; f(4)", "8"),
+        ("const xs = [1, 2]; let f = fn() { xs }; f()", "[1, 2]"),
+        ("let f = fn() { const y = 1; y }; f() + f()", "2"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        assert_eq!(evaluated.inspect(), expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        (
+            "const x = 5; let x = 10;",
+            "cannot reassign const binding 'x'",
+        ),
+        (
+            "const x = 5; const x = 10;",
+            "cannot reassign const binding 'x'",
+        ),
+        (
+            "const f = 1; fn f() { 2 }",
+            "cannot reassign const binding 'f'",
+        ),
+        (
+            "const xs = [1]; xs[0] = 2",
+            "cannot reassign const binding 'xs'",
+        ),
+        ("const x = 1 + true;", "type mismatch: INTEGER + BOOLEAN"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("no error object returned for {}", input));
+        assert_eq!(error.message, expected);
+    }
+
+    let evaluated = test_eval("const x = 5; let x = 10; x");
+    assert!(evaluated.as_any().is::<Error>());
+
+    let tests = vec![
+        (
+            "const k = 1; k = 2;",
+            "ERROR: cannot reassign const binding 'k' at line 1, column 14",
+        ),
+        (
+            "const k = 1; let k = 2;",
+            "ERROR: cannot reassign const binding 'k' at line 1, column 14",
+        ),
+        (
+            "const k = 1;\nconst k = 2;",
+            "ERROR: cannot reassign const binding 'k' at line 2, column 1",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(
+            test_eval(input).inspect(),
+            expected,
+            "wrong error for {}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_reassignment() {
+    let tests = vec![
+        ("let x = 5; x = 10; x", "10"),
+        ("let x = 5; x = x * 2; x", "10"),
+        ("let x = 1; x = 2", "2"),
+        ("let x = 0; while (x < 5) { x = x + 1; } x", "5"),
+        ("let x = 0; loop { x = x + 1; if (x == 5) { break; } }; x", "5"),
+        ("let x = 1; let f = fn() { x = 2; }; f(); x", "2"),
+        ("let x = 1; let f = fn() { let x = 5; x = 6; }; f(); x", "1"),
+        ("let x = 1; let f = fn() { fn() { x = x + 10; } }; f()(); f()(); x", "21"),
+        (
+            "let counter = fn() { let n = 0; fn() { n = n + 1; n } }; let c = counter(); c(); c(); c()",
+            "3",
+        ),
+        (
+            "let counter = fn() { let n = 0; fn() { n = n + 1; n } }; let a = counter(); let b = counter(); a(); a(); b()",
+            "1",
+        ),
+        ("let x = 1; let f = fn() { x }; x = 2; f()", "2"),
+        ("let arr = [1, 2]; let f = fn() { arr[0] = 9; }; f(); arr", "[9, 2]"),
+        ("let sum = 0; for (i in [1, 2, 3]) { sum = sum + i; } sum", "6"),
+        ("let g = fn f() { 1 }; typeof f", "identifier not found: f"),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let result = match evaluated.as_any().downcast_ref::<Error>() {
+            Some(error) => error.message.clone(),
+            None => evaluated.inspect(),
+        };
+        assert_eq!(result, expected, "wrong result for {}", input);
+    }
+
+    let tests = vec![
+        ("y = 1", "identifier not found: y"),
+        ("let f = fn() { y = 1; }; f()", "identifier not found: y"),
+        ("const x = 1; x = 2", "cannot reassign const binding 'x'"),
+        (
+            "const x = 1; let f = fn() { x = 2; }; f()",
+            "cannot reassign const binding 'x'",
+        ),
+        (
+            "let x = 1; x = 1 + true",
+            "type mismatch: INTEGER + BOOLEAN",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated
+            .as_any()
+            .downcast_ref::<Error>()
+            .unwrap_or_else(|| panic!("no error object returned for {}", input));
+        assert_eq!(error.message, expected);
+    }
+
+    let evaluated = test_eval("const x = 1; let f = fn() { let x = 5; x = 6; x }; f()");
+    assert_eq!(evaluated.inspect(), "6");
+}
+
+#[test]
+fn test_evaluator_keeps_bindings_between_programs() {
+    let mut evaluator = Evaluator::new();
+
+    let first = Program::try_from("let x = 5; let double = fn(n) { n * 2 };").unwrap();
+    evaluator.eval_program(&first);
+
+    let second = Program::try_from("double(x)").unwrap();
+    test_integer_object(evaluator.eval_program(&second).as_ref(), 10);
+
+    assert!(evaluator.environment().get("x").is_some());
+
+    let mut env = Environment::new();
+    let program = Program::try_from("let y = [1, 2].map(fn(n) { n + 1 });").unwrap();
+    eval(&program, &mut env);
+    assert_eq!(env.get("y").unwrap().inspect(), "[2, 3]");
+}
+
+/// Runs `f` on a thread with the stack the default depth limits need
+fn with_large_stack(f: impl FnOnce() + Send + 'static) {
+    std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(f)
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+fn assert_depth_error(evaluated: &dyn Object) {
+    let error = evaluated
+        .as_any()
+        .downcast_ref::<Error>()
+        .expect("no error object returned");
+    assert_eq!(error.message, "maximum recursion depth exceeded");
+}
+
+#[test]
+fn test_call_depth_limit() {
+    with_large_stack(|| {
+        let input = "let f = fn(n) { if (n == 0) { 0 } else { 1 + f(n - 1) } };";
+        let program = Program::try_from(input).unwrap();
+
+        let mut evaluator = Evaluator::new();
+        evaluator.eval_program(&program);
+        let call = Program::try_from("f(190)").unwrap();
+        test_integer_object(evaluator.eval_program(&call).as_ref(), 190);
+        let call = Program::try_from("f(999)").unwrap();
+        test_integer_object(evaluator.eval_program(&call).as_ref(), 999);
+
+        let call = Program::try_from("f(5000)").unwrap();
+        assert_depth_error(evaluator.eval_program(&call).as_ref());
+
+        // the failed call unwound completely, so the next call starts from zero
+        let call = Program::try_from("f(100)").unwrap();
+        test_integer_object(evaluator.eval_program(&call).as_ref(), 100);
+
+        let evaluated = test_eval("let g = fn(n) { map([n], fn(x) { g(x + 1) }) }; g(0)");
+        assert_depth_error(evaluated.as_ref());
+
+        // expressions nested around each call use stack too
+        let evaluated =
+            test_eval("let f = fn(n) { let a = [1, [2, [3, 1 + f(n - 1)]]]; a }; f(5000)");
+        assert_depth_error(evaluated.as_ref());
+
+        let evaluated = test_eval("let f = fn(n) { {\"a\": [n, -(-(-f(n - 1)))]} }; f(5000)");
+        assert_depth_error(evaluated.as_ref());
+    });
+
+    let input = "let f = fn(n) { if (n == 0) { 0 } else { 1 + f(n - 1) } };";
+    let program = Program::try_from(input).unwrap();
+
+    let mut evaluator = Evaluator::with_max_depth(5);
+    evaluator.eval_program(&program);
+    let call = Program::try_from("f(4)").unwrap();
+    test_integer_object(evaluator.eval_program(&call).as_ref(), 4);
+    let call = Program::try_from("f(5)").unwrap();
+    let evaluated = evaluator.eval_program(&call);
+    assert!(evaluated.as_any().is::<Error>());
+
+    evaluator.set_max_call_depth(20);
+    test_integer_object(evaluator.eval_program(&call).as_ref(), 5);
+
+    evaluator.set_max_eval_depth(3);
+    let call = Program::try_from("1 + (2 + 3)").unwrap();
+    test_integer_object(evaluator.eval_program(&call).as_ref(), 6);
+    let call = Program::try_from("1 + (2 + (3 + 4))").unwrap();
+    assert_depth_error(evaluator.eval_program(&call).as_ref());
+}
+
+#[test]
+fn test_tail_calls() {
+    let tests = vec![
+        (
+            "let count = fn(n, acc) { if (n == 0) { return acc; } return count(n - 1, acc + 1); }; count(100000, 0)",
+            100000,
+        ),
+        (
+            "let sum = fn(n, acc) { if (n == 0) { return acc; } return sum(n - 1, acc + n); }; sum(100000, 0)",
+            5000050000,
+        ),
+        // mutual recursion only ever occupies one frame at a time
+        (
+            "let even = fn(n) { if (n == 0) { return 1; } return odd(n - 1); }; let odd = fn(n) { if (n == 0) { return 0; } return even(n - 1); }; even(100001)",
+            0,
+        ),
+        ("let f = fn(x) { return len(x); }; f([1, 2, 3])", 3),
+    ];
+
+    for (input, expected) in tests {
+        test_integer_object(test_eval(input).as_ref(), expected);
+    }
+
+    let evaluated = test_eval("let f = fn() { return 5(); }; f()");
+    let error = evaluated.as_any().downcast_ref::<Error>().unwrap();
+    assert_eq!(error.message, "not a function: INTEGER");
+}
+
+#[test]
+#[ignore = "runs a million calls, about 14 s in a debug build"]
+fn test_tail_calls_million() {
+    let input = "let count = fn(n, acc) { if (n == 0) { return acc; } return count(n - 1, acc + 1); }; count(1000000, 0)";
+    test_integer_object(test_eval(input).as_ref(), 1000000);
+}
+
+#[test]
+fn test_error_stack_trace() {
+    let input = "
+fn inner() {
+    1 + true
+}
+fn outer() {
+    inner()
+}
+outer()";
+
+    let evaluated = test_eval(input);
+    let error = evaluated
+        .as_any()
+        .downcast_ref::<Error>()
+        .expect("no error object returned");
+    assert_eq!(error.message, "type mismatch: INTEGER + BOOLEAN");
+    assert_eq!(
+        error.stack_trace(),
+        ["in function inner at line 2", "in function outer at line 5"]
+    );
+
+    let evaluated = test_eval("1 + true");
+    let error = evaluated.as_any().downcast_ref::<Error>().unwrap();
+    assert!(error.stack_trace().is_empty());
+
+    // functions without a declared name are named after what called them
+    let tests = vec![
+        (
+            "let inner = fn() { 1 + true }; let outer = fn() { inner() }; outer()",
+            vec!["in function inner at line 1", "in function outer at line 1"],
+        ),
+        (
+            "let f = fn() { return g(); }; let g = fn() { 1 + true }; f()",
+            vec!["in function g at line 1"],
+        ),
+        (
+            "let f = memoize(fn(x) { 1 + true }); f(1)",
+            vec!["in function f at line 1"],
+        ),
+        (
+            "map([1], fn(x) { 1 + true })",
+            vec!["in function at line 1"],
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let evaluated = test_eval(input);
+        let error = evaluated.as_any().downcast_ref::<Error>().unwrap();
+        assert_eq!(
+            error.stack_trace(),
+            expected,
+            "wrong stack trace for {}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_object_equality() {
+    let equal: Vec<(Box<dyn Object>, Box<dyn Object>)> = vec![
+        (Box::new(Null::new()), Box::new(Null::new())),
+        (Box::new(Integer::new(1)), Box::new(Integer::new(1))),
+        (
+            Box::new(Error::new("boom".to_string())),
+            test_eval(r#"error("boom")"#),
+        ),
+        (test_eval("[1, [true]]"), test_eval("[1, [true]]")),
+        (test_eval(r#"{"a": 1}"#), test_eval(r#"{"a": 1}"#)),
+    ];
+    for (left, right) in equal {
+        assert_eq!(left, right);
+    }
+
+    let unequal: Vec<(Box<dyn Object>, Box<dyn Object>)> = vec![
+        (Box::new(Integer::new(1)), Box::new(FloatObj::new(1.0))),
+        (
+            Box::new(Error::new("boom".to_string())),
+            Box::new(Error::new("bang".to_string())),
+        ),
+        (test_eval("[1, 2]"), test_eval("[1, 2, 3]")),
+        (test_eval(r#"{"a": 1}"#), test_eval(r#"{"a": 2}"#)),
+        (test_eval("fn(x) { x }"), test_eval("fn(x) { x }")),
+        (test_eval("len"), test_eval("len")),
+    ];
+    for (left, right) in unequal {
+        assert_ne!(left, right);
+    }
+
+    let result = test_eval("let f = fn(x) { x }; [f, f]");
+    let array = result.as_any().downcast_ref::<Array>().unwrap();
+    assert_eq!(array.elements[0], array.elements[1]);
+}
+
+#[test]
+fn test_numeric_separators() {
+    let tests = vec![
+        ("1_000_000", 1_000_000),
+        ("1_0_0", 100),
+        ("0xFF_00", 65280),
+        ("0b1111_0000", 240),
+        ("1__2_", 12),
+        ("let _five = 3; _five + 1_0", 13),
+    ];
+
+    for (input, expected) in tests {
+        test_integer_object(test_eval(input).as_ref(), expected);
+    }
+
+    test_float_object(test_eval("1_000.000_5").as_ref(), 1000.0005);
+    assert!(Program::try_from("0x_").is_err());
+}
+
+#[test]
+fn test_null_literal() {
+    test_null_object(test_eval("null").as_ref());
+    test_null_object(test_eval("let x = null; x").as_ref());
+    test_boolean_object(test_eval("null == null").as_ref(), true);
+    test_boolean_object(test_eval("null != null").as_ref(), false);
+    test_boolean_object(test_eval("!null").as_ref(), true);
+}
@@ -1,5 +1,5 @@
-use ruskey::lexer::Lexer;
-use ruskey::token::TokenType;
+use ruskey::lexer::{tokenize, Lexer};
+use ruskey::token::{Token, TokenType};
 
 #[test]
 fn test_next_token_complex() {
@@ -93,3 +93,604 @@ fn test_string_token() {
         );
     }
 }
+
+#[test]
+fn test_bracket_tokens() {
+    let input = "[1, 2];";
+
+    let mut lexer = Lexer::new(input.to_string());
+
+    let tokens = vec![
+        (TokenType::Lbracket, "["),
+        (TokenType::Int, "1"),
+        (TokenType::Comma, ","),
+        (TokenType::Int, "2"),
+        (TokenType::Rbracket, "]"),
+        (TokenType::Semicolon, ";"),
+        (TokenType::Eof, ""),
+    ];
+
+    for (expected_type, expected_literal) in tokens {
+        let tok = lexer.next_token();
+        assert_eq!(
+            tok.token_type, expected_type,
+            "token type wrong. expected={:?}, got={:?}",
+            expected_type, tok.token_type
+        );
+        assert_eq!(
+            tok.literal, expected_literal,
+            "token literal wrong. expected={}, got={}",
+            expected_literal, tok.literal
+        );
+    }
+}
+
+#[test]
+fn test_float_tokens() {
+    let tests = vec![
+        (
+            "3.14",
+            vec![(TokenType::Float, "3.14"), (TokenType::Eof, "")],
+        ),
+        (
+            "10.0;",
+            vec![(TokenType::Float, "10.0"), (TokenType::Semicolon, ";")],
+        ),
+        ("3.", vec![(TokenType::Illegal, "3."), (TokenType::Eof, "")]),
+        (
+            "3.0e2",
+            vec![
+                (TokenType::Float, "3.0"),
+                (TokenType::Ident, "e"),
+                (TokenType::Int, "2"),
+                (TokenType::Eof, ""),
+            ],
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let mut lexer = Lexer::new(input.to_string());
+
+        for (expected_type, expected_literal) in expected {
+            let tok = lexer.next_token();
+            assert_eq!(
+                tok.token_type, expected_type,
+                "input {:?} - token type wrong. expected={:?}, got={:?}",
+                input, expected_type, tok.token_type
+            );
+            assert_eq!(
+                tok.literal, expected_literal,
+                "input {:?} - token literal wrong. expected={}, got={}",
+                input, expected_literal, tok.literal
+            );
+        }
+    }
+}
+
+#[test]
+fn test_power_token() {
+    let input = "2 ** 3 * 4";
+
+    let mut lexer = Lexer::new(input.to_string());
+
+    let tokens = vec![
+        (TokenType::Int, "2"),
+        (TokenType::Power, "**"),
+        (TokenType::Int, "3"),
+        (TokenType::Asterisk, "*"),
+        (TokenType::Int, "4"),
+        (TokenType::Eof, ""),
+    ];
+
+    for (expected_type, expected_literal) in tokens {
+        let tok = lexer.next_token();
+        assert_eq!(tok.token_type, expected_type);
+        assert_eq!(tok.literal, expected_literal);
+    }
+}
+
+#[test]
+fn test_comparison_tokens() {
+    let input = "a <= b >= c < d > e";
+
+    let mut lexer = Lexer::new(input.to_string());
+
+    let tokens = vec![
+        (TokenType::Ident, "a"),
+        (TokenType::Lte, "<="),
+        (TokenType::Ident, "b"),
+        (TokenType::Gte, ">="),
+        (TokenType::Ident, "c"),
+        (TokenType::Lt, "<"),
+        (TokenType::Ident, "d"),
+        (TokenType::Gt, ">"),
+        (TokenType::Ident, "e"),
+        (TokenType::Eof, ""),
+    ];
+
+    for (expected_type, expected_literal) in tokens {
+        let tok = lexer.next_token();
+        assert_eq!(tok.token_type, expected_type);
+        assert_eq!(tok.literal, expected_literal);
+    }
+}
+
+#[test]
+fn test_logical_tokens() {
+    let input = "a && b || c & d | e ?? f ? g";
+
+    let mut lexer = Lexer::new(input.to_string());
+
+    let tokens = vec![
+        (TokenType::Ident, "a"),
+        (TokenType::And, "&&"),
+        (TokenType::Ident, "b"),
+        (TokenType::Or, "||"),
+        (TokenType::Ident, "c"),
+        (TokenType::Illegal, "&"),
+        (TokenType::Ident, "d"),
+        (TokenType::Illegal, "|"),
+        (TokenType::Ident, "e"),
+        (TokenType::NullCoalesce, "??"),
+        (TokenType::Ident, "f"),
+        (TokenType::Illegal, "?"),
+        (TokenType::Ident, "g"),
+        (TokenType::Eof, ""),
+    ];
+
+    for (expected_type, expected_literal) in tokens {
+        let tok = lexer.next_token();
+        assert_eq!(tok.token_type, expected_type);
+        assert_eq!(tok.literal, expected_literal);
+    }
+}
+
+#[test]
+fn test_label_tokens() {
+    let input = "'outer: break 'outer; ' x";
+
+    let mut lexer = Lexer::new(input.to_string());
+
+    let tokens = vec![
+        (TokenType::Label, "outer"),
+        (TokenType::Colon, ":"),
+        (TokenType::Break, "break"),
+        (TokenType::Label, "outer"),
+        (TokenType::Semicolon, ";"),
+        (TokenType::Illegal, "'"),
+        (TokenType::Ident, "x"),
+        (TokenType::Eof, ""),
+    ];
+
+    for (expected_type, expected_literal) in tokens {
+        let tok = lexer.next_token();
+        assert_eq!(tok.token_type, expected_type);
+        assert_eq!(tok.literal, expected_literal);
+    }
+}
+
+#[test]
+fn test_comments() {
+    let tests = vec![
+        ("/**/ 1", vec![(TokenType::Int, "1"), (TokenType::Eof, "")]),
+        (
+            "1 /* a comment */ + 2",
+            vec![
+                (TokenType::Int, "1"),
+                (TokenType::Plus, "+"),
+                (TokenType::Int, "2"),
+                (TokenType::Eof, ""),
+            ],
+        ),
+        (
+            "/* outer /* inner */ still outer */ x",
+            vec![(TokenType::Ident, "x"), (TokenType::Eof, "")],
+        ),
+        (
+            "/* one /* two /* three */ */ */ x",
+            vec![(TokenType::Ident, "x"), (TokenType::Eof, "")],
+        ),
+        (
+            "/* multi\nline\ncomment */ x",
+            vec![(TokenType::Ident, "x"), (TokenType::Eof, "")],
+        ),
+        (
+            "x // trailing comment\ny",
+            vec![
+                (TokenType::Ident, "x"),
+                (TokenType::Ident, "y"),
+                (TokenType::Eof, ""),
+            ],
+        ),
+        ("// only a comment", vec![(TokenType::Eof, "")]),
+        (
+            "8 / 2",
+            vec![(TokenType::Int, "8"), (TokenType::Slash, "/")],
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let mut lexer = Lexer::new(input.to_string());
+
+        for (expected_type, expected_literal) in expected {
+            let tok = lexer.next_token();
+            assert_eq!(
+                tok.token_type, expected_type,
+                "input {:?} - token type wrong. expected={:?}, got={:?}",
+                input, expected_type, tok.token_type
+            );
+            assert_eq!(
+                tok.literal, expected_literal,
+                "input {:?} - token literal wrong. expected={}, got={}",
+                input, expected_literal, tok.literal
+            );
+        }
+    }
+}
+
+#[test]
+fn test_unterminated_block_comment() {
+    let tests = vec!["/* never closed", "/* outer /* inner */ still open", "x /*"];
+
+    for input in tests {
+        let mut lexer = Lexer::new(input.to_string());
+
+        let mut tok = lexer.next_token();
+        while tok.token_type == TokenType::Ident {
+            tok = lexer.next_token();
+        }
+
+        assert_eq!(
+            tok.token_type,
+            TokenType::Illegal,
+            "input {:?} - expected Illegal token, got={:?}",
+            input,
+            tok.token_type
+        );
+        assert_eq!(tok.literal, "/*");
+        assert_eq!(lexer.next_token().token_type, TokenType::Eof);
+    }
+}
+
+#[test]
+fn test_token_positions() {
+    let input = "let x = 5;\n  x + 10;\n\n/* note */ \"hi\"";
+
+    let tests = vec![
+        (TokenType::Let, 1, 1),
+        (TokenType::Ident, 1, 5),
+        (TokenType::Assign, 1, 7),
+        (TokenType::Int, 1, 9),
+        (TokenType::Semicolon, 1, 10),
+        (TokenType::Ident, 2, 3),
+        (TokenType::Plus, 2, 5),
+        (TokenType::Int, 2, 7),
+        (TokenType::Semicolon, 2, 9),
+        (TokenType::String, 4, 12),
+        (TokenType::Eof, 4, 16),
+    ];
+
+    let mut lexer = Lexer::new(input.to_string());
+
+    for (i, (expected_type, expected_line, expected_column)) in tests.into_iter().enumerate() {
+        let tok = lexer.next_token();
+        assert_eq!(
+            tok.token_type, expected_type,
+            "tests[{}] - wrong token type. expected={:?}, got={:?}",
+            i, expected_type, tok.token_type
+        );
+        assert_eq!(
+            (tok.line, tok.column),
+            (expected_line, expected_column),
+            "tests[{}] - wrong position for {:?}",
+            i,
+            tok.token_type
+        );
+    }
+}
+
+#[test]
+fn test_string_escape_sequences() {
+    let tests = vec![
+        (r#""a\nb""#, "a\nb"),
+        (r#""a\tb""#, "a\tb"),
+        (r#""a\rb""#, "a\rb"),
+        (r#""a\0b""#, "a\0b"),
+        (r#""a\\b""#, "a\\b"),
+        (r#""say \"hi\"""#, "say \"hi\""),
+        (r#""héllo\n""#, "héllo\n"),
+    ];
+
+    for (input, expected) in tests {
+        let mut lexer = Lexer::new(input.to_string());
+        let tok = lexer.next_token();
+
+        assert_eq!(
+            tok.token_type,
+            TokenType::String,
+            "input {:?} - token type wrong. got={:?}",
+            input,
+            tok.token_type
+        );
+        assert_eq!(tok.literal, expected, "input {:?} - wrong literal", input);
+        assert!(
+            lexer.errors().is_empty(),
+            "unexpected errors: {:?}",
+            lexer.errors()
+        );
+        assert_eq!(lexer.next_token().token_type, TokenType::Eof);
+    }
+}
+
+#[test]
+fn test_invalid_string_escape() {
+    let mut lexer = Lexer::new(r#"let s = "bad \q escape"; 1"#.to_string());
+
+    let mut tok = lexer.next_token();
+    while tok.token_type != TokenType::Illegal && tok.token_type != TokenType::Eof {
+        tok = lexer.next_token();
+    }
+
+    assert_eq!(tok.token_type, TokenType::Illegal);
+    assert_eq!(
+        lexer.errors(),
+        ["unknown escape sequence \\q at line 1, column 14"]
+    );
+
+    // lexing resumes after the closing quote
+    assert_eq!(lexer.next_token().token_type, TokenType::Semicolon);
+    assert_eq!(lexer.next_token().token_type, TokenType::Int);
+}
+
+#[test]
+fn test_ellipsis_token() {
+    let input = "fn(x, ...rest) . ..";
+
+    let mut lexer = Lexer::new(input.to_string());
+
+    let tokens = vec![
+        (TokenType::Function, "fn"),
+        (TokenType::Lparen, "("),
+        (TokenType::Ident, "x"),
+        (TokenType::Comma, ","),
+        (TokenType::Ellipsis, "..."),
+        (TokenType::Ident, "rest"),
+        (TokenType::Rparen, ")"),
+        (TokenType::Dot, "."),
+        (TokenType::Dot, "."),
+        (TokenType::Dot, "."),
+        (TokenType::Eof, ""),
+    ];
+
+    for (expected_type, expected_literal) in tokens {
+        let tok = lexer.next_token();
+        assert_eq!(tok.token_type, expected_type);
+        assert_eq!(tok.literal, expected_literal);
+    }
+}
+
+#[test]
+fn test_arrow_token() {
+    let input = "x => y == z = w";
+
+    let mut lexer = Lexer::new(input.to_string());
+
+    let tokens = vec![
+        (TokenType::Ident, "x"),
+        (TokenType::Arrow, "=>"),
+        (TokenType::Ident, "y"),
+        (TokenType::Eq, "=="),
+        (TokenType::Ident, "z"),
+        (TokenType::Assign, "="),
+        (TokenType::Ident, "w"),
+        (TokenType::Eof, ""),
+    ];
+
+    for (expected_type, expected_literal) in tokens {
+        let tok = lexer.next_token();
+        assert_eq!(tok.token_type, expected_type);
+        assert_eq!(tok.literal, expected_literal);
+    }
+}
+
+#[test]
+fn test_lexer_iterator() {
+    let input = "let five = 5; add(five, ten);";
+
+    let tokens: Vec<Token> = Lexer::new(input.to_string()).collect();
+    let kinds: Vec<(TokenType, &str)> = tokens
+        .iter()
+        .map(|t| (t.token_type.clone(), t.literal.as_str()))
+        .collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            (TokenType::Let, "let"),
+            (TokenType::Ident, "five"),
+            (TokenType::Assign, "="),
+            (TokenType::Int, "5"),
+            (TokenType::Semicolon, ";"),
+            (TokenType::Ident, "add"),
+            (TokenType::Lparen, "("),
+            (TokenType::Ident, "five"),
+            (TokenType::Comma, ","),
+            (TokenType::Ident, "ten"),
+            (TokenType::Rparen, ")"),
+            (TokenType::Semicolon, ";"),
+        ]
+    );
+
+    let lexer = Lexer::new(input.to_string());
+    assert_eq!(
+        lexer.filter(|t| t.token_type == TokenType::Ident).count(),
+        4
+    );
+
+    let mut lexer = Lexer::new(String::new());
+    assert!(lexer.next().is_none());
+    assert!(lexer.next().is_none());
+}
+
+#[test]
+fn test_tokenize_all() {
+    let input = r#"let add = fn(x, y) { x + y; };
+if (add(1, 2) >= 3) { return "yes"; }"#;
+
+    let expected = vec![
+        (TokenType::Let, "let"),
+        (TokenType::Ident, "add"),
+        (TokenType::Assign, "="),
+        (TokenType::Function, "fn"),
+        (TokenType::Lparen, "("),
+        (TokenType::Ident, "x"),
+        (TokenType::Comma, ","),
+        (TokenType::Ident, "y"),
+        (TokenType::Rparen, ")"),
+        (TokenType::Lbrace, "{"),
+        (TokenType::Ident, "x"),
+        (TokenType::Plus, "+"),
+        (TokenType::Ident, "y"),
+        (TokenType::Semicolon, ";"),
+        (TokenType::Rbrace, "}"),
+        (TokenType::Semicolon, ";"),
+        (TokenType::If, "if"),
+        (TokenType::Lparen, "("),
+        (TokenType::Ident, "add"),
+        (TokenType::Lparen, "("),
+        (TokenType::Int, "1"),
+        (TokenType::Comma, ","),
+        (TokenType::Int, "2"),
+        (TokenType::Rparen, ")"),
+        (TokenType::Gte, ">="),
+        (TokenType::Int, "3"),
+        (TokenType::Rparen, ")"),
+        (TokenType::Lbrace, "{"),
+        (TokenType::Return, "return"),
+        (TokenType::String, "yes"),
+        (TokenType::Semicolon, ";"),
+        (TokenType::Rbrace, "}"),
+        (TokenType::Eof, ""),
+    ];
+
+    let tokens = Lexer::new(input.to_string()).tokenize_all();
+    let kinds: Vec<(TokenType, &str)> = tokens
+        .iter()
+        .map(|t| (t.token_type.clone(), t.literal.as_str()))
+        .collect();
+    assert_eq!(kinds, expected);
+
+    assert_eq!(tokenize(input).len(), tokens.len());
+    assert_eq!(tokens[16].line, 2);
+
+    let tokens = tokenize("");
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].token_type, TokenType::Eof);
+}
+
+#[test]
+fn test_unicode_identifiers() {
+    let input = "let café = \"naïve\"; 名前 + _π;";
+
+    let expected = vec![
+        (TokenType::Let, "let", 1),
+        (TokenType::Ident, "café", 5),
+        (TokenType::Assign, "=", 10),
+        (TokenType::String, "naïve", 12),
+        (TokenType::Semicolon, ";", 19),
+        (TokenType::Ident, "名前", 21),
+        (TokenType::Plus, "+", 24),
+        (TokenType::Ident, "_π", 26),
+        (TokenType::Semicolon, ";", 28),
+        (TokenType::Eof, "", 29),
+    ];
+
+    let tokens = tokenize(input);
+    assert_eq!(tokens.len(), expected.len());
+    for (tok, (token_type, literal, column)) in tokens.iter().zip(expected) {
+        assert_eq!(tok.token_type, token_type);
+        assert_eq!(tok.literal, literal);
+        assert_eq!(tok.column, column, "column of {:?}", literal);
+    }
+}
+
+#[test]
+fn test_hex_literals() {
+    let tests = vec![
+        ("0xFF", TokenType::Int, "0xFF"),
+        ("0X1f", TokenType::Int, "0X1f"),
+        ("0x0", TokenType::Int, "0x0"),
+        ("0", TokenType::Int, "0"),
+    ];
+
+    for (input, token_type, literal) in tests {
+        let tokens = tokenize(input);
+        assert_eq!(tokens.len(), 2, "input: {}", input);
+        assert_eq!(tokens[0].token_type, token_type);
+        assert_eq!(tokens[0].literal, literal);
+    }
+
+    let mut lexer = Lexer::new("0x + 1".to_string());
+    let tok = lexer.next_token();
+    assert_eq!(tok.token_type, TokenType::Illegal);
+    assert_eq!(tok.literal, "0x");
+    assert_eq!(
+        lexer.errors(),
+        ["hexadecimal literal 0x has no digits at line 1, column 1"]
+    );
+    assert_eq!(lexer.next_token().token_type, TokenType::Plus);
+}
+
+#[test]
+fn test_binary_literals() {
+    let tests = vec![
+        ("0b1010", TokenType::Int, "0b1010"),
+        ("0B1", TokenType::Int, "0B1"),
+        ("0b0", TokenType::Int, "0b0"),
+        ("0b", TokenType::Illegal, "0b"),
+        ("0b2", TokenType::Illegal, "0b2"),
+        ("0b102", TokenType::Illegal, "0b102"),
+        ("0xFG", TokenType::Illegal, "0xFG"),
+    ];
+
+    for (input, token_type, literal) in tests {
+        let tokens = tokenize(input);
+        assert_eq!(tokens.len(), 2, "input: {}", input);
+        assert_eq!(tokens[0].token_type, token_type, "input: {}", input);
+        assert_eq!(tokens[0].literal, literal);
+    }
+
+    let mut lexer = Lexer::new("let n = 0b12;".to_string());
+    let types: Vec<TokenType> = lexer.by_ref().map(|t| t.token_type).collect();
+    assert_eq!(
+        types,
+        vec![
+            TokenType::Let,
+            TokenType::Ident,
+            TokenType::Assign,
+            TokenType::Illegal,
+            TokenType::Semicolon,
+        ]
+    );
+    assert_eq!(
+        lexer.errors(),
+        ["invalid digit '2' in binary literal 0b12 at line 1, column 9"]
+    );
+}
+
+#[test]
+fn test_numeric_separators() {
+    let tests = vec![
+        ("1_000", TokenType::Int, "1_000"),
+        ("0xFF_00", TokenType::Int, "0xFF_00"),
+        ("1_0.5", TokenType::Float, "1_0.5"),
+        ("_five", TokenType::Ident, "_five"),
+        ("_", TokenType::Ident, "_"),
+    ];
+
+    for (input, token_type, literal) in tests {
+        let tokens = tokenize(input);
+        assert_eq!(tokens[0].token_type, token_type, "input: {}", input);
+        assert_eq!(tokens[0].literal, literal);
+    }
+}
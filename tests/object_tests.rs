@@ -1,4 +1,4 @@
-use ruskey::object::{Boolean, Integer, Null, Object, ObjectType};
+use ruskey::object::{Array, Boolean, Error, Integer, Null, Object, ObjectType, StringObj};
 
 #[test]
 fn test_object_types() {
@@ -17,3 +17,18 @@ fn test_object_types() {
     assert_eq!(null.type_(), ObjectType::Null);
     assert_eq!(null.inspect(), "null");
 }
+
+#[test]
+fn test_object_display() {
+    assert_eq!(format!("{}", Integer::new(42)), "42");
+    assert_eq!(format!("{}", Null::new()), "null");
+    assert_eq!(format!("{}", Boolean::new(false)), "false");
+    assert_eq!(format!("{}", Error::new("boom".to_string())), "ERROR: boom");
+
+    let array: Box<dyn Object> = Box::new(Array::new(vec![
+        Box::new(Integer::new(1)),
+        Box::new(StringObj::new("two".to_string())),
+    ]));
+    assert_eq!(format!("{}", array), array.inspect());
+    assert_eq!(array.to_string(), "[1, two]");
+}
@@ -1,10 +1,17 @@
 use ruskey::ast::{
-    Boolean, CallExpression, Expression, ExpressionStatement, FunctionLiteral, Identifier,
-    IfExpression, InfixExpression, IntegerLiteral, LetStatement, Node, PrefixExpression,
-    ReturnStatement, Statement, StringLiteral,
+    ArrayDestructurePattern, ArrayLiteral, AssignStatement, AstNode, Boolean, BreakStatement,
+    CallExpression, ConstStatement, ConstantFoldingTransformer, ContinueStatement,
+    DestructureStatement, DoWhileStatement, Expression, ExpressionStatement, FloatLiteral,
+    ForStatement, FunctionLiteral, HashDestructurePattern, HashLiteral, Identifier,
+    IdentityTransformer, IfExpression, IndexAssignExpression, IndexExpression, InfixExpression,
+    IntegerLiteral, LabeledStatement, LetStatement, LoopStatement, MatchExpression,
+    MethodCallExpression, Node, PrefixExpression, PrintVisitor, Program, ReturnStatement, Span,
+    SpreadExpression, Statement, StringLiteral, Transformer, TryCatchExpression, Visitor,
+    WhileLetStatement, WhileStatement, WildcardExpression,
 };
-use ruskey::lexer::Lexer;
+use ruskey::lexer::{tokenize, Lexer};
 use ruskey::parser::Parser;
+use ruskey::token::{Token, TokenType};
 
 #[test]
 fn test_let_statements() {
@@ -13,10 +20,7 @@ let x = 5;
 let y = 10; 
 let foobar = 838383;
 "#;
-    let l = Lexer::new(input.to_string());
-    let mut p = Parser::new(l);
-    let program = p.parse_program();
-    check_parser_errors(&p);
+    let program = Program::try_from(input).unwrap();
 
     assert_eq!(
         program.statements.len(),
@@ -25,25 +29,13 @@ let foobar = 838383;
         program.statements.len()
     );
 
-    let tests = vec!["x", "y", "foobar"];
+    let tests = ["x", "y", "foobar"];
 
     for (i, expected_identifier) in tests.iter().enumerate() {
         test_let_statement(&*program.statements[i], expected_identifier);
     }
 }
 
-fn check_parser_errors(parser: &Parser) {
-    let errors = parser.errors();
-    if errors.is_empty() {
-        return;
-    }
-    println!("parser has {} errors", errors.len());
-    for error in errors {
-        println!("parser error: {}", error);
-    }
-    panic!("parser had errors");
-}
-
 fn test_let_statement(stmt: &dyn Statement, name: &str) {
     let let_stmt = stmt
         .as_any()
@@ -81,10 +73,7 @@ return 10;
 return 993322;
 "#;
 
-    let l = Lexer::new(input.to_string());
-    let mut p = Parser::new(l);
-    let program = p.parse_program();
-    check_parser_errors(&p);
+    let program = Program::try_from(input).unwrap();
 
     assert_eq!(
         program.statements.len(),
@@ -130,10 +119,7 @@ fn test_parsing_prefix_expressions() {
     ];
 
     for test in prefix_tests {
-        let l = Lexer::new(test.input.to_string());
-        let mut p = Parser::new(l);
-        let program = p.parse_program();
-        check_parser_errors(&p);
+        let program = Program::try_from(test.input).unwrap();
 
         assert_eq!(
             program.statements.len(),
@@ -175,7 +161,7 @@ fn test_parsing_prefix_expressions() {
     }
 }
 
-fn test_integer_literal(il: &Box<dyn Expression>, value: i64) {
+fn test_integer_literal(il: &dyn Expression, value: i64) {
     let int_lit = il
         .as_any()
         .downcast_ref::<IntegerLiteral>()
@@ -230,6 +216,12 @@ fn test_parsing_infix_expressions() {
             operator: "/",
             right_value: 5,
         },
+        InfixTest {
+            input: "5 % 5;",
+            left_value: 5,
+            operator: "%",
+            right_value: 5,
+        },
         InfixTest {
             input: "5 > 5;",
             left_value: 5,
@@ -242,6 +234,18 @@ fn test_parsing_infix_expressions() {
             operator: "<",
             right_value: 5,
         },
+        InfixTest {
+            input: "5 >= 5;",
+            left_value: 5,
+            operator: ">=",
+            right_value: 5,
+        },
+        InfixTest {
+            input: "5 <= 5;",
+            left_value: 5,
+            operator: "<=",
+            right_value: 5,
+        },
         InfixTest {
             input: "5 == 5;",
             left_value: 5,
@@ -257,10 +261,7 @@ fn test_parsing_infix_expressions() {
     ];
 
     for test in infix_tests {
-        let l = Lexer::new(test.input.to_string());
-        let mut p = Parser::new(l);
-        let program = p.parse_program();
-        check_parser_errors(&p);
+        let program = Program::try_from(test.input).unwrap();
 
         assert_eq!(
             program.statements.len(),
@@ -277,7 +278,7 @@ fn test_parsing_infix_expressions() {
 
         // We'll need to implement test_infix_expression after we create the InfixExpression struct
         test_infix_expression(
-            &stmt.expression,
+            stmt.expression.as_ref(),
             test.left_value,
             test.operator,
             test.right_value,
@@ -294,11 +295,27 @@ fn test_operator_precedence_parsing() {
         ("a + b - c", "((a + b) - c)"),
         ("a * b * c", "((a * b) * c)"),
         ("a * b / c", "((a * b) / c)"),
+        ("a + b % c", "(a + (b % c))"),
+        ("a * b % c", "((a * b) % c)"),
+        ("-a % b", "((-a) % b)"),
+        ("a ** b ** c", "(a ** (b ** c))"),
+        ("a * b ** c", "(a * (b ** c))"),
+        ("a ** b * c", "((a ** b) * c)"),
+        ("-a ** b", "((-a) ** b)"),
         ("a + b / c", "(a + (b / c))"),
         ("a + b * c + d / e - f", "(((a + (b * c)) + (d / e)) - f)"),
         ("3 + 4; -5 * 5", "(3 + 4)((-5) * 5)"),
         ("5 > 4 == 3 < 4", "((5 > 4) == (3 < 4))"),
         ("5 < 4 != 3 > 4", "((5 < 4) != (3 > 4))"),
+        ("5 >= 4 == 3 <= 4", "((5 >= 4) == (3 <= 4))"),
+        ("a + b <= c * d", "((a + b) <= (c * d))"),
+        ("a || b && c", "(a || (b && c))"),
+        ("a && b || c", "((a && b) || c)"),
+        ("a == b && c != d", "((a == b) && (c != d))"),
+        ("!a || b < c", "((!a) || (b < c))"),
+        ("a ?? b == c", "(a ?? (b == c))"),
+        ("a ?? b ?? c", "((a ?? b) ?? c)"),
+        ("a && b ?? c", "(a && (b ?? c))"),
         (
             "3 + 4 * 5 == 3 * 1 + 4 * 5",
             "((3 + (4 * 5)) == ((3 * 1) + (4 * 5)))",
@@ -317,13 +334,22 @@ fn test_operator_precedence_parsing() {
             "add(a + b + c * d / f + g)",
             "add((((a + b) + ((c * d) / f)) + g))",
         ),
+        (
+            "a * [1, 2, 3, 4][b * c] * d",
+            "((a * ([1, 2, 3, 4][(b * c)])) * d)",
+        ),
+        ("typeof a + b", "((typeof a) + b)"),
+        ("typeof -a", "(typeof (-a))"),
+        ("typeof a[0]", "(typeof (a[0]))"),
+        (
+            "add(a * b[2], b[1], 2 * [1, 2][1])",
+            "add((a * (b[2])), (b[1]), (2 * ([1, 2][1])))",
+        ),
+        ("-a[0]", "(-(a[0]))"),
     ];
 
     for (input, expected) in tests {
-        let l = Lexer::new(input.to_string());
-        let mut p = Parser::new(l);
-        let program = p.parse_program();
-        check_parser_errors(&p);
+        let program = Program::try_from(input).unwrap();
 
         let actual = program.to_string();
         assert_eq!(actual, expected, "expected={}, got={}", expected, actual);
@@ -334,10 +360,7 @@ fn test_operator_precedence_parsing() {
 fn test_boolean_expression() {
     let input = "true;";
 
-    let l = Lexer::new(input.to_string());
-    let mut p = Parser::new(l);
-    let program = p.parse_program();
-    check_parser_errors(&p);
+    let program = Program::try_from(input).unwrap();
 
     assert_eq!(
         program.statements.len(),
@@ -357,10 +380,10 @@ fn test_boolean_expression() {
         .downcast_ref::<Boolean>()
         .expect("expression not Boolean");
 
-    assert_eq!(
-        boolean.value, true,
-        "boolean.value not {}. got={}",
-        true, boolean.value
+    assert!(
+        boolean.value,
+        "boolean.value not true. got={}",
+        boolean.value
     );
 }
 
@@ -368,10 +391,7 @@ fn test_boolean_expression() {
 fn test_if_expression() {
     let input = "if (x < y) { x }";
 
-    let l = Lexer::new(input.to_string());
-    let mut p = Parser::new(l);
-    let program = p.parse_program();
-    check_parser_errors(&p);
+    let program = Program::try_from(input).unwrap();
 
     assert_eq!(
         program.statements.len(),
@@ -392,7 +412,7 @@ fn test_if_expression() {
         .expect("expression not IfExpression");
 
     // Test the condition (x < y)
-    test_infix_expression(&if_exp.condition, "x", "<", "y");
+    test_infix_expression(if_exp.condition.as_ref(), "x", "<", "y");
 
     // Test the consequence
     let consequence = &if_exp.consequence;
@@ -408,7 +428,7 @@ fn test_if_expression() {
         .downcast_ref::<ExpressionStatement>()
         .expect("consequence statements[0] is not ExpressionStatement");
 
-    test_identifier(&consequence_stmt.expression, "x");
+    test_identifier(consequence_stmt.expression.as_ref(), "x");
 
     // Test that alternative is None
     assert!(
@@ -423,10 +443,7 @@ fn test_if_expression() {
 fn test_if_else_expression() {
     let input = "if (x < y) { x } else { y }";
 
-    let l = Lexer::new(input.to_string());
-    let mut p = Parser::new(l);
-    let program = p.parse_program();
-    check_parser_errors(&p);
+    let program = Program::try_from(input).unwrap();
 
     let stmt = program.statements[0]
         .as_any()
@@ -440,7 +457,7 @@ fn test_if_else_expression() {
         .expect("expression not IfExpression");
 
     // Test the condition
-    test_infix_expression(&if_exp.condition, "x", "<", "y");
+    test_infix_expression(if_exp.condition.as_ref(), "x", "<", "y");
 
     // Test the consequence
     let consequence = &if_exp.consequence;
@@ -456,7 +473,7 @@ fn test_if_else_expression() {
         .downcast_ref::<ExpressionStatement>()
         .expect("consequence statements[0] is not ExpressionStatement");
 
-    test_identifier(&consequence_stmt.expression, "x");
+    test_identifier(consequence_stmt.expression.as_ref(), "x");
 
     // Test the alternative
     let alternative = if_exp
@@ -476,11 +493,11 @@ fn test_if_else_expression() {
         .downcast_ref::<ExpressionStatement>()
         .expect("alternative statements[0] is not ExpressionStatement");
 
-    test_identifier(&alternative_stmt.expression, "y");
+    test_identifier(alternative_stmt.expression.as_ref(), "y");
 }
 
 // Helper function to test identifiers
-fn test_identifier(exp: &Box<dyn Expression>, value: &str) {
+fn test_identifier(exp: &dyn Expression, value: &str) {
     let ident = exp
         .as_any()
         .downcast_ref::<Identifier>()
@@ -502,7 +519,7 @@ fn test_identifier(exp: &Box<dyn Expression>, value: &str) {
 }
 
 fn test_infix_expression(
-    exp: &Box<dyn Expression>,
+    exp: &dyn Expression,
     left: impl Into<Value>,
     operator: &str,
     right: impl Into<Value>,
@@ -512,7 +529,7 @@ fn test_infix_expression(
         .downcast_ref::<InfixExpression>()
         .expect("expression is not InfixExpression");
 
-    test_literal_expression(&op_exp.left, left);
+    test_literal_expression(op_exp.left.as_ref(), left);
 
     assert_eq!(
         op_exp.operator, operator,
@@ -520,7 +537,7 @@ fn test_infix_expression(
         operator, op_exp.operator
     );
 
-    test_literal_expression(&op_exp.right, right);
+    test_literal_expression(op_exp.right.as_ref(), right);
 }
 
 // A helper enum to handle different types of values
@@ -548,7 +565,7 @@ impl From<bool> for Value {
     }
 }
 
-fn test_literal_expression(exp: &Box<dyn Expression>, expected: impl Into<Value>) {
+fn test_literal_expression(exp: &dyn Expression, expected: impl Into<Value>) {
     match expected.into() {
         Value::Int(int) => test_integer_literal(exp, int),
         Value::String(string) => test_identifier(exp, &string),
@@ -556,7 +573,7 @@ fn test_literal_expression(exp: &Box<dyn Expression>, expected: impl Into<Value>
     }
 }
 
-fn test_boolean_literal(exp: &Box<dyn Expression>, value: bool) {
+fn test_boolean_literal(exp: &dyn Expression, value: bool) {
     let bo = exp
         .as_any()
         .downcast_ref::<Boolean>()
@@ -581,10 +598,7 @@ fn test_boolean_literal(exp: &Box<dyn Expression>, value: bool) {
 fn test_function_literal_parsing() {
     let input = "fn(x, y) { x + y; }";
 
-    let l = Lexer::new(input.to_string());
-    let mut p = Parser::new(l);
-    let program = p.parse_program();
-    check_parser_errors(&p);
+    let program = Program::try_from(input).unwrap();
 
     // Check if program has one statement
     assert_eq!(
@@ -632,7 +646,7 @@ fn test_function_literal_parsing() {
         .downcast_ref::<ExpressionStatement>()
         .expect("function body stmt is not ast.ExpressionStatement");
 
-    test_infix_expression(&body_stmt.expression, "x", "+", "y");
+    test_infix_expression(body_stmt.expression.as_ref(), "x", "+", "y");
 }
 
 #[test]
@@ -645,10 +659,7 @@ fn test_function_parameter_parsing() {
     ];
 
     for (input, expected_params) in tests {
-        let l = Lexer::new(input.to_string());
-        let mut p = Parser::new(l);
-        let program = p.parse_program();
-        check_parser_errors(&p);
+        let program = Program::try_from(input).unwrap();
 
         let stmt = program.statements[0]
             .as_any()
@@ -684,10 +695,7 @@ fn test_function_parameter_parsing() {
 fn test_call_expression_parsing() {
     let input = "add(1, 2 * 3, 4 + 5);";
 
-    let l = Lexer::new(input.to_string());
-    let mut p = Parser::new(l);
-    let program = p.parse_program();
-    check_parser_errors(&p);
+    let program = Program::try_from(input).unwrap();
 
     assert_eq!(
         program.statements.len(),
@@ -709,7 +717,7 @@ fn test_call_expression_parsing() {
         .expect("expression is not CallExpression");
 
     // Test the function part (should be an identifier "add")
-    test_identifier(&exp.function, "add");
+    test_identifier(exp.function.as_ref(), "add");
 
     // Test that we have the right number of arguments
     assert_eq!(
@@ -720,19 +728,48 @@ fn test_call_expression_parsing() {
     );
 
     // Test each argument
-    test_literal_expression(&exp.arguments[0], 1);
-    test_infix_expression(&exp.arguments[1], 2, "*", 3);
-    test_infix_expression(&exp.arguments[2], 4, "+", 5);
+    test_literal_expression(exp.arguments[0].as_ref(), 1);
+    test_infix_expression(exp.arguments[1].as_ref(), 2, "*", 3);
+    test_infix_expression(exp.arguments[2].as_ref(), 4, "+", 5);
+}
+
+#[test]
+fn test_spread_arguments() {
+    let program = Program::try_from("f(1, ...xs, ...[2, 3]);").unwrap();
+
+    let stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<ExpressionStatement>()
+        .expect("statement is not ExpressionStatement");
+    let call = stmt
+        .expression
+        .as_any()
+        .downcast_ref::<CallExpression>()
+        .expect("expression is not CallExpression");
+
+    assert_eq!(call.arguments.len(), 3);
+    test_literal_expression(call.arguments[0].as_ref(), 1);
+    let spread = call.arguments[1]
+        .as_any()
+        .downcast_ref::<SpreadExpression>()
+        .expect("argument is not SpreadExpression");
+    test_identifier(spread.value.as_ref(), "xs");
+    assert_eq!(program.to_string(), "f(1, ...xs, ...[2, 3])");
+
+    for input in ["...xs", "[...xs]", "f(...)"] {
+        assert!(
+            Program::try_from(input).is_err(),
+            "{} should not parse",
+            input
+        );
+    }
 }
 
 #[test]
 fn test_string_literal_expression() {
     let input = r#""hello world";"#;
 
-    let lexer = Lexer::new(input.to_string());
-    let mut parser = Parser::new(lexer);
-    let program = parser.parse_program();
-    check_parser_errors(&parser);
+    let program = Program::try_from(input).unwrap();
 
     let stmt = program.statements[0]
         .as_any()
@@ -751,3 +788,1212 @@ fn test_string_literal_expression() {
         literal.value
     );
 }
+
+#[test]
+fn test_parsing_array_literals() {
+    let input = "[1, 2 * 2, 3 + 3]";
+
+    let program = Program::try_from(input).unwrap();
+
+    let stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<ExpressionStatement>()
+        .expect("Expected ExpressionStatement");
+
+    let array = stmt
+        .expression
+        .as_any()
+        .downcast_ref::<ArrayLiteral>()
+        .expect("Expected ArrayLiteral");
+
+    assert_eq!(
+        array.elements.len(),
+        3,
+        "len(array.elements) not 3. got={}",
+        array.elements.len()
+    );
+
+    test_integer_literal(array.elements[0].as_ref(), 1);
+    test_infix_expression(array.elements[1].as_ref(), 2, "*", 2);
+    test_infix_expression(array.elements[2].as_ref(), 3, "+", 3);
+}
+
+#[test]
+fn test_parsing_empty_array_literal() {
+    let input = "[]";
+
+    let program = Program::try_from(input).unwrap();
+
+    let stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<ExpressionStatement>()
+        .expect("Expected ExpressionStatement");
+
+    let array = stmt
+        .expression
+        .as_any()
+        .downcast_ref::<ArrayLiteral>()
+        .expect("Expected ArrayLiteral");
+
+    assert!(
+        array.elements.is_empty(),
+        "array.elements not empty. got={}",
+        array.elements.len()
+    );
+}
+
+#[test]
+fn test_parsing_hash_literals_string_keys() {
+    let input = r#"{"one": 1, "two": 2, "three": 3}"#;
+
+    let program = Program::try_from(input).unwrap();
+
+    let stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<ExpressionStatement>()
+        .expect("Expected ExpressionStatement");
+
+    let hash = stmt
+        .expression
+        .as_any()
+        .downcast_ref::<HashLiteral>()
+        .expect("Expected HashLiteral");
+
+    let expected = [("one", 1), ("two", 2), ("three", 3)];
+    assert_eq!(
+        hash.pairs.len(),
+        expected.len(),
+        "hash.pairs has wrong length. got={}",
+        hash.pairs.len()
+    );
+
+    for ((key, value), (expected_key, expected_value)) in hash.pairs.iter().zip(expected) {
+        let literal = key
+            .as_any()
+            .downcast_ref::<StringLiteral>()
+            .expect("key is not StringLiteral");
+        assert_eq!(literal.value, expected_key);
+        test_integer_literal(value.as_ref(), expected_value);
+    }
+}
+
+#[test]
+fn test_parsing_empty_hash_literal() {
+    let input = "{}";
+
+    let program = Program::try_from(input).unwrap();
+
+    let stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<ExpressionStatement>()
+        .expect("Expected ExpressionStatement");
+
+    let hash = stmt
+        .expression
+        .as_any()
+        .downcast_ref::<HashLiteral>()
+        .expect("Expected HashLiteral");
+
+    assert!(
+        hash.pairs.is_empty(),
+        "hash.pairs has wrong length. got={}",
+        hash.pairs.len()
+    );
+}
+
+#[test]
+fn test_parsing_hash_literals_with_expressions() {
+    let input = r#"{"one": 0 + 1, "two": 10 - 8, "three": 15 / 5}"#;
+
+    let program = Program::try_from(input).unwrap();
+
+    let stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<ExpressionStatement>()
+        .expect("Expected ExpressionStatement");
+
+    let hash = stmt
+        .expression
+        .as_any()
+        .downcast_ref::<HashLiteral>()
+        .expect("Expected HashLiteral");
+
+    assert_eq!(hash.pairs.len(), 3);
+    test_infix_expression(hash.pairs[0].1.as_ref(), 0, "+", 1);
+    test_infix_expression(hash.pairs[1].1.as_ref(), 10, "-", 8);
+    test_infix_expression(hash.pairs[2].1.as_ref(), 15, "/", 5);
+}
+
+#[test]
+fn test_parsing_index_expressions() {
+    let input = "myArray[1 + 1]";
+
+    let program = Program::try_from(input).unwrap();
+
+    let stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<ExpressionStatement>()
+        .expect("Expected ExpressionStatement");
+
+    let index_exp = stmt
+        .expression
+        .as_any()
+        .downcast_ref::<IndexExpression>()
+        .expect("Expected IndexExpression");
+
+    test_identifier(index_exp.left.as_ref(), "myArray");
+    test_infix_expression(index_exp.index.as_ref(), 1, "+", 1);
+}
+
+#[test]
+fn test_float_literal_expression() {
+    let input = "3.25;";
+
+    let program = Program::try_from(input).unwrap();
+
+    let stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<ExpressionStatement>()
+        .expect("Expected ExpressionStatement");
+
+    let literal = stmt
+        .expression
+        .as_any()
+        .downcast_ref::<FloatLiteral>()
+        .expect("Expected FloatLiteral");
+
+    assert_eq!(
+        literal.value, 3.25,
+        "literal.value not 3.25. got={}",
+        literal.value
+    );
+    assert_eq!(literal.token_literal(), "3.25");
+}
+
+#[test]
+fn test_while_statement() {
+    let input = "while (x < 10) { let x = x + 1; }";
+
+    let program = Program::try_from(input).unwrap();
+
+    assert_eq!(
+        program.statements.len(),
+        1,
+        "program.statements does not contain 1 statement. got={}",
+        program.statements.len()
+    );
+
+    let while_stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<WhileStatement>()
+        .expect("statement is not WhileStatement");
+
+    test_infix_expression(while_stmt.condition.as_ref(), "x", "<", 10);
+
+    assert_eq!(
+        while_stmt.body.statements.len(),
+        1,
+        "body is not 1 statement. got={}",
+        while_stmt.body.statements.len()
+    );
+    test_let_statement(while_stmt.body.statements[0].as_ref(), "x");
+}
+
+#[test]
+fn test_for_statement() {
+    let input = "for (x in [1, 2]) { x }";
+
+    let program = Program::try_from(input).unwrap();
+
+    assert_eq!(
+        program.statements.len(),
+        1,
+        "program.statements does not contain 1 statement. got={}",
+        program.statements.len()
+    );
+
+    let for_stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<ForStatement>()
+        .expect("statement is not ForStatement");
+
+    assert_eq!(for_stmt.iterator_name.value, "x");
+
+    let array = for_stmt
+        .iterable
+        .as_any()
+        .downcast_ref::<ArrayLiteral>()
+        .expect("iterable is not ArrayLiteral");
+    assert_eq!(array.elements.len(), 2);
+
+    let body_stmt = for_stmt.body.statements[0]
+        .as_any()
+        .downcast_ref::<ExpressionStatement>()
+        .expect("body statements[0] is not ExpressionStatement");
+    test_identifier(body_stmt.expression.as_ref(), "x");
+}
+
+#[test]
+fn test_break_and_continue_statements() {
+    let input = "while (true) { break; continue }";
+
+    let program = Program::try_from(input).unwrap();
+
+    let while_stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<WhileStatement>()
+        .expect("statement is not WhileStatement");
+
+    assert_eq!(while_stmt.body.statements.len(), 2);
+    assert!(while_stmt.body.statements[0]
+        .as_any()
+        .is::<BreakStatement>());
+    assert!(while_stmt.body.statements[1]
+        .as_any()
+        .is::<ContinueStatement>());
+}
+
+#[test]
+fn test_labeled_statements() {
+    let input = "'outer: for (x in xs) { while (true) { break 'outer; continue 'outer } }";
+
+    let program = Program::try_from(input).unwrap();
+
+    assert_eq!(program.statements.len(), 1);
+
+    let labeled = program.statements[0]
+        .as_any()
+        .downcast_ref::<LabeledStatement>()
+        .expect("statement is not LabeledStatement");
+    assert_eq!(labeled.label, "outer");
+
+    let for_stmt = labeled
+        .body
+        .as_any()
+        .downcast_ref::<ForStatement>()
+        .expect("labeled statement is not ForStatement");
+    let while_stmt = for_stmt.body.statements[0]
+        .as_any()
+        .downcast_ref::<WhileStatement>()
+        .expect("statement is not WhileStatement");
+
+    let break_stmt = while_stmt.body.statements[0]
+        .as_any()
+        .downcast_ref::<BreakStatement>()
+        .expect("statement is not BreakStatement");
+    assert_eq!(break_stmt.label.as_deref(), Some("outer"));
+    let continue_stmt = while_stmt.body.statements[1]
+        .as_any()
+        .downcast_ref::<ContinueStatement>()
+        .expect("statement is not ContinueStatement");
+    assert_eq!(continue_stmt.label.as_deref(), Some("outer"));
+
+    assert_eq!(
+        program.to_string(),
+        "'outer: for (x in xs) whiletrue break 'outer;continue 'outer;"
+    );
+
+    let errors = Program::try_from("'outer: let x = 1;").unwrap_err();
+    assert_eq!(
+        errors[0],
+        "expected a loop after label 'outer, got Let instead at line 1, column 9"
+    );
+    assert!(Program::try_from("'outer while (true) {}").is_err());
+}
+
+#[test]
+fn test_parser_errors_include_position() {
+    let tests = vec![
+        (
+            "let x 5;",
+            "expected next token to be Assign, got Int instead at line 1, column 7",
+        ),
+        (
+            "let a = 1;\nlet = 2;",
+            "expected next token to be Ident, got Assign instead at line 2, column 5",
+        ),
+        (
+            "1 +\n   ;",
+            "no prefix parse function for Semicolon found at line 2, column 4",
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let errors = Program::try_from(input).unwrap_err();
+
+        assert!(
+            errors.iter().any(|e| e == expected),
+            "expected error {:?}, got={:?}",
+            expected,
+            errors
+        );
+    }
+}
+
+#[test]
+fn test_function_literal_clone_keeps_body() {
+    let input = "fn(x) { let y = x * 2; while (y > 0) { let y = y - 1; } for (i in [1]) { break; } return y; }";
+
+    let program = Program::try_from(input).unwrap();
+
+    let stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<ExpressionStatement>()
+        .expect("statement is not ExpressionStatement");
+    let function = stmt
+        .expression
+        .as_any()
+        .downcast_ref::<FunctionLiteral>()
+        .expect("expression is not FunctionLiteral");
+
+    let cloned = function.clone();
+
+    assert_eq!(cloned.body.statements.len(), 4);
+    assert_eq!(cloned.body.statements.len(), function.body.statements.len());
+    for (original, copy) in function
+        .body
+        .statements
+        .iter()
+        .zip(cloned.body.statements.iter())
+    {
+        assert_eq!(original.to_string(), copy.to_string());
+    }
+    assert_eq!(function.to_string(), cloned.to_string());
+}
+
+#[test]
+fn test_node_spans() {
+    let input = "let total = price * 2;\nadd(1, total)\nif (x) { -y }";
+
+    let program = Program::try_from(input).unwrap();
+
+    let let_stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<LetStatement>()
+        .expect("statement is not LetStatement");
+    assert_eq!(let_stmt.span(), Span::new((1, 1), (1, 21)));
+    assert_eq!(let_stmt.name.span(), Span::new((1, 5), (1, 9)));
+    let value = let_stmt.value.as_ref().unwrap();
+    assert_eq!(value.span(), Span::new((1, 13), (1, 21)));
+
+    assert_eq!(program.statements[1].span(), Span::new((2, 1), (2, 12)));
+    assert_eq!(program.statements[2].span(), Span::new((3, 1), (3, 11)));
+    assert_eq!(program.span(), Span::new((1, 1), (3, 11)));
+    assert_eq!(program.statements[1].span().to_string(), "line 2, column 1");
+}
+
+#[test]
+fn test_parse_error_reports_column() {
+    let errors = Program::try_from("let x = 1;\nlet y = (2 + 3;").unwrap_err();
+
+    assert_eq!(
+        errors.first().map(String::as_str),
+        Some("expected next token to be Rparen, got Semicolon instead at line 2, column 15")
+    );
+}
+
+#[test]
+fn test_lexer_errors_are_reported() {
+    let tests = vec![
+        (
+            r#"let s = "a\q";"#,
+            vec!["unknown escape sequence \\q at line 1, column 11"],
+        ),
+        (
+            r#""\q""#,
+            vec!["unknown escape sequence \\q at line 1, column 2"],
+        ),
+        (
+            "let h = 0x;",
+            vec!["hexadecimal literal 0x has no digits at line 1, column 9"],
+        ),
+        (
+            "let b = 0b102;",
+            vec!["invalid digit '2' in binary literal 0b102 at line 1, column 9"],
+        ),
+        (
+            "let h = 0x;\nlet b = 0b2;",
+            vec![
+                "hexadecimal literal 0x has no digits at line 1, column 9",
+                "invalid digit '2' in binary literal 0b2 at line 2, column 9",
+            ],
+        ),
+    ];
+
+    for (input, expected) in tests {
+        let errors = Program::try_from(input).unwrap_err();
+        assert_eq!(errors, expected, "wrong errors for {}", input);
+
+        let mut parser = Parser::new(Lexer::new(input.to_string()));
+        parser.parse_program();
+        assert_eq!(parser.errors(), expected, "wrong errors for {}", input);
+    }
+}
+
+#[test]
+fn test_parser_recovers_after_errors() {
+    let input = "let x 5;\nlet = 10;\nlet 838383;\nlet y = 2;";
+
+    let lexer = Lexer::new(input.to_string());
+    let mut parser = Parser::new(lexer);
+    let program = parser.parse_program();
+
+    assert_eq!(
+        parser.errors(),
+        [
+            "expected next token to be Assign, got Int instead at line 1, column 7",
+            "expected next token to be Ident, got Assign instead at line 2, column 5",
+            "expected next token to be Ident, got Int instead at line 3, column 5",
+        ]
+    );
+
+    // the valid statement after the errors is still parsed
+    let last = program
+        .statements
+        .last()
+        .expect("program has no statements");
+    test_let_statement(last.as_ref(), "y");
+}
+
+#[test]
+fn test_while_let_statement() {
+    let input = "while let x = next(n) { x }";
+
+    let program = Program::try_from(input).unwrap();
+
+    assert_eq!(program.statements.len(), 1);
+
+    let while_let = program.statements[0]
+        .as_any()
+        .downcast_ref::<WhileLetStatement>()
+        .expect("statement is not WhileLetStatement");
+
+    assert_eq!(while_let.pattern.value, "x");
+    assert_eq!(while_let.expression.to_string(), "next(n)");
+    assert_eq!(while_let.body.statements.len(), 1);
+    assert_eq!(program.to_string(), "while let x = next(n) x");
+}
+
+#[test]
+fn test_array_destructure_statement() {
+    let input = "let [a, _, c] = pair();";
+
+    let program = Program::try_from(input).unwrap();
+
+    assert_eq!(program.statements.len(), 1);
+
+    let destructure = program.statements[0]
+        .as_any()
+        .downcast_ref::<DestructureStatement>()
+        .expect("statement is not DestructureStatement");
+    let pattern = destructure
+        .pattern
+        .as_any()
+        .downcast_ref::<ArrayDestructurePattern>()
+        .expect("pattern is not ArrayDestructurePattern");
+
+    let names: Vec<Option<&str>> = pattern
+        .elements
+        .iter()
+        .map(|name| name.as_ref().map(|n| n.value.as_str()))
+        .collect();
+    assert_eq!(names, vec![Some("a"), None, Some("c")]);
+    assert_eq!(destructure.value.to_string(), "pair()");
+    assert_eq!(program.to_string(), "let [a, _, c] = pair();");
+
+    for input in ["let [a, 1] = x;", "let [a b] = x;", "let [a] x;"] {
+        assert!(
+            Program::try_from(input).is_err(),
+            "{} should not parse",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_hash_destructure_statement() {
+    let input = "let {x, y: b} = point;";
+
+    let program = Program::try_from(input).unwrap();
+
+    assert_eq!(program.statements.len(), 1);
+
+    let destructure = program.statements[0]
+        .as_any()
+        .downcast_ref::<DestructureStatement>()
+        .expect("statement is not DestructureStatement");
+    let pattern = destructure
+        .pattern
+        .as_any()
+        .downcast_ref::<HashDestructurePattern>()
+        .expect("pattern is not HashDestructurePattern");
+
+    let pairs: Vec<(&str, &str)> = pattern
+        .pairs
+        .iter()
+        .map(|(key, name)| (key.value.as_str(), name.value.as_str()))
+        .collect();
+    assert_eq!(pairs, vec![("x", "x"), ("y", "b")]);
+    assert_eq!(program.to_string(), "let {x, y: b} = point;");
+
+    for input in ["let {x: 1} = h;", "let {\"x\"} = h;", "let {x y} = h;"] {
+        assert!(
+            Program::try_from(input).is_err(),
+            "{} should not parse",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_named_function_declaration() {
+    let input = "fn add(a, b) { a + b }";
+
+    let program = Program::try_from(input).unwrap();
+
+    assert_eq!(program.statements.len(), 1);
+    test_let_statement(program.statements[0].as_ref(), "add");
+
+    let let_stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<LetStatement>()
+        .unwrap();
+    let function = let_stmt
+        .value
+        .as_ref()
+        .unwrap()
+        .as_any()
+        .downcast_ref::<FunctionLiteral>()
+        .expect("value is not FunctionLiteral");
+
+    assert_eq!(
+        function.name.as_ref().map(|n| n.value.as_str()),
+        Some("add")
+    );
+    assert_eq!(function.parameters.len(), 2);
+    assert_eq!(program.to_string(), "let add = fn add(a, b) (a + b);");
+}
+
+#[test]
+fn test_rest_parameter_parsing() {
+    let tests = vec![
+        ("fn(...args) {};", vec![], Some("args")),
+        ("fn(x, y, ...rest) {};", vec!["x", "y"], Some("rest")),
+        ("fn(x) {};", vec!["x"], None),
+    ];
+
+    for (input, expected_params, expected_rest) in tests {
+        let program = Program::try_from(input).unwrap();
+
+        let stmt = program.statements[0]
+            .as_any()
+            .downcast_ref::<ExpressionStatement>()
+            .unwrap();
+        let function = stmt
+            .expression
+            .as_any()
+            .downcast_ref::<FunctionLiteral>()
+            .expect("expression is not FunctionLiteral");
+
+        let params: Vec<&str> = function
+            .parameters
+            .iter()
+            .map(|p| p.value.as_str())
+            .collect();
+        assert_eq!(params, expected_params, "wrong parameters for {}", input);
+        assert_eq!(
+            function.rest.as_ref().map(|r| r.value.as_str()),
+            expected_rest,
+            "wrong rest parameter for {}",
+            input
+        );
+    }
+
+    let errors = Program::try_from("fn(...rest, x) {}").unwrap_err();
+    assert_eq!(
+        errors,
+        ["rest parameter must be the last parameter at line 1, column 4"]
+    );
+}
+
+#[test]
+fn test_method_call_parsing() {
+    let input = "a.push(1, 2 * 3)";
+
+    let program = Program::try_from(input).unwrap();
+
+    let stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<ExpressionStatement>()
+        .unwrap();
+    let call = stmt
+        .expression
+        .as_any()
+        .downcast_ref::<MethodCallExpression>()
+        .expect("expression is not MethodCallExpression");
+
+    test_identifier(call.object.as_ref(), "a");
+    assert_eq!(call.method.value, "push");
+    assert_eq!(call.arguments.len(), 2);
+    test_integer_literal(call.arguments[0].as_ref(), 1);
+
+    let tests = vec![
+        ("a.len() + 1", "(a.len() + 1)"),
+        ("-a.len()", "(-a.len())"),
+        ("a.trim().upper()", "a.trim().upper()"),
+        ("a[0].len()", "(a[0]).len()"),
+        ("f(x).keys()", "f(x).keys()"),
+    ];
+
+    for (input, expected) in tests {
+        let program = Program::try_from(input).unwrap();
+        assert_eq!(program.to_string(), expected);
+    }
+
+    let errors = Program::try_from("a.len").unwrap_err();
+    assert!(!errors.is_empty());
+}
+
+#[test]
+fn test_index_assignment_parsing() {
+    let input = "arr[1] = 2 + 3;";
+
+    let program = Program::try_from(input).unwrap();
+
+    let stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<ExpressionStatement>()
+        .unwrap();
+    let assign = stmt
+        .expression
+        .as_any()
+        .downcast_ref::<IndexAssignExpression>()
+        .expect("expression is not IndexAssignExpression");
+
+    test_identifier(assign.target.left.as_ref(), "arr");
+    test_integer_literal(assign.target.index.as_ref(), 1);
+    assert_eq!(program.to_string(), "((arr[1]) = (2 + 3))");
+
+    let program = Program::try_from("a[0] = b[0] = 1").unwrap();
+    assert_eq!(program.to_string(), "((a[0]) = ((b[0]) = 1))");
+
+    let errors = Program::try_from("a + 1 = 2").unwrap_err();
+    assert_eq!(
+        errors[0],
+        "invalid assignment target (a + 1) at line 1, column 7"
+    );
+}
+
+#[test]
+fn test_loop_statement() {
+    let input = "loop { let x = x + 1; break; }";
+
+    let program = Program::try_from(input).unwrap();
+
+    assert_eq!(program.statements.len(), 1);
+
+    let loop_stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<LoopStatement>()
+        .expect("statement is not LoopStatement");
+
+    assert_eq!(loop_stmt.body.statements.len(), 2);
+    test_let_statement(loop_stmt.body.statements[0].as_ref(), "x");
+    assert!(loop_stmt.body.statements[1].as_any().is::<BreakStatement>());
+}
+
+#[test]
+fn test_do_while_statement() {
+    let input = "do { x = x + 1; } while (x < 5); x";
+
+    let program = Program::try_from(input).unwrap();
+
+    assert_eq!(program.statements.len(), 2);
+
+    let do_while = program.statements[0]
+        .as_any()
+        .downcast_ref::<DoWhileStatement>()
+        .expect("statement is not DoWhileStatement");
+
+    assert_eq!(do_while.body.statements.len(), 1);
+    assert!(do_while.body.statements[0].as_any().is::<AssignStatement>());
+    test_infix_expression(do_while.condition.as_ref(), "x", "<", 5);
+
+    for input in [
+        "do { x } (x < 5);",
+        "do { x } while x < 5;",
+        "do x while (true);",
+    ] {
+        assert!(
+            Program::try_from(input).is_err(),
+            "{} should not parse",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_unless_expression() {
+    let input = "unless (x < y) { x } else { y }";
+
+    let program = Program::try_from(input).unwrap();
+
+    let stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<ExpressionStatement>()
+        .unwrap();
+    let exp = stmt
+        .expression
+        .as_any()
+        .downcast_ref::<IfExpression>()
+        .expect("expression is not IfExpression");
+
+    test_infix_expression(exp.condition.as_ref(), "x", "<", "y");
+    assert_eq!(exp.consequence.to_string(), "y");
+    assert_eq!(exp.alternative.as_ref().unwrap().to_string(), "x");
+
+    let program = Program::try_from("unless (x) { x }").unwrap();
+    assert_eq!(program.to_string(), "ifx else x");
+}
+
+#[test]
+fn test_match_expression() {
+    let input = r#"match (x) { 1 => "one", y => "y", _ => "other", }"#;
+
+    let program = Program::try_from(input).unwrap();
+
+    let stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<ExpressionStatement>()
+        .unwrap();
+    let exp = stmt
+        .expression
+        .as_any()
+        .downcast_ref::<MatchExpression>()
+        .expect("expression is not MatchExpression");
+
+    test_identifier(exp.subject.as_ref(), "x");
+    assert_eq!(exp.arms.len(), 3);
+    test_integer_literal(exp.arms[0].pattern.as_ref(), 1);
+    test_identifier(exp.arms[1].pattern.as_ref(), "y");
+    assert!(exp.arms[2].pattern.as_any().is::<WildcardExpression>());
+    assert_eq!(
+        program.to_string(),
+        "match (x) { 1 => one, y => y, _ => other }"
+    );
+
+    let errors = Program::try_from("match (x) { 1 2 }").unwrap_err();
+    assert_eq!(
+        errors[0],
+        "expected next token to be Arrow, got Int instead at line 1, column 15"
+    );
+}
+
+#[test]
+fn test_try_catch_expression() {
+    let input = "try { risky(); } catch (err) { error_message(err) }";
+
+    let program = Program::try_from(input).unwrap();
+
+    let stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<ExpressionStatement>()
+        .unwrap();
+    let exp = stmt
+        .expression
+        .as_any()
+        .downcast_ref::<TryCatchExpression>()
+        .expect("expression is not TryCatchExpression");
+
+    assert_eq!(exp.try_block.statements.len(), 1);
+    assert_eq!(exp.error_name.value, "err");
+    assert_eq!(exp.catch_block.statements.len(), 1);
+    assert_eq!(
+        program.to_string(),
+        "try risky() catch (err) error_message(err)"
+    );
+
+    let errors = Program::try_from("try { x } (err) { y }").unwrap_err();
+    assert_eq!(
+        errors[0],
+        "expected next token to be Catch, got Lparen instead at line 1, column 11"
+    );
+
+    for input in [
+        "try x catch (err) { y }",
+        "try { x } catch err { y }",
+        "try { x } catch (1) { y }",
+        "try { x } catch (err) y",
+    ] {
+        assert!(
+            Program::try_from(input).is_err(),
+            "{} should not parse",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_const_statement() {
+    let input = "const answer = 40 + 2;";
+
+    let program = Program::try_from(input).unwrap();
+
+    let stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<ConstStatement>()
+        .expect("statement is not ConstStatement");
+
+    assert_eq!(stmt.name.value, "answer");
+    test_infix_expression(stmt.value.as_ref(), 40, "+", 2);
+    assert_eq!(program.to_string(), "const answer = (40 + 2);");
+
+    let errors = Program::try_from("const = 5;").unwrap_err();
+    assert_eq!(
+        errors[0],
+        "expected next token to be Ident, got Assign instead at line 1, column 7"
+    );
+}
+
+#[test]
+fn test_assign_statement() {
+    let input = "x = y + 1; x == 1;";
+
+    let program = Program::try_from(input).unwrap();
+
+    assert_eq!(program.statements.len(), 2);
+
+    let stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<AssignStatement>()
+        .expect("statement is not AssignStatement");
+
+    assert_eq!(stmt.target.value, "x");
+    test_infix_expression(stmt.value.as_ref(), "y", "+", 1);
+    assert!(program.statements[1].as_any().is::<ExpressionStatement>());
+    assert_eq!(program.to_string(), "x = (y + 1);(x == 1)");
+}
+
+#[test]
+fn test_program_try_from() {
+    let program = Program::try_from("let x = 1; x + 2").unwrap();
+    assert_eq!(program.statements.len(), 2);
+
+    let errors = Program::try_from("let = 1;").unwrap_err();
+    assert_eq!(
+        errors,
+        ["expected next token to be Ident, got Assign instead at line 1, column 5"]
+    );
+
+    let program = ruskey::parse("fn(x) { x }(5)").unwrap();
+    assert_eq!(program.to_string(), "fn(x) x(5)");
+    assert!(ruskey::parse("(1 + 2").is_err());
+}
+
+#[test]
+fn test_program_to_tokens() {
+    let program = Program::try_from("let x = 5;").unwrap();
+    assert_eq!(program.statement_count(), 1);
+    assert!(!program.is_empty());
+
+    let kinds: Vec<TokenType> = program
+        .to_tokens()
+        .into_iter()
+        .map(|t| t.token_type)
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            TokenType::Let,
+            TokenType::Ident,
+            TokenType::Assign,
+            TokenType::Int,
+            TokenType::Semicolon,
+            TokenType::Eof,
+        ]
+    );
+
+    // statements stay separate even when printed without a separator
+    let program = Program::try_from("a\nb; return add(a, b);").unwrap();
+    assert_eq!(program.statement_count(), 3);
+    let literals: Vec<String> = program.to_tokens().into_iter().map(|t| t.literal).collect();
+    assert_eq!(
+        literals,
+        vec!["a", "b", "return", "add", "(", "a", ",", "b", ")", ";", ""]
+    );
+
+    let program = Program::try_from("").unwrap();
+    assert!(program.is_empty());
+    assert_eq!(program.statement_count(), 0);
+    assert_eq!(program.to_tokens().len(), 1);
+}
+
+#[test]
+fn test_program_to_tokens_round_trip() {
+    let inputs = [
+        r#"let s = "a b"; let t = "tab\there"; puts(s + t)"#,
+        r#"let h = {"one two": 1, 2: [3, 4]}; h["one two"]"#,
+        "if (x < 1) { let y = 2; y } else { x }",
+        "unless (done) { step() }",
+        "fn add(a, b) { return a + b; } add(1, 2)",
+        "let f = fn(first, ...rest) { rest };",
+        "(1 + 2) * 3 - -(4 - 5) + (a ?? b)",
+        "2 ** 3 ** 2\n(2 ** 3) ** 2",
+        "a - (b - c)\n(a - b) - c",
+        "(-x)[0]\n(a + b).len()\n(f || g)(1)",
+        "let [a, _, c] = xs; let {x, y: b} = point;",
+        "'outer: for (i in range(0, 3)) { while (true) { break 'outer; } }",
+        "do { i = i + 1; } while (i < 3);",
+        "while let x = next() { continue; }",
+        r#"match (n) { 1 => "one", _ => "other" }"#,
+        "try { risky() } catch (e) { error_message(e) }",
+        "arr[0] = arr[1] = 0x1F\ntypeof arr\nloop { break; }",
+        "f(...args, 1)\nconst k = 5; k = null;",
+    ];
+
+    for input in inputs {
+        let program = Program::try_from(input).unwrap();
+        let expected: Vec<(TokenType, String)> = tokenize(input)
+            .into_iter()
+            .map(|t| (t.token_type, t.literal))
+            .collect();
+        let actual: Vec<(TokenType, String)> = program
+            .to_tokens()
+            .into_iter()
+            .map(|t| (t.token_type, t.literal))
+            .collect();
+        assert_eq!(actual, expected, "tokens differ for {}", input);
+    }
+
+    // tokens kept by the nodes keep their source positions
+    let program = Program::try_from("let s = \"a b\";").unwrap();
+    let string = &program.to_tokens()[3];
+    assert_eq!(string.token_type, TokenType::String);
+    assert_eq!((string.line, string.column), (1, 9));
+}
+
+#[test]
+fn test_visitor() {
+    struct IdentifierCollector {
+        names: Vec<String>,
+    }
+
+    impl Visitor for IdentifierCollector {
+        fn visit_identifier(&mut self, ident: &Identifier) {
+            self.names.push(ident.value.clone());
+        }
+    }
+
+    let program =
+        Program::try_from("let add = fn(a, b) { a + b }; for (x in [1, 2]) { puts(add(x, y)); }")
+            .unwrap();
+    let mut collector = IdentifierCollector { names: vec![] };
+    collector.visit_program(&program);
+    assert_eq!(
+        collector.names,
+        vec!["add", "a", "b", "a", "b", "x", "puts", "add", "x", "y"]
+    );
+
+    let program = Program::try_from("let x = -1 + 2; if (x) { return true; }").unwrap();
+    let expected = "\
+Program
+  LetStatement
+    Identifier x
+    InfixExpression +
+      PrefixExpression -
+        IntegerLiteral 1
+      IntegerLiteral 2
+  ExpressionStatement
+    IfExpression
+      Identifier x
+      BlockStatement
+        ReturnStatement
+          Boolean true
+";
+    assert_eq!(PrintVisitor::print(&program), expected);
+}
+
+#[test]
+fn test_transformer() {
+    let input = "let f = fn(x) { [x + 1, 2 + 3] }; f(1 + 2 + x) * (3 + 4);";
+    let program = Program::try_from(input).unwrap();
+    let printed = program.to_string();
+    assert_eq!(
+        IdentityTransformer.transform_program(program).to_string(),
+        printed
+    );
+
+    let program = Program::try_from("(3 + 4) * 2").unwrap();
+    let program = ConstantFoldingTransformer.transform_program(program);
+    let stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<ExpressionStatement>()
+        .unwrap();
+    let infix = stmt
+        .expression
+        .as_any()
+        .downcast_ref::<InfixExpression>()
+        .unwrap();
+    assert_eq!(infix.operator, "*");
+    let left = infix
+        .left
+        .as_any()
+        .downcast_ref::<IntegerLiteral>()
+        .unwrap();
+    assert_eq!(left.value, 7);
+    assert_eq!(left.token.literal, "7");
+
+    let tests = vec![
+        ("1 + 2 + x", "(3 + x)"),
+        ("x + 1 + 2", "((x + 1) + 2)"),
+        ("fn() { return 1 + 1; }", "fn() return 2;"),
+        ("[1 + 1, -(2 + 2)]", "[2, (-4)]"),
+        ("1 - 2", "(1 - 2)"),
+        ("9223372036854775807 + 1", "(9223372036854775807 + 1)"),
+    ];
+
+    for (input, expected) in tests {
+        let program = Program::try_from(input).unwrap();
+        let folded = ConstantFoldingTransformer.transform_program(program);
+        assert_eq!(folded.to_string(), expected, "input: {}", input);
+    }
+}
+
+#[test]
+fn test_ast_node_round_trip() {
+    let input = r#"
+let add = fn(a, b) { a + b };
+let apply = fn(f, a, ...rest) { return f(a, a); };
+if (!(apply(add, 2) < -3)) { true } else { false }
+"#;
+    let program = Program::try_from(input).unwrap();
+
+    let node = AstNode::try_from(&program).unwrap();
+    assert_eq!(node.kind(), "Program");
+    let restored = Program::try_from(node).unwrap();
+
+    assert_eq!(restored.to_string(), program.to_string());
+    assert_eq!(format!("{:?}", restored), format!("{:?}", program));
+    assert_eq!(restored.statements[2].span(), program.statements[2].span());
+
+    let program = Program::try_from("let s = \"text\";").unwrap();
+    assert_eq!(
+        AstNode::try_from(&program).unwrap_err(),
+        "cannot convert expression to AstNode: text"
+    );
+
+    let node = AstNode::try_from(&Program::try_from("5").unwrap()).unwrap();
+    let AstNode::Program { mut statements } = node else {
+        panic!("expected a Program");
+    };
+    let stmt = statements.remove(0);
+    assert_eq!(
+        Program::try_from(stmt).unwrap_err(),
+        "expected a Program, got ExpressionStatement"
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_json_round_trip() {
+    let input = r#"
+let add = fn(a, b) { a + b };
+let apply = fn(f, a, ...rest) { return f(a, a); };
+if (!(apply(add, 2) < -3)) { true } else { false }
+"#;
+    let program = Program::try_from(input).unwrap();
+
+    let json = serde_json::to_string(&program).unwrap();
+    let restored: Program = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(restored, program);
+    assert_eq!(format!("{:?}", restored), format!("{:?}", program));
+    assert_eq!(serde_json::to_string(&restored).unwrap(), json);
+
+    // node types round trip on their own too
+    let stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<LetStatement>()
+        .unwrap();
+    let json = serde_json::to_string(stmt).unwrap();
+    let restored: LetStatement = serde_json::from_str(&json).unwrap();
+    assert_eq!(&restored, stmt);
+
+    let json = serde_json::to_string(
+        &program.statements[2]
+            .as_any()
+            .downcast_ref::<ExpressionStatement>()
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(serde_json::from_str::<LetStatement>(&json)
+        .unwrap_err()
+        .to_string()
+        .contains("expected a LetStatement, got ExpressionStatement"));
+
+    let program = Program::try_from("let s = \"text\";").unwrap();
+    assert!(serde_json::to_string(&program)
+        .unwrap_err()
+        .to_string()
+        .contains("cannot convert expression to AstNode: text"));
+}
+
+#[test]
+fn test_ast_equality() {
+    let input = r#"
+let add = fn(a, b) { return a + b; };
+const xs = [1, 2.5, "three", {"k": true}];
+for (x in xs) { if (typeof x == "INTEGER") { break; } else { puts(x.len()); } }
+match (add(1, 2)) { 3 => "three", _ => xs[0] }
+"#;
+    assert_eq!(
+        Program::try_from(input).unwrap(),
+        Program::try_from(input).unwrap()
+    );
+
+    // positions are not compared
+    assert_eq!(
+        Program::try_from("let x = 1 + 2;").unwrap(),
+        Program::try_from("let   x =\n  (1 + 2)").unwrap()
+    );
+
+    let tests = vec![
+        ("let x = 1;", "let y = 1;"),
+        ("1 + 2", "1 - 2"),
+        ("1", "1.0"),
+        ("1", "\"1\""),
+        ("f(a)", "f(a, b)"),
+        ("if (x) { 1 }", "if (x) { 1 } else { 2 }"),
+        ("x", "x; y"),
+    ];
+
+    for (left, right) in tests {
+        assert_ne!(
+            Program::try_from(left).unwrap(),
+            Program::try_from(right).unwrap(),
+            "{} == {}",
+            left,
+            right
+        );
+    }
+
+    let program = Program::try_from("-x").unwrap();
+    let stmt = program.statements[0]
+        .as_any()
+        .downcast_ref::<ExpressionStatement>()
+        .unwrap();
+    let expected = PrefixExpression {
+        token: Token::new(TokenType::Minus, "-".to_string()),
+        operator: "-".to_string(),
+        right: Box::new(Identifier {
+            token: Token::new(TokenType::Ident, "x".to_string()),
+            value: "x".to_string(),
+        }),
+    };
+    let prefix = stmt
+        .expression
+        .as_any()
+        .downcast_ref::<PrefixExpression>()
+        .unwrap();
+    assert_eq!(*prefix, expected);
+}
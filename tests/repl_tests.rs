@@ -1,4 +1,4 @@
-use ruskey::repl::Repl;
+use ruskey::repl::{is_complete_input, Repl};
 use std::io::Cursor;
 
 #[test]
@@ -14,3 +14,282 @@ fn test_repl_parser_functionality() {
 
     assert!(!output_str.is_empty());
 }
+
+#[test]
+fn test_repl_prints_stack_trace() {
+    let input = "let f = fn() { 1 + true }; f()\n".as_bytes();
+    let mut output = Vec::new();
+
+    let mut repl = Repl::new();
+    repl.start(&mut Cursor::new(input), &mut output).unwrap();
+
+    let output_str = String::from_utf8(output).unwrap();
+
+    assert!(output_str.contains("ERROR: type mismatch: INTEGER + BOOLEAN"));
+    assert!(output_str.contains("\n    in function f at line 1\n"));
+}
+
+#[test]
+fn test_repl_recurses_to_the_default_depth() {
+    // deeper than the 2 MiB stack of the test thread allows
+    let input = "let f = fn(n) { if (n == 0) { 0 } else { 1 + f(n - 1) } }; f(999)\n".as_bytes();
+    let mut output = Vec::new();
+
+    let mut repl = Repl::new();
+    repl.start(&mut Cursor::new(input), &mut output).unwrap();
+
+    let output_str = String::from_utf8(output).unwrap();
+
+    assert!(output_str.contains(">> 999\n"));
+}
+
+#[test]
+fn test_repl_collapses_repeated_frames() {
+    let input = "let f = fn(n) { 1 + f(n + 1) }; f(0)\n".as_bytes();
+    let mut output = Vec::new();
+
+    let mut repl = Repl::new();
+    repl.start(&mut Cursor::new(input), &mut output).unwrap();
+
+    let output_str = String::from_utf8(output).unwrap();
+
+    assert!(output_str.contains("ERROR: maximum recursion depth exceeded"));
+    assert!(output_str.contains("\n    in function f at line 1\n    ... repeated "));
+    assert_eq!(output_str.matches("in function").count(), 1);
+}
+
+#[test]
+fn test_repl_builtins_share_streams() {
+    let input =
+        "let name = input(\"name? \");\nAda\nputs(\"hello \" + name)\nreadline()\n".as_bytes();
+    let mut output = Vec::new();
+
+    let mut repl = Repl::new();
+    repl.start(&mut Cursor::new(input), &mut output).unwrap();
+
+    let output_str = String::from_utf8(output).unwrap();
+
+    assert!(output_str.contains(">> name? Ada\n"));
+    assert!(output_str.contains(">> hello Ada\nnull\n"));
+    // readline() finds the input exhausted
+    assert!(output_str.ends_with(">> null\n>> "));
+}
+
+#[test]
+fn test_repl_exit() {
+    let input = "exit(0)\nputs(\"unreachable\")\n".as_bytes();
+    let mut output = Vec::new();
+
+    let mut repl = Repl::new();
+    let code = repl.start(&mut Cursor::new(input), &mut output).unwrap();
+
+    let output_str = String::from_utf8(output).unwrap();
+
+    assert_eq!(code, 0);
+    assert!(output_str.ends_with(">> "));
+    assert!(!output_str.contains("unreachable"));
+
+    let input = "let f = fn() { exit(1) };\nf(); 5\n".as_bytes();
+    let mut output = Vec::new();
+
+    let code = repl.start(&mut Cursor::new(input), &mut output).unwrap();
+
+    assert_eq!(code, 1);
+    assert!(!String::from_utf8(output).unwrap().contains('5'));
+}
+
+#[test]
+fn test_repl_inspection_commands() {
+    let input = ":ast let x = 5;\n:tokens x + 1\n:ast let = ;\n:nope\nx\n".as_bytes();
+    let mut output = Vec::new();
+
+    let mut repl = Repl::new();
+    repl.start(&mut Cursor::new(input), &mut output).unwrap();
+
+    let output_str = String::from_utf8(output).unwrap();
+
+    assert!(output_str.contains("LetStatement"));
+    assert!(output_str.contains("value: 5"));
+    assert!(output_str.contains("Type:Ident, Literal:x\nType:Plus, Literal:+\n"));
+    assert!(output_str.contains("Parser errors:"));
+    assert!(output_str.contains("unknown command: :nope"));
+    // commands only inspect, so x was never bound
+    assert!(output_str.contains("ERROR: identifier not found: x"));
+}
+
+/// Writes `source` to a file in the temp directory, returning its path
+fn temp_script(name: &str, source: &str) -> String {
+    let path = std::env::temp_dir().join(format!("ruskey-{}-{}.mk", name, std::process::id()));
+    std::fs::write(&path, source).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+/// Runs the script at `path`, returning its exit code, output and errors
+fn run_file(path: &str, input: &str) -> (i64, String, String) {
+    let mut output = Vec::new();
+    let mut errors = Vec::new();
+    let code = Repl::new()
+        .run_file(
+            path,
+            &mut Cursor::new(input.as_bytes()),
+            &mut output,
+            &mut errors,
+        )
+        .unwrap();
+    (
+        code,
+        String::from_utf8(output).unwrap(),
+        String::from_utf8(errors).unwrap(),
+    )
+}
+
+#[test]
+fn test_repl_run_file() {
+    let path = temp_script(
+        "ok",
+        "let greet = fn(name) {\n  puts(\"hello \" + name);\n};\ngreet(input());\n42\n",
+    );
+    assert_eq!(
+        run_file(&path, "Ada\n"),
+        (0, "hello Ada\n".to_string(), String::new())
+    );
+
+    let path = temp_script("exit", "puts(1);\nexit(3);\nputs(2);\n");
+    assert_eq!(run_file(&path, ""), (3, "1\n".to_string(), String::new()));
+
+    let path = temp_script("error", "puts(1);\nlet f = fn() { 1 + true };\nf();\n");
+    let (code, output, errors) = run_file(&path, "");
+    assert_eq!(code, 1);
+    assert_eq!(output, "1\n");
+    assert!(errors.starts_with("ERROR: type mismatch: INTEGER + BOOLEAN"));
+    assert!(errors.contains("\n    in function f at line 2\n"));
+
+    let path = temp_script("parse", "let = 5;\n");
+    let (code, output, errors) = run_file(&path, "");
+    assert_eq!(code, 1);
+    assert!(output.is_empty());
+    assert!(errors.starts_with("Parser errors:\n"));
+
+    let (code, output, errors) = run_file("/no/such/script.mk", "");
+    assert_eq!(code, 1);
+    assert!(output.is_empty());
+    assert!(errors.starts_with("could not read /no/such/script.mk: "));
+}
+
+#[test]
+fn test_repl_multiline_input() {
+    let input = "fn add(x, y) {\n  x + y\n}\nadd(2, 3)\nlet xs = [\n1,\n2];\nxs\n".as_bytes();
+    let mut output = Vec::new();
+
+    let mut repl = Repl::new();
+    repl.start(&mut Cursor::new(input), &mut output).unwrap();
+
+    let output_str = String::from_utf8(output).unwrap();
+
+    assert!(!output_str.contains("Parser errors"));
+    assert!(output_str.contains(">> ... ... >> 5\n"));
+    assert!(output_str.contains("[1, 2]\n"));
+}
+
+#[test]
+fn test_is_complete_input() {
+    let tests = vec![
+        ("let x = 5;", true),
+        ("fn add(x, y) {", false),
+        ("fn add(x, y) {\n  x + y\n}", true),
+        ("puts(\"{\"", false),
+        ("puts(\"{\")", true),
+        ("[1, [2, 3]", false),
+        ("}", true),
+        ("", true),
+    ];
+
+    for (input, expected) in tests {
+        assert_eq!(is_complete_input(input), expected, "input: {:?}", input);
+    }
+}
+
+#[test]
+fn test_repl_save_and_load() {
+    let path = temp_script("session", "");
+
+    let input = format!(
+        "let x = 5;\nx + 1;\nlet f = fn(y) {{\n  y * 2\n}};\nx + true\n:save {}\n",
+        path
+    );
+    let mut output = Vec::new();
+    Repl::new()
+        .start(&mut Cursor::new(input.as_bytes()), &mut output)
+        .unwrap();
+
+    // inputs that fail are not saved
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "let x = 5;\nx + 1;\nlet f = fn(y) {\n  y * 2\n};\n"
+    );
+
+    let input = format!(":load {}\nf(x + 1)\n:load /no/such/session\n", path);
+    let mut output = Vec::new();
+    Repl::new()
+        .start(&mut Cursor::new(input.as_bytes()), &mut output)
+        .unwrap();
+
+    let output_str = String::from_utf8(output).unwrap();
+    assert!(output_str.contains(">> 5\n6\n"));
+    assert!(output_str.contains(">> 12\n"));
+    assert!(output_str.contains("could not read /no/such/session: "));
+}
+
+#[test]
+fn test_repl_env_and_reset() {
+    let input = "let y = 10;\nlet x = 5;\n:env\n:reset\nx\n:env\nlet z = 1;\n:env\n".as_bytes();
+    let mut output = Vec::new();
+
+    let mut repl = Repl::new();
+    repl.start(&mut Cursor::new(input), &mut output).unwrap();
+
+    let output_str = String::from_utf8(output).unwrap();
+
+    assert!(output_str.contains(">> x = 5\ny = 10\n>> Environment cleared.\n"));
+    assert!(output_str.contains(">> ERROR: identifier not found: x at line 1"));
+    assert!(output_str.contains(">> >> 1\n>> z = 1\n"));
+}
+
+#[test]
+fn test_repl_timing() {
+    let input = "1\n:timing on\n2\n:timing off\n3\n:timing\n".as_bytes();
+    let mut output = Vec::new();
+
+    let mut repl = Repl::new();
+    repl.start(&mut Cursor::new(input), &mut output).unwrap();
+
+    let output_str = String::from_utf8(output).unwrap();
+
+    assert_eq!(output_str.matches("// evaluated in ").count(), 1);
+    assert!(output_str.contains(">> 1\n>> >> 2\n// evaluated in "));
+    assert!(output_str.contains(">> >> 3\n>> usage: :timing on|off\n"));
+}
+
+#[test]
+fn test_repl_custom_prompt() {
+    let input = "let x = 5;\nx * 2\nfn() {\n1 }\n".as_bytes();
+    let mut output = Vec::new();
+
+    let mut repl = Repl::with_prompt("$ ");
+    repl.set_continuation_prompt("| ");
+    repl.start(&mut Cursor::new(input), &mut output).unwrap();
+
+    let output_str = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = output_str.lines().skip(2).collect();
+
+    assert_eq!(lines, vec!["$ 5", "$ 10", "$ | $ "]);
+
+    let mut output = Vec::new();
+    let mut repl = Repl::new();
+    repl.set_prompt("ruskey> ");
+    repl.start(&mut Cursor::new("1\n".as_bytes()), &mut output)
+        .unwrap();
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .ends_with("ruskey> 1\nruskey> "));
+}
@@ -24,6 +24,7 @@ fn test_all_token_types() {
         (TokenType::Bang, "!"),
         (TokenType::Asterisk, "*"),
         (TokenType::Slash, "/"),
+        (TokenType::Percent, "%"),
         (TokenType::Lt, "<"),
         (TokenType::Gt, ">"),
         (TokenType::Eq, "=="),